@@ -0,0 +1,65 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::rules::Finding;
+use crate::util::write_atomic;
+
+/// Lint findings accepted by `oav lint --update-baseline`, kept next to `.oavc`.
+pub const FILE_NAME: &str = ".oav-baseline.json";
+
+/// One accepted finding of a lint check (`redocly`, `governance`, a plugin, ...).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(deny_unknown_fields)]
+pub struct Entry {
+    pub check: String,
+    pub rule: String,
+    /// JSON pointer into the spec, or `file:line:column`.
+    pub path: String,
+    pub message: String,
+}
+
+impl Entry {
+    pub fn new(check: &str, finding: &Finding) -> Self {
+        Self {
+            check: check.to_string(),
+            rule: finding.rule.clone(),
+            path: finding.path.clone(),
+            message: finding.message.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct File {
+    findings: Vec<Entry>,
+}
+
+/// The accepted findings. No file means an empty baseline.
+pub fn load(root: &Path) -> Result<Vec<Entry>> {
+    let path = root.join(FILE_NAME);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {FILE_NAME}"))?;
+    let file: File =
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {FILE_NAME}"))?;
+    Ok(file.findings)
+}
+
+/// Replace the baseline with `findings`, sorted so diffs stay small.
+/// Returns how many it holds.
+pub fn write(root: &Path, mut findings: Vec<Entry>) -> Result<usize> {
+    findings.sort();
+    findings.dedup();
+    let count = findings.len();
+    write_atomic(
+        &root.join(FILE_NAME),
+        &serde_json::to_string_pretty(&File { findings })?,
+    )
+    .with_context(|| format!("Failed to write {FILE_NAME}"))?;
+    Ok(count)
+}
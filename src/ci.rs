@@ -0,0 +1,161 @@
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::Path;
+
+use crate::cli::{CiProvider, Mode};
+use crate::config::Config;
+use crate::output::Output;
+
+const INSTALL_URL: &str =
+    "https://raw.githubusercontent.com/entur/openapi-validator-cli/main/install.sh";
+
+pub fn init(
+    root: &Path,
+    output: &Output,
+    config: &Config,
+    provider: CiProvider,
+    force: bool,
+) -> Result<()> {
+    let (path, content) = match provider {
+        CiProvider::Github => (
+            root.join(".github").join("workflows").join("oav.yml"),
+            github_workflow(config),
+        ),
+        CiProvider::Gitlab => (root.join(".gitlab-ci.yml"), gitlab_job(config)),
+    };
+
+    if path.exists() && !force {
+        bail!(
+            "{} already exists. Use --force to overwrite it.",
+            path.display()
+        );
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    output.println(&format!("Wrote {}", path.display()));
+    Ok(())
+}
+
+/// The `oav validate` invocation for the pipeline, pinned to the current `.oavc`
+/// so the job behaves the same even when `.oavc` is gitignored.
+fn validate_command(config: &Config) -> String {
+    let mut args = vec!["oav".to_string(), "validate".to_string()];
+    if let Some(spec) = &config.spec {
+        args.push(format!("--spec {}", shell_quote(spec)));
+    }
+    args.push(format!("--mode {}", config.mode.as_str()));
+    if matches!(config.mode, Mode::Server | Mode::Both) && !config.server_generators.is_empty() {
+        args.push(format!(
+            "--server-generators {}",
+            shell_quote(&config.server_generators.join(","))
+        ));
+    }
+    if matches!(config.mode, Mode::Client | Mode::Both) && !config.client_generators.is_empty() {
+        args.push(format!(
+            "--client-generators {}",
+            shell_quote(&config.client_generators.join(","))
+        ));
+    }
+    if !config.lint {
+        args.push("--skip-lint".to_string());
+    }
+    if !config.generate {
+        args.push("--skip-generate".to_string());
+    }
+    if !config.compile {
+        args.push("--skip-compile".to_string());
+    }
+    args.join(" ")
+}
+
+/// `word` as a single POSIX shell word, quoted only when it needs to be.
+fn shell_quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "@%+=:,./_-".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+fn github_workflow(config: &Config) -> String {
+    let version = env!("CARGO_PKG_VERSION");
+    let command = validate_command(config);
+    format!(
+        r#"# Generated by `oav ci init`.
+name: openapi-validation
+
+on:
+  push:
+    branches: [main]
+  pull_request:
+
+jobs:
+  oav:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+
+      - name: Cache oav
+        id: cache-oav
+        uses: actions/cache@v4
+        with:
+          path: ~/.local/bin/oav
+          key: oav-{version}-${{{{ runner.os }}}}
+
+      - name: Install oav
+        if: steps.cache-oav.outputs.cache-hit != 'true'
+        run: curl -fsSL {INSTALL_URL} | OAV_VERSION={version} OAV_INSTALL_DIR="$HOME/.local/bin" bash
+
+      - name: Validate OpenAPI spec
+        run: |
+          export PATH="$HOME/.local/bin:$PATH"
+          {command}
+
+      - name: Upload reports
+        if: always()
+        uses: actions/upload-artifact@v4
+        with:
+          name: oav-reports
+          path: .oav/reports
+"#
+    )
+}
+
+fn gitlab_job(config: &Config) -> String {
+    let version = env!("CARGO_PKG_VERSION");
+    let command = validate_command(config);
+    format!(
+        r#"# Generated by `oav ci init --provider gitlab`.
+oav:
+  image: docker:27
+  services:
+    - docker:27-dind
+  variables:
+    DOCKER_TLS_CERTDIR: ""
+    DOCKER_HOST: tcp://docker:2375
+    OAV_VERSION: "{version}"
+    OAV_INSTALL_DIR: $CI_PROJECT_DIR/.oav-bin
+  cache:
+    key: oav-{version}
+    paths:
+      - .oav-bin/
+  before_script:
+    - apk add --no-cache bash curl gcompat
+    - test -x .oav-bin/oav || (curl -fsSL {INSTALL_URL} | bash)
+    - export PATH="$CI_PROJECT_DIR/.oav-bin:$PATH"
+  script:
+    - {command}
+  artifacts:
+    when: always
+    paths:
+      - .oav/reports/
+"#
+    )
+}
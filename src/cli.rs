@@ -1,71 +1,433 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::ArgValueCandidates;
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+use crate::completions;
 
 #[derive(Parser, Debug)]
 #[command(name = "oav", version, about = "OpenAPI Validator CLI")]
 pub struct Cli {
+    /// Stream full tool output
     #[arg(short, long, global = true, conflicts_with = "quiet")]
     pub verbose: bool,
+    /// Minimal output (still prints final locations)
     #[arg(short, long, global = true, conflicts_with = "verbose")]
     pub quiet: bool,
+    /// Write pipeline events as JSON lines to FILE, or to stdout with -
+    #[arg(long, global = true, value_name = "FILE")]
+    pub events: Option<String>,
     #[command(subcommand)]
     pub command: Commands,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
+    /// Create .oav/, scaffold .oavc, and add gitignore entries
     Init {
+        /// Path to the OpenAPI spec (with --from-url, where to save it)
         #[arg(long)]
         spec: Option<String>,
+        /// Download the spec from this URL and point .oavc at it
+        #[arg(long)]
+        from_url: Option<String>,
+        /// Start from a generator set for a common stack; other flags override it
+        #[arg(long, value_enum)]
+        preset: Option<Preset>,
+        /// Generate servers, clients, or both
         #[arg(long)]
         mode: Option<Mode>,
-        #[arg(long, value_delimiter = ',')]
+        /// Comma-separated server generators (default: all supported)
+        #[arg(long, value_delimiter = ',', add = ArgValueCandidates::new(completions::server_generators))]
         server_generators: Option<Vec<String>>,
-        #[arg(long, value_delimiter = ',')]
+        /// Comma-separated client generators (default: all supported)
+        #[arg(long, value_delimiter = ',', add = ArgValueCandidates::new(completions::client_generators))]
         client_generators: Option<Vec<String>>,
+        /// Add .oavc to .gitignore
         #[arg(long)]
         ignore_config: bool,
     },
+    /// Run lint, generate, and compile, and write reports
     Validate {
+        #[command(flatten)]
+        overrides: Overrides,
+        /// Regenerate every target even if its inputs are unchanged
+        #[arg(long)]
+        no_cache: bool,
+        /// Stop at the first failed task instead of running everything
+        #[arg(long)]
+        fail_fast: bool,
+        /// Fail on lint and generator warnings and ignore allowed_failures and severity
+        #[arg(long)]
+        strict: bool,
+        /// Wait for another run in this workspace to finish instead of failing
+        #[arg(long)]
+        wait: bool,
+        /// How to print the results; compact prints one file:line:column line per finding
+        #[arg(long, value_enum, default_value_t = OutputFormat::Default)]
+        format: OutputFormat,
+    },
+    /// Run only the lint step, optionally fixing what can be fixed safely first
+    Lint {
+        /// Path to the OpenAPI spec
         #[arg(long)]
         spec: Option<String>,
+        /// Accept the current findings: write them to .oav-baseline.json
         #[arg(long)]
-        mode: Option<Mode>,
-        #[arg(long, value_delimiter = ',')]
-        server_generators: Option<Vec<String>>,
-        #[arg(long, value_delimiter = ',')]
-        client_generators: Option<Vec<String>>,
+        update_baseline: bool,
+        /// Apply safe fixes to the spec first, then lint the result
+        #[arg(long)]
+        fix: bool,
+        /// With --fix, write the fixed spec here instead of over the spec
+        #[arg(long, requires = "fix")]
+        out: Option<PathBuf>,
+        /// Fail on lint warnings and ignore severity
+        #[arg(long)]
+        strict: bool,
+        /// Wait for another run in this workspace to finish instead of failing
+        #[arg(long)]
+        wait: bool,
+        /// How to print the results; compact prints one file:line:column line per finding
+        #[arg(long, value_enum, default_value_t = OutputFormat::Default)]
+        format: OutputFormat,
+    },
+    /// Run only generation, optionally copying the generated code out of .oav
+    Generate {
+        /// Path to the OpenAPI spec
+        #[arg(long)]
+        spec: Option<String>,
+        /// Generators to run, server or client (default: the configured generators)
+        #[arg(long, value_delimiter = ',', add = ArgValueCandidates::new(completions::generators))]
+        only: Vec<String>,
+        /// Copy generated code to <dir>/<scope>/<generator>
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Fail on generator warnings and ignore allowed_failures and severity
+        #[arg(long)]
+        strict: bool,
+        /// Regenerate every target even if its inputs are unchanged
+        #[arg(long)]
+        no_cache: bool,
+        /// Wait for another run in this workspace to finish instead of failing
+        #[arg(long)]
+        wait: bool,
+    },
+    /// Rebuild the dashboard and summaries from stored results without running anything
+    Report {
+        /// Run to report on (a file name under .oav/results, without .json; default: latest)
+        #[arg(long)]
+        run: Option<String>,
+        /// Also write .oav/reports/report.pdf with the summary, tasks, and findings
+        #[arg(long)]
+        pdf: bool,
+        #[command(subcommand)]
+        command: Option<ReportCommand>,
+    },
+    /// Show the runs kept under .oav/history
+    Runs {
+        #[command(subcommand)]
+        command: RunsCommand,
+    },
+    /// Upload a run's manifest and results, and optionally its reports, to a central endpoint
+    Publish {
+        /// URL to upload to (default: publish_endpoint)
+        #[arg(long)]
+        endpoint: Option<String>,
+        /// Run to publish: a run ID, latest, or a results JSON file (default: latest)
+        #[arg(long)]
+        run: Option<String>,
+        /// Also upload .oav/reports as a zip archive (default: publish_reports)
         #[arg(long)]
-        skip_lint: bool,
+        reports: bool,
+    },
+    /// Serve a mock of the spec with Prism until interrupted
+    Mock {
+        /// Path to the OpenAPI spec
+        #[arg(long)]
+        spec: Option<String>,
+        /// Host port to listen on (default: mock_port, 4010)
         #[arg(long)]
-        skip_generate: bool,
+        port: Option<u16>,
+        /// Generate random responses from the schemas instead of using examples
         #[arg(long)]
-        skip_compile: bool,
+        dynamic: bool,
     },
+    /// Serve a Language Server on stdin/stdout with diagnostics from the native checks
+    Lsp {
+        /// Also lint with Redocly when saves pause (needs a container engine)
+        #[arg(long)]
+        lint: bool,
+    },
+    /// Serve .oav/reports over HTTP, reloading pages when the reports are regenerated
+    Serve {
+        /// Run the API daemon instead: POST /validate, GET /runs/<id>, GET /report/<id>
+        #[arg(long)]
+        daemon: bool,
+        /// Port to listen on (default: serve_port, 4011)
+        #[arg(long)]
+        port: Option<u16>,
+        /// Address to serve the reports on, e.g. 0.0.0.0 inside a container
+        #[arg(long, default_value = "127.0.0.1", conflicts_with = "daemon")]
+        host: IpAddr,
+    },
+    /// Rewrite the spec with conventional key order and consistent indentation
+    Fmt {
+        /// Path to the OpenAPI spec
+        #[arg(long)]
+        spec: Option<String>,
+        /// Only report whether the spec is formatted; exit 1 if it isn't
+        #[arg(long)]
+        check: bool,
+        /// Sort paths by route
+        #[arg(long)]
+        sort_paths: bool,
+        /// Sort the top-level tags by name
+        #[arg(long)]
+        sort_tags: bool,
+    },
+    /// Show or change .oavc settings
     Config {
         #[command(subcommand)]
         command: Option<ConfigCommand>,
     },
-    Clean,
+    /// Remove the .oav directory, or parts of it
+    Clean {
+        /// Remove reports, run results and history, status.tsv, and run.json
+        #[arg(long)]
+        reports: bool,
+        /// Remove generated code
+        #[arg(long)]
+        generated: bool,
+        /// Remove generation fingerprints, update checks, and compile_cache_dir when it is inside .oav
+        #[arg(long)]
+        cache: bool,
+        /// Remove compile_cache_dir, even when it is outside .oav
+        #[arg(long)]
+        compile_cache: bool,
+        /// Remove all of .oav (the default)
+        #[arg(long, conflicts_with_all = ["reports", "generated", "cache"])]
+        all: bool,
+        /// List what would be removed and the space it takes, without removing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Manage git hooks that run oav before commits or pushes
+    Hook {
+        #[command(subcommand)]
+        command: HookCommand,
+    },
+    /// Scaffold CI pipelines
+    Ci {
+        #[command(subcommand)]
+        command: CiCommand,
+    },
+    /// Write man pages for oav and its subcommands
+    Man {
+        /// Directory to write the pages to
+        #[arg(long, default_value = ".")]
+        out: PathBuf,
+    },
+    /// Print a shell completion script
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+}
+
+/// Settings `validate` takes on the command line, on top of `.oavc` and
+/// `OAV_*` variables.
+#[derive(Args, Debug)]
+pub struct Overrides {
+    /// Apply this profile from .oavc on top of the rest of the file
+    #[arg(long)]
+    pub profile: Option<String>,
+    /// Path to the OpenAPI spec
+    #[arg(long)]
+    pub spec: Option<String>,
+    /// Generate servers, clients, or both
+    #[arg(long)]
+    pub mode: Option<Mode>,
+    /// Comma-separated server generators (default: all supported)
+    #[arg(long, value_delimiter = ',', add = ArgValueCandidates::new(completions::server_generators))]
+    pub server_generators: Option<Vec<String>>,
+    /// Comma-separated client generators (default: all supported)
+    #[arg(long, value_delimiter = ',', add = ArgValueCandidates::new(completions::client_generators))]
+    pub client_generators: Option<Vec<String>>,
+    /// Skip the lint step
+    #[arg(long)]
+    pub skip_lint: bool,
+    /// Skip the security step
+    #[arg(long)]
+    pub skip_security: bool,
+    /// Skip the generate step
+    #[arg(long)]
+    pub skip_generate: bool,
+    /// Skip the compile step
+    #[arg(long)]
+    pub skip_compile: bool,
+    /// Skip the test step
+    #[arg(long)]
+    pub skip_test: bool,
+    /// Skip the smoke step
+    #[arg(long)]
+    pub skip_smoke: bool,
+    /// Skip the contract step
+    #[arg(long)]
+    pub skip_contract: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    /// Progress, a summary, and where the dashboard is
+    Default,
+    /// Only the findings, as `file:line:column: severity[rule] message`
+    Compact,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CiCommand {
+    /// Write a CI pipeline that runs `oav validate` with the current .oavc settings
+    Init {
+        #[arg(long, value_enum, default_value_t = CiProvider::Github)]
+        provider: CiProvider,
+        /// Overwrite an existing pipeline file
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum CiProvider {
+    Github,
+    Gitlab,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HookCommand {
+    /// Install a git hook running lint only (or the full pipeline with --full)
+    Install {
+        #[arg(value_enum, default_value_t = HookKind::PreCommit)]
+        kind: HookKind,
+        /// Run lint, generate, and compile instead of lint only
+        #[arg(long)]
+        full: bool,
+        /// Overwrite an existing hook that was not installed by oav
+        #[arg(long)]
+        force: bool,
+        /// Remove a previously installed oav hook
+        #[arg(long, conflicts_with_all = ["full", "force"])]
+        uninstall: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ReportCommand {
+    /// Compare two stored runs: tasks that regressed or were fixed, and new or resolved findings
+    Diff {
+        /// Earlier run: a run ID, `latest`, or a results JSON file
+        before: String,
+        /// Later run: a run ID, `latest`, or a results JSON file
+        after: String,
+        /// Markdown file to write (default: .oav/reports/diff.md)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RunsCommand {
+    /// List kept runs, newest first, with their pass and fail counts
+    List,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum HookKind {
+    PreCommit,
+    PrePush,
+}
+
+impl HookKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookKind::PreCommit => "pre-commit",
+            HookKind::PrePush => "pre-push",
+        }
+    }
 }
 
 #[derive(Subcommand, Debug)]
 pub enum ConfigCommand {
     /// Get a config value. Use dot notation for map keys (e.g., generator_overrides.spring)
     Get {
+        #[arg(add = ArgValueCandidates::new(completions::config_keys))]
         key: String,
     },
     /// Set a config value. Use dot notation for map keys (e.g., generator_overrides.spring)
     Set {
+        #[arg(add = ArgValueCandidates::new(completions::config_keys))]
         key: String,
+        #[arg(allow_hyphen_values = true)]
         value: String,
     },
+    /// Add values to a generator list or allowed_content_types
+    Add {
+        #[arg(add = ArgValueCandidates::new(completions::config_keys))]
+        key: String,
+        #[arg(required = true)]
+        values: Vec<String>,
+    },
+    /// Remove values from a generator list or allowed_content_types
+    Remove {
+        #[arg(add = ArgValueCandidates::new(completions::config_keys))]
+        key: String,
+        #[arg(required = true)]
+        values: Vec<String>,
+    },
+    /// Open .oavc in $EDITOR
     Edit,
+    /// Print .oavc (the default)
     Print,
+    /// Report unknown keys and invalid values in .oavc
+    Check,
+    /// Rewrite .oavc to the current config schema, showing the changes
+    Migrate {
+        /// Show the changes without writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Print the effective config and where each value comes from
+    Resolve {
+        /// Flags to resolve as `oav validate` would receive them
+        #[command(flatten)]
+        overrides: Overrides,
+    },
+    /// Add .oavc to .gitignore
     Ignore,
+    /// Remove .oavc from .gitignore
     Unignore,
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Preset {
+    /// spring and kotlin-spring servers, java and kotlin clients
+    Jvm,
+    /// typescript-nestjs server, typescript-axios, -fetch, and -node clients
+    Typescript,
+    /// go-server server, go client
+    Go,
+    /// Every supported server and client generator
+    Full,
+}
+
 #[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Mode {
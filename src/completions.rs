@@ -0,0 +1,70 @@
+use anyhow::{Result, anyhow};
+use clap::ValueEnum;
+use clap_complete::CompletionCandidate;
+use clap_complete::env::Shells;
+use std::io::{self, Write};
+
+use crate::cli::Shell;
+use crate::config::{self, GENERATOR_GROUPS};
+use crate::steps::{SUPPORTED_CLIENT_GENERATORS, SUPPORTED_SERVER_GENERATORS};
+
+/// Variable that makes `oav` print completions instead of running, as set
+/// by the completion scripts.
+pub const VAR: &str = "COMPLETE";
+
+/// Print the completion script for `shell`. Scripts call back into the
+/// installed `oav` (with `COMPLETE=<shell>`), so candidates (subcommands,
+/// flags, generator names, config keys) always match the binary.
+pub fn write_script(shell: Shell) -> Result<()> {
+    let name = shell
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default();
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&name)
+        .ok_or_else(|| anyhow!("No completion support for {name}"))?;
+    let mut stdout = io::stdout().lock();
+    completer.write_registration(VAR, "oav", "oav", "oav", &mut stdout)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Server generators and generator groups, for `--server-generators`.
+pub fn server_generators() -> Vec<CompletionCandidate> {
+    candidates(SUPPORTED_SERVER_GENERATORS.iter().copied())
+}
+
+/// Client generators and generator groups, for `--client-generators`.
+pub fn client_generators() -> Vec<CompletionCandidate> {
+    candidates(SUPPORTED_CLIENT_GENERATORS.iter().copied())
+}
+
+/// All generators and generator groups, for `generate --only`.
+pub fn generators() -> Vec<CompletionCandidate> {
+    candidates(
+        SUPPORTED_SERVER_GENERATORS
+            .iter()
+            .chain(&SUPPORTED_CLIENT_GENERATORS)
+            .copied(),
+    )
+}
+
+/// Keys accepted by `oav config get`/`set`/`add`/`remove`.
+pub fn config_keys() -> Vec<CompletionCandidate> {
+    config::keys()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+fn candidates<'a>(generators: impl Iterator<Item = &'a str>) -> Vec<CompletionCandidate> {
+    generators
+        .map(|name| CompletionCandidate::new(name.to_string()))
+        .chain(
+            GENERATOR_GROUPS
+                .iter()
+                .map(|(group, _)| CompletionCandidate::new(format!("@{group}"))),
+        )
+        .collect()
+}
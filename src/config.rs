@@ -1,83 +1,1218 @@
 use anyhow::{Context, Result, bail};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use crate::cli::Mode;
+use crate::cli::{Mode, Preset};
+use crate::docker;
+use crate::lifecycle;
+use crate::steps::{SUPPORTED_CLIENT_GENERATORS, SUPPORTED_SERVER_GENERATORS};
+use crate::util::{self, parse_duration};
 
 pub const CONFIG_FILE: &str = ".oavc";
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(default)]
 pub struct Config {
+    /// Schema version of the file; see [`CONFIG_VERSION`].
+    pub version: u32,
     pub spec: Option<String>,
+    /// Substitute `${VAR}` placeholders into `.oav/effective-spec.yaml` first.
+    pub spec_template: bool,
+    /// YAML map of placeholder values, used where the environment has none.
+    pub spec_values: Option<String>,
+    /// Steps (`generate`, `generate.<generator>`, `contract`, `mock`) that read
+    /// a 3.0 conversion of an OpenAPI 3.1 spec.
+    pub downconvert: Vec<String>,
     pub mode: Mode,
     pub lint: bool,
+    /// Audit the spec against the OWASP API Security rules after linting.
+    pub security: bool,
     pub generate: bool,
     pub compile: bool,
+    /// Run the generated projects' own test suites after compiling.
+    pub test: bool,
+    /// Boot generated servers and probe `smoke_path` after compiling.
+    pub smoke: bool,
+    pub smoke_path: String,
+    /// Run Schemathesis against `contract_url`, or booted generated servers.
+    pub contract: bool,
+    pub contract_url: Option<String>,
+    /// Extra `schemathesis run` arguments, e.g. `[--checks, all]`.
+    pub contract_args: Vec<String>,
     pub server_generators: Vec<String>,
     pub client_generators: Vec<String>,
+    /// Generators compile builds, when only some of the generated ones should gate.
+    pub compile_server_generators: Option<Vec<String>>,
+    pub compile_client_generators: Option<Vec<String>>,
+    /// User-defined groups, selectable as `@<name>` in generator lists.
+    pub generator_groups: BTreeMap<String, Vec<String>>,
     pub generator_overrides: HashMap<String, String>,
+    /// Directory with `server/` and `client/` generator configs that take
+    /// precedence over `.oav/generators`.
+    pub generator_config_dir: Option<String>,
+    /// `additionalProperties` merged into each generator's config, by generator.
+    pub generator_properties: BTreeMap<String, Mapping>,
+    /// Custom template directory per generator, passed as `templateDir`.
+    pub templates: BTreeMap<String, String>,
+    /// Generators outside the built-in lists, by name.
+    pub custom_generators: BTreeMap<String, CustomGenerator>,
+    /// Containers run between the built-in stages, by name.
+    pub custom_steps: BTreeMap<String, CustomStep>,
     pub generator_image: String,
+    /// More generator images, or tags of `generator_image`, to generate with.
+    pub generator_matrix: Vec<String>,
+    /// `JAVA_OPTS` of the generator container, e.g. `-Xmx4g`.
+    pub generator_java_opts: Option<String>,
     pub redocly_image: String,
+    pub schemathesis_image: String,
+    /// Prism image `oav mock` serves the spec with.
+    pub prism_image: String,
+    /// Host port `oav mock` listens on.
+    pub mock_port: u16,
+    /// Port `oav serve` listens on, for the reports or the API daemon.
+    pub serve_port: u16,
+    pub lint_config: Option<String>,
+    /// Extra `redocly lint` arguments, e.g. `[--skip-rule, operation-4xx-response]`.
+    pub lint_args: Vec<String>,
+    /// Check the spec files' YAML (tabs, duplicate keys, anchors) before linting.
+    pub yaml_style: bool,
+    /// Spell-check `summary` and `description` fields with cspell while linting.
+    pub spellcheck: bool,
+    /// Words file accepted by the spell check, one per line.
+    pub spellcheck_dictionary: Option<String>,
+    /// cspell image the spell check runs.
+    pub cspell_image: String,
+    /// Directory of `*.wasm` rule plugins run with the native checks.
+    pub plugin_dir: Option<String>,
     pub manage_gitignore: bool,
+    pub allowed_content_types: Vec<String>,
+    /// Generators whose failures are reported without failing the run.
+    pub allowed_failures: Vec<String>,
+    pub artifact_url_template: Option<String>,
+    /// Also write run metrics here in Prometheus textfile format.
+    pub metrics_textfile: Option<String>,
+    /// Runs whose results and reports are kept under `.oav/history`.
+    pub history_runs: usize,
+    /// Left-hand text of `badge.svg`.
+    pub badge_label: String,
+    pub badge_style: BadgeStyle,
+    /// Where `oav validate` posts the run summary, and when.
+    pub notifications: Notifications,
+    /// Where `oav publish` uploads runs; `${VAR}`s are expanded.
+    pub publish_endpoint: Option<String>,
+    /// Also upload `.oav/reports` as a gzipped tarball with `oav publish`.
+    pub publish_reports: bool,
+    pub compile_cache: bool,
+    pub compile_cache_dir: Option<String>,
+    pub warm_builders: bool,
+    pub generate_batch: bool,
+    pub timeouts: HashMap<String, String>,
+    /// Fail on lint and generator warnings, ignoring `allowed_failures` and `severity`.
+    pub strict: bool,
+    /// `fail` or `warn` per stage; failures of `warn` stages don't fail the run.
+    pub severity: HashMap<String, String>,
+    /// `off`, `warn`, or `error` per governance or security rule, over
+    /// [`GOVERNANCE_RULES`] and [`SECURITY_RULES`].
+    pub rules: HashMap<String, String>,
+    /// Casing the `naming` check enforces per kind of name.
+    pub naming: Naming,
+    /// Query parameters that page a list endpoint, for the `pagination` rule.
+    pub pagination_params: Vec<String>,
+    /// Shell command per lifecycle event (`pre_validate`, `post_lint`, ...).
+    pub hooks: HashMap<String, String>,
+    pub container_engine: EngineKind,
+    pub docker_context: Option<String>,
+    /// Lint natively instead of failing when no container engine responds.
+    pub offline_fallback: bool,
+    pub platform: Option<String>,
+    pub image_update_check: bool,
+    pub container_env: HashMap<String, String>,
+    pub docker_args: HashMap<String, Vec<String>>,
+    pub selinux_label: SelinuxLabel,
+    pub container_user: String,
+    pub container_memory: Option<String>,
+    pub container_cpus: Option<String>,
+    /// Named sets of keys `--profile` applies on top of the rest of the file.
+    pub profiles: BTreeMap<String, Mapping>,
+}
+
+/// A generator declared under `custom_generators`: any openapi-generator
+/// target, with the build that gates it. Without `service` or `command`
+/// the generator is only generated.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CustomGenerator {
+    /// `server` or `client`.
+    pub scope: String,
+    /// Generator config, relative to the workspace root.
+    pub config: String,
+    /// Service in `.oav/docker-compose.yaml` that compiles the output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+    /// Image `command` runs in, in the generated project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+/// A step declared under `custom_steps`: a container `oav validate` runs
+/// after one of the built-in stages, in the workspace root mounted at
+/// `/work`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CustomStep {
+    pub image: String,
+    /// Arguments to the image's entrypoint; `{spec}` is replaced with the
+    /// spec's path.
+    pub command: Vec<String>,
+    /// Stage the step runs after, whether or not that stage is enabled.
+    #[serde(default = "default_step_position")]
+    pub after: String,
+    /// More `host:container[:ro]` bind mounts, hosts relative to the root.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mounts: Vec<String>,
+}
+
+fn default_step_position() -> String {
+    "lint".to_string()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EngineKind {
+    #[default]
+    Auto,
+    Docker,
+    Podman,
+    Nerdctl,
+}
+
+impl EngineKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EngineKind::Auto => "auto",
+            EngineKind::Docker => "docker",
+            EngineKind::Podman => "podman",
+            EngineKind::Nerdctl => "nerdctl",
+        }
+    }
+}
+
+/// Naming conventions of the `naming` native check. Unset kinds of names
+/// aren't checked.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Naming {
+    /// Static path segments.
+    pub paths: Option<NameCase>,
+    /// Path, query, and cookie parameter names.
+    pub parameters: Option<NameCase>,
+    /// Schema property names.
+    pub properties: Option<NameCase>,
+    /// `components.schemas` names.
+    pub schemas: Option<NameCase>,
+    /// String `enum` values.
+    pub enums: Option<NameCase>,
+}
+
+/// Kinds of names under `naming`.
+pub const NAMING_KINDS: [&str; 5] = ["paths", "parameters", "properties", "schemas", "enums"];
+
+impl Naming {
+    fn get(&self, kind: &str) -> Option<NameCase> {
+        match kind {
+            "paths" => self.paths,
+            "parameters" => self.parameters,
+            "properties" => self.properties,
+            "schemas" => self.schemas,
+            "enums" => self.enums,
+            _ => None,
+        }
+    }
+
+    fn get_mut(&mut self, kind: &str) -> Option<&mut Option<NameCase>> {
+        match kind {
+            "paths" => Some(&mut self.paths),
+            "parameters" => Some(&mut self.parameters),
+            "properties" => Some(&mut self.properties),
+            "schemas" => Some(&mut self.schemas),
+            "enums" => Some(&mut self.enums),
+            _ => None,
+        }
+    }
+}
+
+/// A casing convention: `camel` (`stopPlace`), `pascal` (`StopPlace`),
+/// `snake` (`stop_place`), `kebab` (`stop-place`), or `upper-snake`
+/// (`STOP_PLACE`).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum NameCase {
+    Camel,
+    Pascal,
+    Snake,
+    Kebab,
+    UpperSnake,
+}
+
+impl NameCase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NameCase::Camel => "camel",
+            NameCase::Pascal => "pascal",
+            NameCase::Snake => "snake",
+            NameCase::Kebab => "kebab",
+            NameCase::UpperSnake => "upper-snake",
+        }
+    }
+}
+
+/// SELinux relabeling of bind mounts: `shared` adds `:z`, `private` adds `:Z`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SelinuxLabel {
+    #[default]
+    Auto,
+    None,
+    Shared,
+    Private,
+}
+
+impl SelinuxLabel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SelinuxLabel::Auto => "auto",
+            SelinuxLabel::None => "none",
+            SelinuxLabel::Shared => "shared",
+            SelinuxLabel::Private => "private",
+        }
+    }
+}
+
+/// Look of `badge.svg`, after the shields.io styles of the same names.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum BadgeStyle {
+    #[default]
+    Flat,
+    FlatSquare,
+    ForTheBadge,
+}
+
+impl BadgeStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BadgeStyle::Flat => "flat",
+            BadgeStyle::FlatSquare => "flat-square",
+            BadgeStyle::ForTheBadge => "for-the-badge",
+        }
+    }
+}
+
+/// Webhooks `oav validate` posts the run summary to when it finishes.
+/// `${VAR}` placeholders in the URLs are expanded, to keep secrets out of
+/// `.oavc`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Notifications {
+    /// Receives the summary as JSON.
+    pub webhook_url: Option<String>,
+    /// A Slack incoming webhook, receiving the summary as a message.
+    pub slack_webhook_url: Option<String>,
+    pub on: NotifyOn,
+    /// Link to the report in the summary; the local dashboard when unset.
+    pub report_url: Option<String>,
+}
+
+/// Keys under `notifications`.
+pub const NOTIFICATION_KEYS: [&str; 4] = ["webhook_url", "slack_webhook_url", "on", "report_url"];
+
+impl Notifications {
+    fn url(&self, key: &str) -> Option<&Option<String>> {
+        match key {
+            "webhook_url" | "webhook-url" => Some(&self.webhook_url),
+            "slack_webhook_url" | "slack-webhook-url" => Some(&self.slack_webhook_url),
+            "report_url" | "report-url" => Some(&self.report_url),
+            _ => None,
+        }
+    }
+
+    fn url_mut(&mut self, key: &str) -> Option<&mut Option<String>> {
+        match key {
+            "webhook_url" | "webhook-url" => Some(&mut self.webhook_url),
+            "slack_webhook_url" | "slack-webhook-url" => Some(&mut self.slack_webhook_url),
+            "report_url" | "report-url" => Some(&mut self.report_url),
+            _ => None,
+        }
+    }
+}
+
+/// Runs that are notified: `failure` (the default) or `always`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyOn {
+    #[default]
+    Failure,
+    Always,
+}
+
+impl NotifyOn {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotifyOn::Failure => "failure",
+            NotifyOn::Always => "always",
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             spec: None,
+            spec_template: false,
+            spec_values: None,
+            downconvert: vec!["generate".to_string()],
             mode: Mode::Server,
             lint: true,
+            security: false,
             generate: true,
             compile: true,
+            test: false,
+            smoke: false,
+            smoke_path: "/".to_string(),
+            contract: false,
+            contract_url: None,
+            contract_args: Vec::new(),
             server_generators: Vec::new(),
             client_generators: Vec::new(),
+            compile_server_generators: None,
+            compile_client_generators: None,
+            generator_groups: BTreeMap::new(),
             generator_overrides: HashMap::new(),
+            generator_config_dir: None,
+            generator_properties: BTreeMap::new(),
+            templates: BTreeMap::new(),
+            custom_generators: BTreeMap::new(),
+            custom_steps: BTreeMap::new(),
             generator_image: "openapitools/openapi-generator-cli:v7.17.0".to_string(),
+            generator_matrix: Vec::new(),
+            generator_java_opts: None,
             redocly_image: "redocly/cli:1.25.5".to_string(),
+            schemathesis_image: "schemathesis/schemathesis:stable".to_string(),
+            prism_image: "stoplight/prism:5.12.0".to_string(),
+            mock_port: 4010,
+            serve_port: 4011,
+            lint_config: None,
+            lint_args: Vec::new(),
+            yaml_style: false,
+            spellcheck: false,
+            spellcheck_dictionary: None,
+            cspell_image: "ghcr.io/streetsidesoftware/cspell:8.19.4".to_string(),
+            plugin_dir: None,
             manage_gitignore: true,
+            allowed_content_types: Vec::new(),
+            allowed_failures: Vec::new(),
+            artifact_url_template: None,
+            metrics_textfile: None,
+            history_runs: 10,
+            badge_label: "openapi".to_string(),
+            badge_style: BadgeStyle::Flat,
+            notifications: Notifications::default(),
+            publish_endpoint: None,
+            publish_reports: false,
+            compile_cache: true,
+            compile_cache_dir: None,
+            warm_builders: false,
+            generate_batch: false,
+            timeouts: HashMap::new(),
+            strict: false,
+            severity: HashMap::new(),
+            rules: HashMap::new(),
+            naming: Naming::default(),
+            pagination_params: ["limit", "offset", "page", "pageSize", "cursor"]
+                .map(str::to_string)
+                .to_vec(),
+            hooks: HashMap::new(),
+            container_engine: EngineKind::Auto,
+            docker_context: None,
+            offline_fallback: true,
+            platform: None,
+            image_update_check: false,
+            container_env: HashMap::new(),
+            docker_args: HashMap::new(),
+            selinux_label: SelinuxLabel::Auto,
+            container_user: "auto".to_string(),
+            container_memory: None,
+            container_cpus: None,
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+/// Generate servers and clients with `preset`'s generators. `full` leaves
+/// both lists empty, which selects every supported generator.
+pub fn apply_preset(config: &mut Config, preset: Preset) {
+    let (server, client): (&[&str], &[&str]) = match preset {
+        Preset::Jvm => (&["spring", "kotlin-spring"], &["java", "kotlin"]),
+        Preset::Typescript => (
+            &["typescript-nestjs"],
+            &["typescript-axios", "typescript-fetch", "typescript-node"],
+        ),
+        Preset::Go => (&["go-server"], &["go"]),
+        Preset::Full => (&[], &[]),
+    };
+    config.mode = Mode::Both;
+    config.server_generators = server.iter().map(|name| name.to_string()).collect();
+    config.client_generators = client.iter().map(|name| name.to_string()).collect();
+}
+
+/// Built-in generator groups, selectable as `@<name>` in generator lists.
+/// Groups span scopes; each list keeps the members of its own scope.
+pub const GENERATOR_GROUPS: [(&str, &[&str]); 4] = [
+    (
+        "jvm",
+        &[
+            "spring",
+            "kotlin-spring",
+            "scala-play-server",
+            "java",
+            "kotlin",
+        ],
+    ),
+    ("dotnet", &["aspnetcore", "csharp"]),
+    (
+        "typescript",
+        &[
+            "typescript-nestjs",
+            "typescript-axios",
+            "typescript-fetch",
+            "typescript-node",
+        ],
+    ),
+    (
+        "scripting",
+        &[
+            "python-fastapi",
+            "php-slim4",
+            "nodejs-express-server",
+            "python",
+            "ruby",
+        ],
+    ),
+];
+
+/// Stages that accept a `timeouts` entry.
+pub const TIMEOUT_STAGES: [&str; 6] = ["lint", "generate", "compile", "test", "smoke", "contract"];
+
+/// Stages that accept a `severity` entry.
+pub const SEVERITY_STAGES: [&str; 7] = [
+    "lint", "security", "generate", "compile", "test", "smoke", "contract",
+];
+
+/// Governance rules and their level unless `rules.<rule>` sets one.
+pub const GOVERNANCE_RULES: [(&str, &str); 5] = [
+    ("operation-id-unique", "error"),
+    ("operation-tags", "warn"),
+    ("operation-description", "warn"),
+    ("error-responses", "warn"),
+    ("pagination", "off"),
+];
+
+/// Rules of the `security` stage and their level unless `rules.<rule>` sets one.
+pub const SECURITY_RULES: [(&str, &str); 5] = [
+    ("owasp-auth", "error"),
+    ("owasp-auth-schemes", "warn"),
+    ("owasp-https", "error"),
+    ("owasp-rate-limit", "warn"),
+    ("owasp-numeric-ids", "warn"),
+];
+
+impl Config {
+    /// Members of the generator group `name`: `generator_groups.<name>`, or
+    /// the built-in group of that name.
+    pub fn generator_group(&self, name: &str) -> Option<Vec<String>> {
+        self.generator_groups.get(name).cloned().or_else(|| {
+            GENERATOR_GROUPS
+                .iter()
+                .find(|(group, _)| *group == name)
+                .map(|(_, members)| members.iter().map(|name| name.to_string()).collect())
+        })
+    }
+
+    /// Config paths of generators that don't read `.oav/generators`:
+    /// `generator_overrides` and the configs of `custom_generators`.
+    pub fn generator_config_paths(&self) -> HashMap<String, String> {
+        let mut paths: HashMap<String, String> = self
+            .custom_generators
+            .iter()
+            .map(|(name, generator)| (name.clone(), generator.config.clone()))
+            .collect();
+        paths.extend(self.generator_overrides.clone());
+        paths
+    }
+
+    /// `(label, image)` of each `generator_matrix` entry other than
+    /// `generator_image`. A bare tag such as `v7.6.0` is a tag of the
+    /// `generator_image` repository; the label is the tag.
+    pub fn matrix_images(&self) -> Vec<(String, String)> {
+        let repository = docker::image_repository(&self.generator_image);
+        let mut images: Vec<(String, String)> = Vec::new();
+        for entry in self.generator_matrix.iter().map(|entry| entry.trim()) {
+            let image = if entry.contains(['/', ':', '@']) {
+                entry.to_string()
+            } else {
+                format!("{repository}:{entry}")
+            };
+            let tag = image
+                .strip_prefix(docker::image_repository(&image))
+                .unwrap_or("");
+            let label: String = tag
+                .trim_start_matches([':', '@'])
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                        c
+                    } else {
+                        '-'
+                    }
+                })
+                .collect();
+            let label = if label.is_empty() {
+                "latest".to_string()
+            } else {
+                label
+            };
+            if !entry.is_empty()
+                && image != self.generator_image
+                && !images.iter().any(|(_, existing)| *existing == image)
+            {
+                images.push((label, image));
+            }
+        }
+        images
+    }
+
+    /// `<generator_config_dir>/<scope>`, relative to the workspace root.
+    pub fn generator_config_dir(&self, scope: &str) -> Option<String> {
+        self.generator_config_dir
+            .as_ref()
+            .map(|dir| format!("{}/{scope}", dir.trim_end_matches('/')))
+    }
+
+    /// Replace `@group` entries in the generator and compile lists with the
+    /// group's members.
+    pub fn expand_generator_groups(&mut self) -> Result<()> {
+        self.server_generators = self.expand_groups("server", &self.server_generators)?;
+        self.client_generators = self.expand_groups("client", &self.client_generators)?;
+        if let Some(list) = &self.compile_server_generators {
+            self.compile_server_generators = Some(self.expand_groups("server", list)?);
+        }
+        if let Some(list) = &self.compile_client_generators {
+            self.compile_client_generators = Some(self.expand_groups("client", list)?);
+        }
+        Ok(())
+    }
+
+    /// `list` with each `@group` replaced by the members of the group for
+    /// `scope`. Members supported only by the other scope are left out;
+    /// anything else, such as custom generators, is kept.
+    pub fn expand_groups(&self, scope: &str, list: &[String]) -> Result<Vec<String>> {
+        let other: &[&str] = if scope == "server" {
+            &SUPPORTED_CLIENT_GENERATORS
+        } else {
+            &SUPPORTED_SERVER_GENERATORS
+        };
+        let mut expanded: Vec<String> = Vec::new();
+        for entry in list.iter().map(|entry| entry.trim()) {
+            let members = match entry.strip_prefix('@') {
+                Some(group) => {
+                    let Some(members) = self.generator_group(group) else {
+                        let names: Vec<&str> = self
+                            .generator_groups
+                            .keys()
+                            .map(String::as_str)
+                            .chain(GENERATOR_GROUPS.iter().map(|(name, _)| *name))
+                            .collect();
+                        match util::closest(group, names) {
+                            Some(suggestion) => bail!(
+                                "Unknown generator group: {entry}; did you mean @{suggestion}?"
+                            ),
+                            None => bail!("Unknown generator group: {entry}"),
+                        }
+                    };
+                    let members: Vec<String> = members
+                        .into_iter()
+                        .filter(|member| !other.contains(&member.as_str()))
+                        .collect();
+                    if members.is_empty() {
+                        bail!("Generator group {entry} has no {scope} generators");
+                    }
+                    members
+                }
+                None => vec![entry.to_string()],
+            };
+            for member in members {
+                if !expanded.contains(&member) {
+                    expanded.push(member);
+                }
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// Per-task time limit for a stage, from `timeouts.<stage>`.
+    pub fn timeout(&self, stage: &str) -> Result<Option<Duration>> {
+        self.timeouts
+            .get(stage)
+            .map(|raw| {
+                parse_duration(raw).with_context(|| format!("Invalid timeouts.{stage}: {raw}"))
+            })
+            .transpose()
+    }
+
+    /// Under `strict`, drop the settings that let failures pass.
+    pub fn apply_strict(&mut self) {
+        if self.strict {
+            self.allowed_failures.clear();
+            self.severity.clear();
+        }
+    }
+
+    /// Whether failures of `stage` are reported without failing the run
+    /// (`severity.<stage>: warn`).
+    pub fn warn_only(&self, stage: &str) -> bool {
+        self.severity
+            .get(stage)
+            .is_some_and(|level| level == "warn")
+    }
+
+    /// Level of a governance or security rule: `rules.<rule>`, or its default.
+    pub fn rule_level(&self, rule: &str) -> &str {
+        self.rules.get(rule).map(String::as_str).unwrap_or_else(|| {
+            GOVERNANCE_RULES
+                .iter()
+                .chain(&SECURITY_RULES)
+                .find(|(name, _)| *name == rule)
+                .map_or("off", |(_, level)| level)
+        })
+    }
+
+    /// Whether `step`, or `generator` of the generate step, reads the 3.0
+    /// conversion of a 3.1 spec.
+    pub fn downconverts(&self, step: &str, generator: Option<&str>) -> bool {
+        self.downconvert.iter().any(|entry| {
+            entry == step || generator.is_some_and(|name| *entry == format!("{step}.{name}"))
+        })
+    }
+
+    /// Stages whose `severity` is `warn`.
+    pub fn warn_stages(&self) -> Vec<String> {
+        SEVERITY_STAGES
+            .iter()
+            .filter(|stage| self.warn_only(stage))
+            .map(|stage| stage.to_string())
+            .collect()
+    }
+
+    /// Extra container arguments for a task: `docker_args.all`, then
+    /// `docker_args.<stage>`, then `docker_args.<stage>.<generator>`.
+    pub fn docker_args(&self, stage: &str, generator: Option<&str>) -> Vec<String> {
+        let mut keys = vec!["all".to_string(), stage.to_string()];
+        keys.extend(generator.map(|generator| format!("{stage}.{generator}")));
+        keys.iter()
+            .filter_map(|key| self.docker_args.get(key))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Config file names in lookup order.
+const CONFIG_FILES: [&str; 3] = [CONFIG_FILE, ".oavc.toml", ".oavc.json"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Yaml,
+    Toml,
+    Json,
+}
+
+/// The workspace config file: the first of `.oavc`, `.oavc.toml`, and
+/// `.oavc.json` that exists, or `.oavc` for a new workspace.
+pub fn path(root: &Path) -> PathBuf {
+    CONFIG_FILES
+        .iter()
+        .map(|name| root.join(name))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| root.join(CONFIG_FILE))
+}
+
+/// File name of the workspace config, for messages and `.gitignore`.
+pub fn file_name(root: &Path) -> String {
+    path(root)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| CONFIG_FILE.to_string())
+}
+
+/// Format from the extension; a plain `.oavc` is sniffed so it can hold
+/// JSON or TOML too.
+fn format_of(path: &Path, content: &str) -> Format {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => return Format::Toml,
+        Some("json") => return Format::Json,
+        _ => {}
+    }
+    if content.trim_start().starts_with('{') {
+        return Format::Json;
+    }
+    let first = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'));
+    match first {
+        Some(line) if line.starts_with('[') => Format::Toml,
+        Some(line)
+            if line
+                .split_once('=')
+                .is_some_and(|(key, _)| is_toml_key(key)) =>
+        {
+            Format::Toml
         }
+        _ => Format::Yaml,
+    }
+}
+
+fn is_toml_key(key: &str) -> bool {
+    let key = key.trim();
+    !key.is_empty()
+        && (key.starts_with('"')
+            || key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+}
+
+/// Current config schema version. Files from older versions are upgraded in
+/// memory when loaded, and on disk by `oav config migrate`.
+///
+/// 1. The baseline layout. Files written before versioning have no
+///    `version` key and are read as version 1.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Upgrade `document` from an older schema version in place. Returns the
+/// version it had, or `None` if it had no `version` key.
+fn upgrade(document: &mut Mapping, name: &str) -> Result<Option<u32>> {
+    let Some(value) = document.get("version") else {
+        // Unversioned files have the version 1 layout; only the key is new.
+        let mut upgraded = Mapping::new();
+        upgraded.insert("version".into(), CONFIG_VERSION.into());
+        upgraded.extend(std::mem::take(document));
+        *document = upgraded;
+        return Ok(None);
+    };
+    let version = value
+        .as_u64()
+        .and_then(|version| u32::try_from(version).ok())
+        .filter(|version| *version >= 1)
+        .with_context(|| format!("Invalid version in {name}: expected a number from 1"))?;
+    if version > CONFIG_VERSION {
+        bail!(
+            "{name} has version {version}, but this oav only reads up to {CONFIG_VERSION}. Upgrade oav."
+        );
+    }
+    Ok(Some(version))
+}
+
+/// The config file's text and its text rewritten to the current schema
+/// version, or `None` if it is current or there is no file.
+pub fn migrate(root: &Path) -> Result<Option<(String, String)>> {
+    let Some(mut document) = read::<Mapping>(root)? else {
+        return Ok(None);
+    };
+    if upgrade(&mut document, &file_name(root))? == Some(CONFIG_VERSION) {
+        return Ok(None);
     }
+    let existing = fs::read_to_string(path(root))
+        .with_context(|| format!("Failed to read {}", file_name(root)))?;
+    Ok(Some((existing, serialize(root, &document)?)))
 }
 
+/// Load the workspace config. Unknown keys are rejected, naming the closest
+/// known key, since serde would otherwise drop a misspelled key silently.
 pub fn load(root: &Path) -> Result<Config> {
-    let path = root.join(CONFIG_FILE);
-    if !path.exists() {
+    let Some(mut document) = read::<Mapping>(root)? else {
         return Ok(Config::default());
+    };
+    let name = file_name(root);
+    upgrade(&mut document, &name)?;
+    if let Some((key, suggestion)) = unknown_keys(&document).into_iter().next() {
+        let hint = suggestion
+            .map(|suggestion| format!("; did you mean `{suggestion}`?"))
+            .unwrap_or_default();
+        bail!("Unknown key `{key}` in {name}{hint} Run `oav config check` to list every problem.");
+    }
+    serde_yaml::from_value(Value::Mapping(document))
+        .with_context(|| format!("Failed to parse {name}"))
+}
+
+/// Every problem in the workspace config: unknown keys and values `config
+/// set` would reject.
+pub fn check(root: &Path) -> Result<Vec<String>> {
+    let Some(mut document) = read::<Mapping>(root)? else {
+        return Ok(Vec::new());
+    };
+    upgrade(&mut document, &file_name(root))?;
+    let mut problems: Vec<String> = unknown_keys(&document)
+        .into_iter()
+        .map(|(key, suggestion)| match suggestion {
+            Some(suggestion) => format!("Unknown key `{key}`; did you mean `{suggestion}`?"),
+            None => format!("Unknown key `{key}`"),
+        })
+        .collect();
+    let known = top_level_keys();
+    let mut config = Config::default();
+    for (key, value) in &document {
+        let Some(key) = key.as_str().filter(|key| known.iter().any(|k| k == key)) else {
+            continue;
+        };
+        if let Err(err) = set_value(&mut config, key, raw_value(value)?) {
+            problems.push(format!("{key}: {err}"));
+        }
+    }
+    if let Err(err) = config.clone().expand_generator_groups() {
+        problems.push(err.to_string());
+    }
+    for (name, overlay) in &config.profiles {
+        let mut scratch = Config::default();
+        for (key, value) in overlay {
+            let key = key.as_str().unwrap_or_default();
+            if let Err(err) = set_raw(&mut scratch, key, value) {
+                problems.push(format!("profiles.{name}.{key}: {err}"));
+            }
+        }
     }
-    let content = fs::read_to_string(&path).context("Failed to read .oavc")?;
-    let config = serde_yaml::from_str(&content).context("Failed to parse .oavc")?;
-    Ok(config)
+    Ok(problems)
 }
 
+/// Top-level keys `Config` doesn't have, each with the closest known key
+/// if one is near enough to be a typo.
+fn unknown_keys(document: &Mapping) -> Vec<(String, Option<String>)> {
+    let known = top_level_keys();
+    document
+        .keys()
+        .map(|key| {
+            key.as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{key:?}"))
+        })
+        .filter(|key| !known.contains(key))
+        .map(|key| {
+            let suggestion =
+                util::closest(&key, known.iter().map(String::as_str)).map(str::to_string);
+            (key, suggestion)
+        })
+        .collect()
+}
+
+/// The workspace config file parsed into `T`, or `None` if there is none.
+fn read<T: DeserializeOwned>(root: &Path) -> Result<Option<T>> {
+    let path = path(root);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let name = file_name(root);
+    let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {name}"))?;
+    let value = match format_of(&path, &content) {
+        Format::Yaml => serde_yaml::from_str(&content).map_err(anyhow::Error::from),
+        Format::Toml => toml::from_str(&content).map_err(anyhow::Error::from),
+        Format::Json => serde_json::from_str(&content).map_err(anyhow::Error::from),
+    };
+    value
+        .map(Some)
+        .with_context(|| format!("Failed to parse {name}"))
+}
+
+/// Write `config` back to the workspace config file, keeping its format.
 pub fn write(root: &Path, config: &Config) -> Result<()> {
-    let path = root.join(CONFIG_FILE);
-    let content = serde_yaml::to_string(config).context("Failed to serialize config")?;
-    fs::write(&path, content).context("Failed to write .oavc")?;
+    let path = path(root);
+    let content = to_string(root, config)?;
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", file_name(root)))?;
     Ok(())
 }
 
+/// `config` serialized in the format of the workspace config file.
+pub fn to_string(root: &Path, config: &Config) -> Result<String> {
+    serialize(root, config)
+}
+
+fn serialize<T: Serialize>(root: &Path, value: &T) -> Result<String> {
+    let path = path(root);
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    match format_of(&path, &existing) {
+        Format::Yaml => serde_yaml::to_string(value).context("Failed to serialize config"),
+        Format::Toml => toml::to_string(value).context("Failed to serialize config"),
+        Format::Json => serde_json::to_string_pretty(value)
+            .map(|json| json + "\n")
+            .context("Failed to serialize config"),
+    }
+}
+
+/// `config` as YAML with each key's source in a trailing comment: the
+/// default, the config file, the profile or an `OAV_*` variable in
+/// `overlays`, or a command-line flag in `flag_keys`. Later sources win in
+/// that order.
+pub fn resolve(
+    root: &Path,
+    config: &Config,
+    overlays: &Overlays,
+    flag_keys: &[&str],
+) -> Result<String> {
+    let file_keys: Vec<String> = read::<Mapping>(root)?
+        .unwrap_or_default()
+        .keys()
+        .filter_map(|key| key.as_str().map(str::to_string))
+        .collect();
+    let file = file_name(root);
+    let mut out = String::new();
+    for (key, value) in mapping_of(config) {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        let source = if flag_keys.contains(&key) {
+            "command line".to_string()
+        } else if let Some(source) = overlays.source(key) {
+            source
+        } else if file_keys.iter().any(|file_key| file_key == key) {
+            file.clone()
+        } else {
+            "default".to_string()
+        };
+        out.push_str(&format!(
+            "{key}: {}  # {source}\n",
+            serde_json::to_string(&value)?
+        ));
+    }
+    Ok(out)
+}
+
+/// Keys accepted by `config get`/`config set`, for shell completion.
+pub fn keys() -> Vec<String> {
+    let mut keys = top_level_keys();
+    keys.extend(TIMEOUT_STAGES.map(|stage| format!("timeouts.{stage}")));
+    keys.extend(SEVERITY_STAGES.map(|stage| format!("severity.{stage}")));
+    keys.extend(GOVERNANCE_RULES.map(|(rule, _)| format!("rules.{rule}")));
+    keys.extend(SECURITY_RULES.map(|(rule, _)| format!("rules.{rule}")));
+    keys.extend(NAMING_KINDS.map(|kind| format!("naming.{kind}")));
+    keys.extend(NOTIFICATION_KEYS.map(|key| format!("notifications.{key}")));
+    keys.extend(lifecycle::EVENTS.map(|event| format!("hooks.{event}")));
+    keys
+}
+
+fn top_level_keys() -> Vec<String> {
+    mapping_of(&Config::default())
+        .into_iter()
+        .filter_map(|(key, _)| key.as_str().map(str::to_string))
+        .collect()
+}
+
+fn mapping_of(config: &Config) -> Mapping {
+    serde_yaml::to_value(config)
+        .ok()
+        .and_then(|value| value.as_mapping().cloned())
+        .unwrap_or_default()
+}
+
+/// Prefix of the environment variables overriding config keys.
+pub const ENV_PREFIX: &str = "OAV_";
+
+/// Environment variable overriding `key`: `server_generators` ->
+/// `OAV_SERVER_GENERATORS`.
+pub fn env_var(key: &str) -> String {
+    format!("{ENV_PREFIX}{}", key.to_uppercase())
+}
+
+/// Config keys set on top of the config file by a profile and by `OAV_*`
+/// environment variables, applied in that order before command-line flags.
+pub struct Overlays {
+    file: Config,
+    applied: Config,
+    profile: Option<String>,
+    profile_keys: Vec<String>,
+    env_keys: Vec<String>,
+}
+
+impl Overlays {
+    /// Keys that were set from the environment.
+    pub fn env_keys(&self) -> &[String] {
+        &self.env_keys
+    }
+
+    /// Where `key` was last set by an overlay: `profile <name>` or its
+    /// environment variable.
+    fn source(&self, key: &str) -> Option<String> {
+        if self.env_keys.iter().any(|env_key| env_key == key) {
+            Some(env_var(key))
+        } else if self
+            .profile_keys
+            .iter()
+            .any(|profile_key| profile_key == key)
+        {
+            self.profile.as_ref().map(|name| format!("profile {name}"))
+        } else {
+            None
+        }
+    }
+
+    /// `config` as it should be written back: keys still holding an overlay's
+    /// value get the file's value again, so profile and CI values never end
+    /// up in the config file. Keys changed since, by flags, are kept.
+    pub fn strip(&self, config: &Config) -> Result<Config> {
+        if self.env_keys.is_empty() && self.profile_keys.is_empty() {
+            return Ok(config.clone());
+        }
+        let file = mapping_of(&self.file);
+        let applied = mapping_of(&self.applied);
+        let mut current = mapping_of(config);
+        for key in self.env_keys.iter().chain(&self.profile_keys) {
+            let key = Value::from(key.as_str());
+            if current.get(&key) == applied.get(&key) {
+                match file.get(&key) {
+                    Some(value) => current.insert(key, value.clone()),
+                    None => current.remove(&key),
+                };
+            }
+        }
+        serde_yaml::from_value(Value::Mapping(current)).context("Failed to restore config values")
+    }
+}
+
+/// The workspace config with the profile `profile` and then `OAV_<KEY>`
+/// environment variables applied on top. Values are parsed like `config set`
+/// values.
+pub fn load_effective(root: &Path, profile: Option<&str>) -> Result<(Config, Overlays)> {
+    let file = load(root)?;
+    let mut config = file.clone();
+    let mut profile_keys = Vec::new();
+    if let Some(name) = profile {
+        let Some(overlay) = file.profiles.get(name) else {
+            let defined: Vec<&str> = file.profiles.keys().map(String::as_str).collect();
+            let hint = match util::closest(name, defined.iter().copied()) {
+                Some(suggestion) => format!("; did you mean {suggestion}?"),
+                None if defined.is_empty() => format!("; {} defines none", file_name(root)),
+                None => format!(" (defined: {})", defined.join(", ")),
+            };
+            bail!("Unknown profile: {name}{hint}");
+        };
+        for (key, value) in overlay {
+            let Some(key) = key.as_str() else {
+                continue;
+            };
+            set_raw(&mut config, key, value)
+                .with_context(|| format!("Invalid profiles.{name}.{key}"))?;
+            profile_keys.push(key.to_string());
+        }
+    }
+    let mut env_keys = Vec::new();
+    for key in top_level_keys().into_iter().filter(|key| key != "version") {
+        let name = env_var(&key);
+        let Ok(value) = std::env::var(&name) else {
+            continue;
+        };
+        set_value(&mut config, &key, value).with_context(|| format!("Invalid {name}"))?;
+        env_keys.push(key);
+    }
+    let overlays = Overlays {
+        file,
+        applied: config.clone(),
+        profile: profile.map(str::to_string),
+        profile_keys,
+        env_keys,
+    };
+    Ok((config, overlays))
+}
+
+/// Set `key` from a parsed config value, validated as `config set` does.
+fn set_raw(config: &mut Config, key: &str, value: &Value) -> Result<()> {
+    if matches!(key, "version" | "profiles") {
+        bail!("{key} can't be set by a profile");
+    }
+    set_value(config, key, raw_value(value)?)
+}
+
+/// `value` as `config set` takes it: scalars as written, lists and maps as
+/// flow YAML.
+fn raw_value(value: &Value) -> Result<String> {
+    Ok(match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        _ => serde_json::to_string(value)?,
+    })
+}
+
 pub fn print_value(config: &Config, key: &str) -> Result<()> {
     let (base, subkey) = parse_key(key);
 
     match base {
+        "version" => println!("{}", config.version),
         "spec" => {
             if let Some(spec) = &config.spec {
                 println!("{spec}");
             }
         }
+        "spec_template" | "spec-template" => println!("{}", config.spec_template),
+        "spec_values" | "spec-values" => {
+            if let Some(path) = &config.spec_values {
+                println!("{path}");
+            }
+        }
         "mode" => println!("{}", config.mode.as_str()),
         "lint" => println!("{}", config.lint),
+        "security" => println!("{}", config.security),
         "generate" => println!("{}", config.generate),
         "compile" => println!("{}", config.compile),
+        "test" => println!("{}", config.test),
+        "smoke" => println!("{}", config.smoke),
+        "smoke_path" | "smoke-path" => println!("{}", config.smoke_path),
+        "contract" => println!("{}", config.contract),
+        "contract_url" | "contract-url" => {
+            if let Some(url) = &config.contract_url {
+                println!("{url}");
+            }
+        }
+        "contract_args" | "contract-args" => print_yaml(&config.contract_args)?,
+        "lint_args" | "lint-args" => print_yaml(&config.lint_args)?,
         "server_generators" | "server-generators" => {
             print_yaml(&config.server_generators)?;
         }
         "client_generators" | "client-generators" => {
             print_yaml(&config.client_generators)?;
         }
+        "compile_server_generators" | "compile-server-generators" => {
+            if let Some(generators) = &config.compile_server_generators {
+                print_yaml(generators)?;
+            }
+        }
+        "compile_client_generators" | "compile-client-generators" => {
+            if let Some(generators) = &config.compile_client_generators {
+                print_yaml(generators)?;
+            }
+        }
+        "generator_groups" | "generator-groups" => {
+            if let Some(subkey) = subkey {
+                if let Some(members) = config.generator_groups.get(subkey) {
+                    print_yaml(members)?;
+                }
+            } else {
+                print_yaml(&config.generator_groups)?;
+            }
+        }
         "generator_overrides" | "generator-overrides" => {
             if let Some(subkey) = subkey {
                 if let Some(value) = config.generator_overrides.get(subkey) {
@@ -87,14 +1222,326 @@ pub fn print_value(config: &Config, key: &str) -> Result<()> {
                 print_yaml(&config.generator_overrides)?;
             }
         }
+        "generator_config_dir" | "generator-config-dir" => {
+            if let Some(dir) = &config.generator_config_dir {
+                println!("{dir}");
+            }
+        }
+        "generator_properties" | "generator-properties" => {
+            if let Some(subkey) = subkey {
+                if let Some(properties) = config.generator_properties.get(subkey) {
+                    print_yaml(properties)?;
+                }
+            } else {
+                print_yaml(&config.generator_properties)?;
+            }
+        }
+        "templates" => {
+            if let Some(subkey) = subkey {
+                if let Some(dir) = config.templates.get(subkey) {
+                    println!("{dir}");
+                }
+            } else {
+                print_yaml(&config.templates)?;
+            }
+        }
+        "custom_generators" | "custom-generators" => {
+            if let Some(subkey) = subkey {
+                if let Some(generator) = config.custom_generators.get(subkey) {
+                    print_yaml(generator)?;
+                }
+            } else {
+                print_yaml(&config.custom_generators)?;
+            }
+        }
+        "custom_steps" | "custom-steps" => {
+            if let Some(subkey) = subkey {
+                if let Some(step) = config.custom_steps.get(subkey) {
+                    print_yaml(step)?;
+                }
+            } else {
+                print_yaml(&config.custom_steps)?;
+            }
+        }
         "generator_image" | "generator-image" => println!("{}", config.generator_image),
+        "generator_matrix" | "generator-matrix" => print_yaml(&config.generator_matrix)?,
+        "generator_java_opts" | "generator-java-opts" => {
+            if let Some(options) = &config.generator_java_opts {
+                println!("{options}");
+            }
+        }
         "redocly_image" | "redocly-image" => println!("{}", config.redocly_image),
+        "schemathesis_image" | "schemathesis-image" => {
+            println!("{}", config.schemathesis_image)
+        }
+        "prism_image" | "prism-image" => println!("{}", config.prism_image),
+        "mock_port" | "mock-port" => println!("{}", config.mock_port),
+        "serve_port" | "serve-port" => println!("{}", config.serve_port),
+        "yaml_style" | "yaml-style" => println!("{}", config.yaml_style),
+        "spellcheck" => println!("{}", config.spellcheck),
+        "spellcheck_dictionary" | "spellcheck-dictionary" => {
+            if let Some(path) = &config.spellcheck_dictionary {
+                println!("{path}");
+            }
+        }
+        "cspell_image" | "cspell-image" => println!("{}", config.cspell_image),
+        "downconvert" => print_yaml(&config.downconvert)?,
+        "lint_config" | "lint-config" => {
+            if let Some(path) = &config.lint_config {
+                println!("{path}");
+            }
+        }
+        "plugin_dir" | "plugin-dir" => {
+            if let Some(dir) = &config.plugin_dir {
+                println!("{dir}");
+            }
+        }
         "manage_gitignore" | "manage-gitignore" => println!("{}", config.manage_gitignore),
-        _ => bail!("Unknown config key: {key}"),
+        "allowed_content_types" | "allowed-content-types" => {
+            print_yaml(&config.allowed_content_types)?;
+        }
+        "allowed_failures" | "allowed-failures" => print_yaml(&config.allowed_failures)?,
+        "artifact_url_template" | "artifact-url-template" => {
+            if let Some(template) = &config.artifact_url_template {
+                println!("{template}");
+            }
+        }
+        "metrics_textfile" | "metrics-textfile" => {
+            if let Some(path) = &config.metrics_textfile {
+                println!("{path}");
+            }
+        }
+        "history_runs" | "history-runs" => println!("{}", config.history_runs),
+        "badge_label" | "badge-label" => println!("{}", config.badge_label),
+        "badge_style" | "badge-style" => println!("{}", config.badge_style.as_str()),
+        "compile_cache" | "compile-cache" => println!("{}", config.compile_cache),
+        "compile_cache_dir" | "compile-cache-dir" => {
+            if let Some(dir) = &config.compile_cache_dir {
+                println!("{dir}");
+            }
+        }
+        "warm_builders" | "warm-builders" => println!("{}", config.warm_builders),
+        "generate_batch" | "generate-batch" => println!("{}", config.generate_batch),
+        "strict" => println!("{}", config.strict),
+        "container_engine" | "container-engine" => {
+            println!("{}", config.container_engine.as_str())
+        }
+        "docker_context" | "docker-context" => {
+            if let Some(context) = &config.docker_context {
+                println!("{context}");
+            }
+        }
+        "offline_fallback" | "offline-fallback" => println!("{}", config.offline_fallback),
+        "platform" => {
+            if let Some(platform) = &config.platform {
+                println!("{platform}");
+            }
+        }
+        "image_update_check" | "image-update-check" => println!("{}", config.image_update_check),
+        "timeouts" => {
+            if let Some(subkey) = subkey {
+                if let Some(value) = config.timeouts.get(subkey) {
+                    println!("{value}");
+                }
+            } else {
+                print_yaml(&config.timeouts)?;
+            }
+        }
+        "severity" => {
+            if let Some(subkey) = subkey {
+                if let Some(value) = config.severity.get(subkey) {
+                    println!("{value}");
+                }
+            } else {
+                print_yaml(&config.severity)?;
+            }
+        }
+        "rules" => {
+            if let Some(subkey) = subkey {
+                println!("{}", config.rule_level(subkey));
+            } else {
+                print_yaml(&config.rules)?;
+            }
+        }
+        "naming" => match subkey {
+            Some(kind) => {
+                if let Some(case) = config.naming.get(kind) {
+                    println!("{}", case.as_str());
+                }
+            }
+            None => print_yaml(&config.naming)?,
+        },
+        "notifications" => match subkey {
+            Some("on") => println!("{}", config.notifications.on.as_str()),
+            Some(key) => {
+                if let Some(Some(url)) = config.notifications.url(key) {
+                    println!("{url}");
+                }
+            }
+            None => print_yaml(&config.notifications)?,
+        },
+        "publish_endpoint" | "publish-endpoint" => {
+            if let Some(endpoint) = &config.publish_endpoint {
+                println!("{endpoint}");
+            }
+        }
+        "publish_reports" | "publish-reports" => println!("{}", config.publish_reports),
+        "pagination_params" | "pagination-params" => print_yaml(&config.pagination_params)?,
+        "hooks" => {
+            if let Some(subkey) = subkey {
+                if let Some(command) = config.hooks.get(subkey) {
+                    println!("{command}");
+                }
+            } else {
+                print_yaml(&config.hooks)?;
+            }
+        }
+        "container_env" | "container-env" => {
+            if let Some(subkey) = subkey {
+                if let Some(value) = config.container_env.get(subkey) {
+                    println!("{value}");
+                }
+            } else {
+                print_yaml(&config.container_env)?;
+            }
+        }
+        "selinux_label" | "selinux-label" => println!("{}", config.selinux_label.as_str()),
+        "container_user" | "container-user" => println!("{}", config.container_user),
+        "container_memory" | "container-memory" => {
+            if let Some(memory) = &config.container_memory {
+                println!("{memory}");
+            }
+        }
+        "container_cpus" | "container-cpus" => {
+            if let Some(cpus) = &config.container_cpus {
+                println!("{cpus}");
+            }
+        }
+        "docker_args" | "docker-args" => {
+            if let Some(subkey) = subkey {
+                if let Some(args) = config.docker_args.get(subkey) {
+                    print_yaml(args)?;
+                }
+            } else {
+                print_yaml(&config.docker_args)?;
+            }
+        }
+        "profiles" => {
+            if let Some(subkey) = subkey {
+                if let Some(profile) = config.profiles.get(subkey) {
+                    print_yaml(profile)?;
+                }
+            } else {
+                print_yaml(&config.profiles)?;
+            }
+        }
+        _ => bail!(unknown_config_key(key)),
     }
     Ok(())
 }
 
+/// The list at `key`, for `config add` and `config remove`. An empty
+/// generator list selects every supported generator, so it is expanded for
+/// `config remove`, and an unset compile list starts from the generated ones.
+fn list_value(config: &Config, key: &str) -> Result<Vec<String>> {
+    let expand = |listed: &[String], supported: &[&str]| {
+        if listed.is_empty() {
+            supported.iter().map(|name| name.to_string()).collect()
+        } else {
+            listed.to_vec()
+        }
+    };
+    Ok(match key {
+        "server_generators" | "server-generators" => {
+            expand(&config.server_generators, &SUPPORTED_SERVER_GENERATORS)
+        }
+        "client_generators" | "client-generators" => {
+            expand(&config.client_generators, &SUPPORTED_CLIENT_GENERATORS)
+        }
+        "compile_server_generators" | "compile-server-generators" => {
+            match &config.compile_server_generators {
+                Some(generators) => generators.clone(),
+                None => list_value(config, "server_generators")?,
+            }
+        }
+        "compile_client_generators" | "compile-client-generators" => {
+            match &config.compile_client_generators {
+                Some(generators) => generators.clone(),
+                None => list_value(config, "client_generators")?,
+            }
+        }
+        "allowed_content_types" | "allowed-content-types" => config.allowed_content_types.clone(),
+        "allowed_failures" | "allowed-failures" => config.allowed_failures.clone(),
+        _ => bail!(
+            "{key} is not a list (expected a generator list, allowed_content_types, or allowed_failures)"
+        ),
+    })
+}
+
+/// Whether the list at `key` is empty, or unset for a compile list, and so
+/// selects every supported generator.
+pub fn selects_all(config: &Config, key: &str) -> bool {
+    match key {
+        "server_generators" | "server-generators" => config.server_generators.is_empty(),
+        "client_generators" | "client-generators" => config.client_generators.is_empty(),
+        "compile_server_generators" | "compile-server-generators" => {
+            config.compile_server_generators.is_none() && config.server_generators.is_empty()
+        }
+        "compile_client_generators" | "compile-client-generators" => {
+            config.compile_client_generators.is_none() && config.client_generators.is_empty()
+        }
+        _ => false,
+    }
+}
+
+/// Append `values` to the list at `key`. Returns the values already in it,
+/// which are skipped. A list that selects every generator already has them
+/// all, so it is left as it is once the values are checked.
+pub fn add_values(config: &mut Config, key: &str, values: &[String]) -> Result<Vec<String>> {
+    if selects_all(config, key) {
+        set_value(&mut config.clone(), key, serde_json::to_string(values)?)?;
+        return Ok(values.to_vec());
+    }
+    let mut list = list_value(config, key)?;
+    let mut present = Vec::new();
+    for value in values {
+        if list.contains(value) {
+            present.push(value.clone());
+        } else {
+            list.push(value.clone());
+        }
+    }
+    set_value(config, key, serde_json::to_string(&list)?)?;
+    Ok(present)
+}
+
+/// Remove `values` from the list at `key`. Returns the values that weren't
+/// in it.
+pub fn remove_values(config: &mut Config, key: &str, values: &[String]) -> Result<Vec<String>> {
+    let mut list = list_value(config, key)?;
+    let missing = values
+        .iter()
+        .filter(|value| !list.contains(value))
+        .cloned()
+        .collect();
+    list.retain(|item| !values.contains(item));
+    if list.is_empty() && key.ends_with("generators") {
+        bail!(
+            "Can't remove every generator from {key}: an empty list selects all of them. Change mode instead."
+        );
+    }
+    set_value(config, key, serde_json::to_string(&list)?)?;
+    Ok(missing)
+}
+
+fn unknown_config_key(key: &str) -> String {
+    let keys = keys();
+    match util::closest(key, keys.iter().map(String::as_str)) {
+        Some(suggestion) => format!("Unknown config key: {key} (did you mean {suggestion}?)"),
+        None => format!("Unknown config key: {key}"),
+    }
+}
+
 fn parse_key(key: &str) -> (&str, Option<&str>) {
     match key.split_once('.') {
         Some((base, subkey)) => (base, Some(subkey)),
@@ -114,11 +1561,45 @@ pub fn set_value(config: &mut Config, key: &str, value: String) -> Result<()> {
     let (base, subkey) = parse_key(key);
 
     match base {
+        "version" => {
+            if value.trim() != CONFIG_VERSION.to_string() {
+                bail!("version can't be set; run `oav config migrate` to upgrade the config");
+            }
+        }
         "spec" => config.spec = Some(value),
+        "spec_template" | "spec-template" => config.spec_template = parse_bool(&value)?,
+        "spec_values" | "spec-values" => {
+            config.spec_values = (!value.is_empty()).then_some(value);
+        }
         "mode" => config.mode = parse_mode(&value)?,
         "lint" => config.lint = parse_bool(&value)?,
+        "security" => config.security = parse_bool(&value)?,
         "generate" => config.generate = parse_bool(&value)?,
         "compile" => config.compile = parse_bool(&value)?,
+        "test" => config.test = parse_bool(&value)?,
+        "smoke" => config.smoke = parse_bool(&value)?,
+        "smoke_path" | "smoke-path" => {
+            if !value.starts_with('/') {
+                bail!("Invalid smoke_path: {value} (expected a path starting with /)");
+            }
+            config.smoke_path = value;
+        }
+        "contract" => config.contract = parse_bool(&value)?,
+        "contract_url" | "contract-url" => {
+            if !value.is_empty() && !value.starts_with("http://") && !value.starts_with("https://")
+            {
+                bail!("Invalid contract_url: {value} (expected an http:// or https:// URL)");
+            }
+            config.contract_url = (!value.is_empty()).then_some(value);
+        }
+        "lint_args" | "lint-args" => {
+            config.lint_args = parse_args(&value)
+                .context("Invalid lint_args (example: [--max-problems, '50'])")?;
+        }
+        "contract_args" | "contract-args" => {
+            config.contract_args =
+                parse_args(&value).context("Invalid contract_args (example: [--checks, all])")?;
+        }
         "server_generators" | "server-generators" => {
             config.server_generators = parse_yaml_list(&value)
                 .context("Invalid YAML list for server_generators (example: [spring, kotlin])")?;
@@ -128,6 +1609,48 @@ pub fn set_value(config: &mut Config, key: &str, value: String) -> Result<()> {
                 "Invalid YAML list for client_generators (example: [typescript, swift])",
             )?;
         }
+        "compile_server_generators" | "compile-server-generators" => {
+            config.compile_server_generators =
+                Some(parse_yaml_list(&value).context(
+                    "Invalid YAML list for compile_server_generators (example: [spring])",
+                )?)
+                .filter(|generators| !generators.is_empty());
+        }
+        "compile_client_generators" | "compile-client-generators" => {
+            config.compile_client_generators = Some(
+                parse_yaml_list(&value)
+                    .context("Invalid YAML list for compile_client_generators (example: [java])")?,
+            )
+            .filter(|generators| !generators.is_empty());
+        }
+        "generator_groups" | "generator-groups" => {
+            let groups = match subkey {
+                Some(name) => {
+                    let mut groups = config.generator_groups.clone();
+                    let members = parse_yaml_list(&value).context(
+                        "Invalid YAML list for a generator group (example: [spring, java])",
+                    )?;
+                    if members.is_empty() {
+                        groups.remove(name);
+                    } else {
+                        groups.insert(name.to_string(), members);
+                    }
+                    groups
+                }
+                None if value.trim().is_empty() => BTreeMap::new(),
+                None => serde_yaml::from_str(&value).context(
+                    "Invalid YAML map for generator_groups (example: {mobile: [kotlin, swift5]})",
+                )?,
+            };
+            if let Some(name) = groups.keys().find(|name| {
+                name.is_empty()
+                    || name.contains(['@', ',', '.'])
+                    || name.contains(char::is_whitespace)
+            }) {
+                bail!("Invalid generator group name: {name}");
+            }
+            config.generator_groups = groups;
+        }
         "generator_overrides" | "generator-overrides" => {
             if let Some(subkey) = subkey {
                 if value.is_empty() {
@@ -141,10 +1664,499 @@ pub fn set_value(config: &mut Config, key: &str, value: String) -> Result<()> {
                 )?;
             }
         }
-        "generator_image" | "generator-image" => config.generator_image = value,
-        "redocly_image" | "redocly-image" => config.redocly_image = value,
+        "generator_config_dir" | "generator-config-dir" => {
+            config.generator_config_dir = (!value.is_empty()).then_some(value);
+        }
+        "generator_properties" | "generator-properties" => {
+            let generator_properties = match subkey {
+                Some(name) => {
+                    let mut generator_properties = config.generator_properties.clone();
+                    let properties: Mapping = if value.trim().is_empty() {
+                        Mapping::new()
+                    } else {
+                        serde_yaml::from_str(&value).context(
+                            "Invalid YAML map for generator properties (example: {useSpringBoot3: true})",
+                        )?
+                    };
+                    if properties.is_empty() {
+                        generator_properties.remove(name);
+                    } else {
+                        generator_properties.insert(name.to_string(), properties);
+                    }
+                    generator_properties
+                }
+                None if value.trim().is_empty() => BTreeMap::new(),
+                None => serde_yaml::from_str(&value).context(
+                    "Invalid YAML map for generator_properties (example: {spring: {useSpringBoot3: true}})",
+                )?,
+            };
+            for (name, properties) in &generator_properties {
+                if let Some(key) = properties.keys().find(|key| key.as_str().is_none()) {
+                    bail!("Invalid generator_properties.{name} key: {key:?} (expected a name)");
+                }
+            }
+            config.generator_properties = generator_properties;
+        }
+        "templates" => {
+            config.templates = match subkey {
+                Some(name) => {
+                    let mut templates = config.templates.clone();
+                    if value.is_empty() {
+                        templates.remove(name);
+                    } else {
+                        templates.insert(name.to_string(), value);
+                    }
+                    templates
+                }
+                None if value.trim().is_empty() => BTreeMap::new(),
+                None => serde_yaml::from_str(&value).context(
+                    "Invalid YAML map for templates (example: {spring: templates/spring})",
+                )?,
+            };
+        }
+        "custom_generators" | "custom-generators" => {
+            let generators = match subkey {
+                Some(name) => {
+                    let mut generators = config.custom_generators.clone();
+                    if value.trim().is_empty() {
+                        generators.remove(name);
+                    } else {
+                        let generator = serde_yaml::from_str(&value).context(
+                            "Invalid YAML map for a custom generator (example: {scope: client, config: generators/elixir.yaml})",
+                        )?;
+                        generators.insert(name.to_string(), generator);
+                    }
+                    generators
+                }
+                None if value.trim().is_empty() => BTreeMap::new(),
+                None => serde_yaml::from_str(&value).context(
+                    "Invalid YAML map for custom_generators (example: {elixir: {scope: client, config: generators/elixir.yaml}})",
+                )?,
+            };
+            for (name, generator) in &generators {
+                check_custom_generator(name, generator)
+                    .with_context(|| format!("Invalid custom_generators.{name}"))?;
+            }
+            config.custom_generators = generators;
+        }
+        "custom_steps" | "custom-steps" => {
+            let steps = match subkey {
+                Some(name) => {
+                    let mut steps = config.custom_steps.clone();
+                    if value.trim().is_empty() {
+                        steps.remove(name);
+                    } else {
+                        let step = serde_yaml::from_str(&value).context(
+                            "Invalid YAML map for a custom step (example: {image: stoplight/spectral:6, command: [lint, '{spec}']})",
+                        )?;
+                        steps.insert(name.to_string(), step);
+                    }
+                    steps
+                }
+                None if value.trim().is_empty() => BTreeMap::new(),
+                None => serde_yaml::from_str(&value).context(
+                    "Invalid YAML map for custom_steps (example: {spectral: {image: stoplight/spectral:6, command: [lint, '{spec}']}})",
+                )?,
+            };
+            for (name, step) in &steps {
+                check_custom_step(name, step)
+                    .with_context(|| format!("Invalid custom_steps.{name}"))?;
+            }
+            config.custom_steps = steps;
+        }
+        "generator_image" | "generator-image" => config.generator_image = parse_image(&value)?,
+        "generator_matrix" | "generator-matrix" => {
+            config.generator_matrix = parse_yaml_list(&value)
+                .context("Invalid YAML list for generator_matrix (example: [v7.6.0, v7.17.0])")?;
+        }
+        "generator_java_opts" | "generator-java-opts" => {
+            config.generator_java_opts = (!value.trim().is_empty()).then_some(value);
+        }
+        "redocly_image" | "redocly-image" => config.redocly_image = parse_image(&value)?,
+        "schemathesis_image" | "schemathesis-image" => {
+            config.schemathesis_image = parse_image(&value)?
+        }
+        "prism_image" | "prism-image" => config.prism_image = parse_image(&value)?,
+        "mock_port" | "mock-port" => config.mock_port = parse_port(&value)?,
+        "serve_port" | "serve-port" => config.serve_port = parse_port(&value)?,
+        "yaml_style" | "yaml-style" => config.yaml_style = parse_bool(&value)?,
+        "spellcheck" => config.spellcheck = parse_bool(&value)?,
+        "spellcheck_dictionary" | "spellcheck-dictionary" => {
+            config.spellcheck_dictionary = (!value.is_empty()).then_some(value);
+        }
+        "cspell_image" | "cspell-image" => config.cspell_image = parse_image(&value)?,
+        "downconvert" => {
+            let steps = parse_yaml_list(&value)
+                .context("Invalid YAML list for downconvert (example: [generate, contract])")?;
+            for step in &steps {
+                let (name, generator) = step.split_once('.').unwrap_or((step, ""));
+                if !matches!(name, "generate" | "contract" | "mock")
+                    || (!generator.is_empty() && name != "generate")
+                {
+                    bail!(
+                        "Invalid downconvert step {step} (expected generate, generate.<generator>, contract, or mock)"
+                    );
+                }
+            }
+            config.downconvert = steps;
+        }
+        "lint_config" | "lint-config" => {
+            config.lint_config = (!value.is_empty()).then_some(value);
+        }
+        "plugin_dir" | "plugin-dir" => {
+            config.plugin_dir = (!value.is_empty()).then_some(value);
+        }
         "manage_gitignore" | "manage-gitignore" => config.manage_gitignore = parse_bool(&value)?,
-        _ => bail!("Unknown config key: {key}"),
+        "allowed_failures" | "allowed-failures" => {
+            config.allowed_failures = parse_yaml_list(&value)
+                .context("Invalid YAML list for allowed_failures (example: [typescript-nestjs])")?;
+        }
+        "allowed_content_types" | "allowed-content-types" => {
+            config.allowed_content_types = parse_yaml_list(&value).context(
+                "Invalid YAML list for allowed_content_types (example: [application/json])",
+            )?;
+        }
+        "artifact_url_template" | "artifact-url-template" => {
+            config.artifact_url_template = (!value.is_empty()).then_some(value);
+        }
+        "metrics_textfile" | "metrics-textfile" => {
+            config.metrics_textfile = (!value.is_empty()).then_some(value);
+        }
+        "history_runs" | "history-runs" => match value.trim().parse() {
+            Ok(runs) => config.history_runs = runs,
+            Err(_) => bail!("Invalid history_runs: {value} (expected a number of runs)"),
+        },
+        "badge_label" | "badge-label" => config.badge_label = value,
+        "badge_style" | "badge-style" => config.badge_style = parse_badge_style(&value)?,
+        "compile_cache" | "compile-cache" => config.compile_cache = parse_bool(&value)?,
+        "compile_cache_dir" | "compile-cache-dir" => {
+            config.compile_cache_dir = (!value.is_empty()).then_some(value);
+        }
+        "warm_builders" | "warm-builders" => config.warm_builders = parse_bool(&value)?,
+        "generate_batch" | "generate-batch" => config.generate_batch = parse_bool(&value)?,
+        "strict" => config.strict = parse_bool(&value)?,
+        "container_engine" | "container-engine" => config.container_engine = parse_engine(&value)?,
+        "docker_context" | "docker-context" => {
+            config.docker_context = (!value.is_empty()).then_some(value);
+        }
+        "offline_fallback" | "offline-fallback" => config.offline_fallback = parse_bool(&value)?,
+        "platform" => config.platform = parse_platform(&value)?,
+        "image_update_check" | "image-update-check" => {
+            config.image_update_check = parse_bool(&value)?
+        }
+        "timeouts" => {
+            let timeouts = match subkey {
+                Some(subkey) => {
+                    let mut timeouts = config.timeouts.clone();
+                    if value.is_empty() {
+                        timeouts.remove(subkey);
+                    } else {
+                        timeouts.insert(subkey.to_string(), value);
+                    }
+                    timeouts
+                }
+                None => parse_yaml_map(&value)
+                    .context("Invalid YAML map for timeouts (example: {compile: 30m})")?,
+            };
+            for (stage, raw) in &timeouts {
+                if !TIMEOUT_STAGES.contains(&stage.as_str()) {
+                    bail!(
+                        "Unknown timeout stage: {stage} (expected {})",
+                        TIMEOUT_STAGES.join(", ")
+                    );
+                }
+                parse_duration(raw).with_context(|| format!("Invalid timeouts.{stage}: {raw}"))?;
+            }
+            config.timeouts = timeouts;
+        }
+        "severity" => {
+            let severity = match subkey {
+                Some(subkey) => {
+                    let mut severity = config.severity.clone();
+                    if value.is_empty() {
+                        severity.remove(subkey);
+                    } else {
+                        severity.insert(subkey.to_string(), value);
+                    }
+                    severity
+                }
+                None => parse_yaml_map(&value)
+                    .context("Invalid YAML map for severity (example: {lint: warn})")?,
+            };
+            for (stage, level) in &severity {
+                if !SEVERITY_STAGES.contains(&stage.as_str()) {
+                    bail!(
+                        "Unknown severity stage: {stage} (expected {})",
+                        SEVERITY_STAGES.join(", ")
+                    );
+                }
+                if !matches!(level.as_str(), "fail" | "warn") {
+                    bail!("Invalid severity.{stage}: {level} (expected fail or warn)");
+                }
+            }
+            config.severity = severity;
+        }
+        "rules" => {
+            let rules = match subkey {
+                Some(subkey) => {
+                    let mut rules = config.rules.clone();
+                    if value.is_empty() {
+                        rules.remove(subkey);
+                    } else {
+                        rules.insert(subkey.to_string(), value);
+                    }
+                    rules
+                }
+                None => parse_yaml_map(&value)
+                    .context("Invalid YAML map for rules (example: {operation-tags: error})")?,
+            };
+            for (rule, level) in &rules {
+                let known = GOVERNANCE_RULES.iter().chain(&SECURITY_RULES);
+                if !known.clone().any(|(name, _)| name == rule) {
+                    bail!(
+                        "Unknown rule: {rule} (expected {})",
+                        known.map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+                    );
+                }
+                if !matches!(level.as_str(), "off" | "warn" | "error") {
+                    bail!("Invalid rules.{rule}: {level} (expected off, warn, or error)");
+                }
+            }
+            config.rules = rules;
+        }
+        "naming" => match subkey {
+            Some(kind) => {
+                let case = config.naming.get_mut(kind).with_context(|| {
+                    format!(
+                        "Unknown naming kind: {kind} (expected {})",
+                        NAMING_KINDS.join(", ")
+                    )
+                })?;
+                *case = if value.is_empty() {
+                    None
+                } else {
+                    Some(serde_yaml::from_str(&value).with_context(|| {
+                        format!(
+                            "Invalid naming.{kind}: {value} (expected camel, pascal, snake, kebab, or upper-snake)"
+                        )
+                    })?)
+                };
+            }
+            None if value.trim().is_empty() => config.naming = Naming::default(),
+            None => {
+                config.naming = serde_yaml::from_str(&value).context(
+                    "Invalid YAML map for naming (example: {paths: kebab, properties: camel, schemas: pascal})",
+                )?
+            }
+        },
+        "notifications" => match subkey {
+            Some("on") => config.notifications.on = parse_notify_on(&value)?,
+            Some(key) => {
+                let url = config.notifications.url_mut(key).with_context(|| {
+                    format!(
+                        "Unknown notifications key: {key} (expected {})",
+                        NOTIFICATION_KEYS.join(", ")
+                    )
+                })?;
+                *url = (!value.is_empty()).then_some(value);
+            }
+            None if value.trim().is_empty() => config.notifications = Notifications::default(),
+            None => {
+                config.notifications = serde_yaml::from_str(&value).context(
+                    "Invalid YAML map for notifications (example: {slack_webhook_url: '${SLACK_WEBHOOK_URL}', on: always})",
+                )?
+            }
+        },
+        "publish_endpoint" | "publish-endpoint" => {
+            config.publish_endpoint = (!value.is_empty()).then_some(value);
+        }
+        "publish_reports" | "publish-reports" => config.publish_reports = parse_bool(&value)?,
+        "pagination_params" | "pagination-params" => {
+            config.pagination_params = parse_yaml_list(&value)
+                .context("Invalid YAML list for pagination_params (example: [limit, offset])")?;
+        }
+        "hooks" => {
+            let hooks = match subkey {
+                Some(subkey) => {
+                    let mut hooks = config.hooks.clone();
+                    if value.is_empty() {
+                        hooks.remove(subkey);
+                    } else {
+                        hooks.insert(subkey.to_string(), value);
+                    }
+                    hooks
+                }
+                None => parse_yaml_map(&value).context(
+                    "Invalid YAML map for hooks (example: {post_validate: ./notify.sh})",
+                )?,
+            };
+            if let Some(event) = hooks
+                .keys()
+                .find(|event| !lifecycle::EVENTS.contains(&event.as_str()))
+            {
+                bail!(
+                    "Unknown hook: {event} (expected {})",
+                    lifecycle::EVENTS.join(", ")
+                );
+            }
+            config.hooks = hooks;
+        }
+        "container_env" | "container-env" => {
+            let env = match subkey {
+                Some(subkey) => {
+                    let mut env = config.container_env.clone();
+                    if value.is_empty() {
+                        env.remove(subkey);
+                    } else {
+                        env.insert(subkey.to_string(), value);
+                    }
+                    env
+                }
+                None => parse_yaml_map(&value).context(
+                    "Invalid YAML map for container_env (example: {GRADLE_OPTS: -Xmx2g})",
+                )?,
+            };
+            if let Some(name) = env.keys().find(|name| {
+                name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            }) {
+                bail!("Invalid environment variable name in container_env: {name}");
+            }
+            config.container_env = env;
+        }
+        "selinux_label" | "selinux-label" => config.selinux_label = parse_selinux_label(&value)?,
+        "container_user" | "container-user" => {
+            config.container_user = parse_container_user(&value)?
+        }
+        "container_memory" | "container-memory" => config.container_memory = parse_memory(&value)?,
+        "container_cpus" | "container-cpus" => config.container_cpus = parse_cpus(&value)?,
+        "docker_args" | "docker-args" => {
+            let docker_args = match subkey {
+                Some(subkey) => {
+                    let mut docker_args = config.docker_args.clone();
+                    let args = parse_args(&value)?;
+                    if args.is_empty() {
+                        docker_args.remove(subkey);
+                    } else {
+                        docker_args.insert(subkey.to_string(), args);
+                    }
+                    docker_args
+                }
+                None => {
+                    if value.trim().is_empty() {
+                        HashMap::new()
+                    } else {
+                        serde_yaml::from_str(&value).context(
+                            "Invalid YAML map for docker_args (example: {compile: [-v, /host:/container]})",
+                        )?
+                    }
+                }
+            };
+            for key in docker_args.keys() {
+                let (stage, generator) = match key.split_once('.') {
+                    Some((stage, generator)) => (stage, Some(generator)),
+                    None => (key.as_str(), None),
+                };
+                let valid = match generator {
+                    None => key == "all" || TIMEOUT_STAGES.contains(&stage),
+                    Some(generator) => {
+                        matches!(
+                            stage,
+                            "generate" | "compile" | "test" | "smoke" | "contract"
+                        ) && !generator.is_empty()
+                    }
+                };
+                if !valid {
+                    bail!(
+                        "Unknown docker_args key: {key} (expected all, a stage, or <stage>.<generator> for generate, compile, test, smoke, or contract)"
+                    );
+                }
+            }
+            config.docker_args = docker_args;
+        }
+        "profiles" => {
+            let profiles = match subkey {
+                Some(name) => {
+                    let mut profiles = config.profiles.clone();
+                    let profile: Mapping = if value.trim().is_empty() {
+                        Mapping::new()
+                    } else {
+                        serde_yaml::from_str(&value)
+                            .context("Invalid YAML map for a profile (example: {compile: false})")?
+                    };
+                    if profile.is_empty() {
+                        profiles.remove(name);
+                    } else {
+                        profiles.insert(name.to_string(), profile);
+                    }
+                    profiles
+                }
+                None if value.trim().is_empty() => BTreeMap::new(),
+                None => serde_yaml::from_str(&value)
+                    .context("Invalid YAML map for profiles (example: {fast: {compile: false}})")?,
+            };
+            for (name, profile) in &profiles {
+                let mut scratch = Config::default();
+                for (key, value) in profile {
+                    let key = key.as_str().unwrap_or_default();
+                    set_raw(&mut scratch, key, value)
+                        .with_context(|| format!("Invalid profiles.{name}.{key}"))?;
+                }
+            }
+            config.profiles = profiles;
+        }
+        _ => bail!(unknown_config_key(key)),
+    }
+    Ok(())
+}
+
+fn check_custom_generator(name: &str, generator: &CustomGenerator) -> Result<()> {
+    if name.is_empty() || name.contains(['@', ',', '/']) || name.contains(char::is_whitespace) {
+        bail!("Invalid generator name: {name}");
+    }
+    if SUPPORTED_SERVER_GENERATORS.contains(&name) || SUPPORTED_CLIENT_GENERATORS.contains(&name) {
+        bail!("{name} is a built-in generator; use generator_overrides to change its config");
+    }
+    if !matches!(generator.scope.as_str(), "server" | "client") {
+        bail!(
+            "Invalid scope: {} (expected server or client)",
+            generator.scope
+        );
+    }
+    if generator.config.trim().is_empty() {
+        bail!("config is required");
+    }
+    match (&generator.service, &generator.image, &generator.command) {
+        (Some(_), None, None) | (None, None, None) | (None, Some(_), Some(_)) => Ok(()),
+        (Some(_), _, _) => bail!("service can't be combined with image and command"),
+        _ => bail!("image and command must be set together"),
+    }
+}
+
+fn check_custom_step(name: &str, step: &CustomStep) -> Result<()> {
+    if name.is_empty() || name.contains('/') || name.contains(char::is_whitespace) {
+        bail!("Invalid step name: {name}");
+    }
+    parse_image(&step.image)?;
+    if step.command.is_empty() {
+        bail!("command is required");
+    }
+    if !TIMEOUT_STAGES.contains(&step.after.as_str()) {
+        bail!(
+            "Invalid after: {} (expected one of {})",
+            step.after,
+            TIMEOUT_STAGES.join(", ")
+        );
+    }
+    for mount in &step.mounts {
+        let parts: Vec<&str> = mount.split(':').collect();
+        let valid = match parts.as_slice() {
+            [host, target] => !host.is_empty() && target.starts_with('/'),
+            [host, target, "ro" | "rw"] => !host.is_empty() && target.starts_with('/'),
+            _ => false,
+        };
+        if !valid || parts[1] == "/work" {
+            bail!("Invalid mount: {mount} (expected host:/container/path[:ro])");
+        }
     }
     Ok(())
 }
@@ -158,6 +2170,128 @@ fn parse_mode(raw: &str) -> Result<Mode> {
     }
 }
 
+fn parse_engine(raw: &str) -> Result<EngineKind> {
+    match raw.trim().to_lowercase().as_str() {
+        "auto" => Ok(EngineKind::Auto),
+        "docker" => Ok(EngineKind::Docker),
+        "podman" => Ok(EngineKind::Podman),
+        "nerdctl" => Ok(EngineKind::Nerdctl),
+        _ => bail!("Invalid container_engine: {raw} (expected auto, docker, podman, or nerdctl)"),
+    }
+}
+
+/// An image reference, optionally pinned by digest (`repo@sha256:<64 hex>`).
+fn parse_image(raw: &str) -> Result<String> {
+    let image = raw.trim();
+    if image.is_empty() {
+        bail!("Image must not be empty");
+    }
+    if let Some((_, digest)) = image.split_once('@') {
+        let valid = digest.strip_prefix("sha256:").is_some_and(|hex| {
+            hex.len() == 64
+                && hex
+                    .chars()
+                    .all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase())
+        });
+        if !valid {
+            bail!("Invalid image digest: {image} (expected <image>@sha256:<64 hex digits>)");
+        }
+    }
+    Ok(image.to_string())
+}
+
+fn parse_platform(raw: &str) -> Result<Option<String>> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(None);
+    }
+    let parts: Vec<&str> = raw.split('/').collect();
+    if !(2..=3).contains(&parts.len()) || parts.iter().any(|part| part.is_empty()) {
+        bail!("Invalid platform: {raw} (expected os/arch, e.g. linux/amd64)");
+    }
+    Ok(Some(raw.to_string()))
+}
+
+fn parse_port(raw: &str) -> Result<u16> {
+    match raw.trim().parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => bail!("Invalid port: {raw} (expected 1-65535)"),
+    }
+}
+
+fn parse_selinux_label(raw: &str) -> Result<SelinuxLabel> {
+    match raw.trim() {
+        "auto" => Ok(SelinuxLabel::Auto),
+        "none" => Ok(SelinuxLabel::None),
+        "shared" | "z" => Ok(SelinuxLabel::Shared),
+        "private" | "Z" => Ok(SelinuxLabel::Private),
+        _ => bail!("Invalid selinux_label: {raw} (expected auto, none, shared, or private)"),
+    }
+}
+
+fn parse_notify_on(raw: &str) -> Result<NotifyOn> {
+    match raw.trim().to_lowercase().as_str() {
+        "failure" => Ok(NotifyOn::Failure),
+        "always" => Ok(NotifyOn::Always),
+        _ => bail!("Invalid notifications.on: {raw} (expected failure or always)"),
+    }
+}
+
+fn parse_badge_style(raw: &str) -> Result<BadgeStyle> {
+    match raw.trim().to_lowercase().as_str() {
+        "flat" => Ok(BadgeStyle::Flat),
+        "flat-square" => Ok(BadgeStyle::FlatSquare),
+        "for-the-badge" => Ok(BadgeStyle::ForTheBadge),
+        _ => bail!("Invalid badge_style: {raw} (expected flat, flat-square, or for-the-badge)"),
+    }
+}
+
+fn parse_container_user(raw: &str) -> Result<String> {
+    let user = raw.trim();
+    let valid_part = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    };
+    let valid = match user.split_once(':') {
+        Some((uid, gid)) => valid_part(uid) && valid_part(gid),
+        None => valid_part(user),
+    };
+    if !valid {
+        bail!("Invalid container_user: {raw} (expected auto, root, none, or <uid>[:<gid>])");
+    }
+    Ok(user.to_string())
+}
+
+/// A memory size such as `2g` or `512m`, as `--memory` takes it.
+fn parse_memory(raw: &str) -> Result<Option<String>> {
+    let memory = raw.trim().to_lowercase();
+    if memory.is_empty() {
+        return Ok(None);
+    }
+    let digits = memory.trim_end_matches(['b', 'k', 'm', 'g']);
+    let unit = &memory[digits.len()..];
+    if digits.is_empty()
+        || !digits.chars().all(|c| c.is_ascii_digit())
+        || !matches!(unit, "" | "b" | "k" | "m" | "g")
+    {
+        bail!("Invalid container_memory: {raw} (expected a size such as 512m or 2g)");
+    }
+    Ok(Some(memory))
+}
+
+fn parse_cpus(raw: &str) -> Result<Option<String>> {
+    let cpus = raw.trim();
+    if cpus.is_empty() {
+        return Ok(None);
+    }
+    match cpus.parse::<f64>() {
+        Ok(value) if value > 0.0 && value.is_finite() => Ok(Some(cpus.to_string())),
+        _ => bail!("Invalid container_cpus: {raw} (expected a positive number such as 1.5)"),
+    }
+}
+
 fn parse_bool(raw: &str) -> Result<bool> {
     match raw.trim().to_lowercase().as_str() {
         "true" | "1" | "yes" | "y" => Ok(true),
@@ -166,13 +2300,30 @@ fn parse_bool(raw: &str) -> Result<bool> {
     }
 }
 
+/// A YAML list, or a comma-separated one as the command-line flags take it.
 fn parse_yaml_list(raw: &str) -> Result<Vec<String>> {
     if raw.trim().is_empty() {
         return Ok(Vec::new());
     }
+    if !raw.trim_start().starts_with(['[', '-']) {
+        return Ok(raw
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect());
+    }
     serde_yaml::from_str(raw).context("Failed to parse as YAML list")
 }
 
+/// A YAML list of arguments, or a plain string split on whitespace.
+fn parse_args(raw: &str) -> Result<Vec<String>> {
+    if raw.trim_start().starts_with('[') {
+        return parse_yaml_list(raw);
+    }
+    Ok(raw.split_whitespace().map(str::to_string).collect())
+}
+
 fn parse_yaml_map(raw: &str) -> Result<HashMap<String, String>> {
     if raw.trim().is_empty() {
         return Ok(HashMap::new());
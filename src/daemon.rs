@@ -0,0 +1,421 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::output::Output;
+use crate::results::{self, RunResults};
+use crate::steps;
+use crate::util::OAV_DIR;
+
+/// Stages `POST /validate` can skip, as `--skip-<stage>`.
+const SKIPPABLE: [&str; 7] = [
+    "lint", "security", "generate", "compile", "test", "smoke", "contract",
+];
+/// Largest request body accepted.
+const MAX_BODY: usize = 64 * 1024;
+/// How often a started validation is checked for its run ID.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Options of `POST /validate`, as the `oav validate` flags of the same names.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ValidateRequest {
+    profile: Option<String>,
+    strict: bool,
+    fail_fast: bool,
+    no_cache: bool,
+    skip: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Started {
+    id: String,
+    state: &'static str,
+    run: String,
+    report: String,
+}
+
+#[derive(Serialize)]
+struct RunState<'a> {
+    id: &'a str,
+    /// `running`, `passed`, or `failed`.
+    state: &'static str,
+    passed: usize,
+    failed: usize,
+    results: &'a RunResults,
+}
+
+#[derive(Serialize)]
+struct RunList {
+    runs: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ApiError<'a> {
+    error: &'a str,
+}
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub body: String,
+}
+
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+    pub location: Option<String>,
+}
+
+impl Response {
+    fn json<T: Serialize>(status: u16, body: &T) -> Result<Self> {
+        Ok(Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_string_pretty(body)?.into_bytes(),
+            location: None,
+        })
+    }
+
+    pub fn error(status: u16, message: &str) -> Result<Self> {
+        Self::json(status, &ApiError { error: message })
+    }
+}
+
+/// The validation the daemon started last, while it runs.
+struct Job {
+    id: String,
+    child: Child,
+}
+
+struct Daemon {
+    root: PathBuf,
+    config: Config,
+    job: Option<Job>,
+    /// Whether each validation the daemon started and saw finish passed.
+    finished: HashMap<String, bool>,
+}
+
+/// Serve the HTTP API on localhost `port` until interrupted, one request at
+/// a time. Validations run as `oav validate` child processes, one at a time
+/// as the workspace lock allows, and their results are read from
+/// `.oav/results`.
+pub fn serve(root: &Path, config: Config, port: u16, output: &Output) -> Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .with_context(|| format!("Failed to listen on port {port}"))?;
+    fs::create_dir_all(root.join(OAV_DIR).join("daemon"))
+        .context("Failed to create daemon directory")?;
+    output.println_always(&format!(
+        "Serving the oav API on http://127.0.0.1:{port} (Ctrl-C to stop)"
+    ));
+    let mut daemon = Daemon {
+        root: root.to_path_buf(),
+        config,
+        job: None,
+        finished: HashMap::new(),
+    };
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        daemon.reap();
+        let response = match read_request(&mut stream) {
+            Ok(request) => {
+                let response = daemon
+                    .handle(&request)
+                    .or_else(|err| Response::error(500, &format!("{err:#}")))?;
+                output.println(&format!(
+                    "{} {} {}",
+                    request.method, request.path, response.status
+                ));
+                response
+            }
+            Err(err) => Response::error(400, &format!("{err:#}"))?,
+        };
+        let _ = write_response(&mut stream, &response);
+    }
+    Ok(())
+}
+
+impl Daemon {
+    fn handle(&mut self, request: &Request) -> Result<Response> {
+        let segments: Vec<&str> = request
+            .path
+            .trim_matches('/')
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        match (request.method.as_str(), segments.as_slice()) {
+            ("POST", ["validate"]) => self.validate(&request.body),
+            ("GET", ["runs"]) => Response::json(
+                200,
+                &RunList {
+                    runs: results::run_ids(&self.root)?.into_iter().rev().collect(),
+                },
+            ),
+            ("GET", ["runs", id]) => self.run(id),
+            ("GET", ["report", id]) => self.report(id, &request.query),
+            (_, ["validate"] | ["runs"] | ["runs", _] | ["report", _]) => {
+                Response::error(405, &format!("{} is not allowed here", request.method))
+            }
+            _ => Response::error(404, &format!("No endpoint at {}", request.path)),
+        }
+    }
+
+    /// Start `oav validate` and answer with its run ID once it has one.
+    fn validate(&mut self, body: &str) -> Result<Response> {
+        if let Some(job) = &self.job {
+            return Response::error(409, &format!("Run {} is still running", job.id));
+        }
+        let options: ValidateRequest = if body.trim().is_empty() {
+            ValidateRequest::default()
+        } else {
+            match serde_json::from_str(body) {
+                Ok(options) => options,
+                Err(err) => return Response::error(400, &format!("Invalid request: {err:#}")),
+            }
+        };
+        if let Some(stage) = options
+            .skip
+            .iter()
+            .find(|stage| !SKIPPABLE.contains(&stage.as_str()))
+        {
+            return Response::error(
+                400,
+                &format!(
+                    "Unknown stage {stage} in skip (expected {})",
+                    SKIPPABLE.join(", ")
+                ),
+            );
+        }
+
+        let log_path = self.root.join(OAV_DIR).join("daemon").join("validate.log");
+        let log = File::create(&log_path)
+            .with_context(|| format!("Failed to create {}", log_path.display()))?;
+        let mut command = Command::new(env::current_exe().context("Failed to locate oav")?);
+        command
+            .current_dir(&self.root)
+            .args(["--quiet", "validate"])
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log);
+        if let Some(profile) = &options.profile {
+            command.args(["--profile", profile]);
+        }
+        for (flag, set) in [
+            ("--strict", options.strict),
+            ("--fail-fast", options.fail_fast),
+            ("--no-cache", options.no_cache),
+        ] {
+            if set {
+                command.arg(flag);
+            }
+        }
+        for stage in &options.skip {
+            command.arg(format!("--skip-{stage}"));
+        }
+        let earlier = results::run_ids(&self.root)?;
+        let mut child = command.spawn().context("Failed to start oav validate")?;
+
+        // The run ID ends in the process ID, and exists once the run has
+        // loaded its config and locked the workspace.
+        let suffix = format!("-{}", child.id());
+        let id = loop {
+            if let Some(id) = results::run_ids(&self.root)?
+                .into_iter()
+                .find(|id| id.ends_with(&suffix) && !earlier.contains(id))
+            {
+                break id;
+            }
+            if child.try_wait()?.is_some() {
+                let log = fs::read_to_string(&log_path).unwrap_or_default();
+                let status = if log.contains("Another oav run") {
+                    409
+                } else {
+                    500
+                };
+                return Response::error(status, log.trim());
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+        let run = format!("/runs/{id}");
+        let mut response = Response::json(
+            202,
+            &Started {
+                id: id.clone(),
+                state: "running",
+                run: run.clone(),
+                report: format!("/report/{id}"),
+            },
+        )?;
+        response.location = Some(run);
+        self.job = Some(Job { id, child });
+        Ok(response)
+    }
+
+    fn run(&self, id: &str) -> Result<Response> {
+        let Some(results) = self.load(id)? else {
+            return Response::error(404, &format!("No results for run {id}"));
+        };
+        let state = if self.job_id() == id {
+            "running"
+        } else {
+            match self.finished.get(id) {
+                Some(true) => "passed",
+                Some(false) => "failed",
+                None if results.failed() > 0 => "failed",
+                None => "passed",
+            }
+        };
+        Response::json(
+            200,
+            &RunState {
+                id,
+                state,
+                passed: results.passed(),
+                failed: results.failed(),
+                results: &results,
+            },
+        )
+    }
+
+    /// The run's dashboard, or with `?format=markdown` its Markdown summary.
+    fn report(&self, id: &str, query: &str) -> Result<Response> {
+        if !is_run_id(id) {
+            return Response::error(404, &format!("No results for run {id}"));
+        }
+        let Some((html, summary)) = steps::render_report(&self.root, &self.config, id)? else {
+            return Response::error(404, &format!("No results for run {id}"));
+        };
+        let markdown = query
+            .split('&')
+            .any(|pair| pair == "format=markdown" || pair == "format=md");
+        Ok(if markdown {
+            Response {
+                status: 200,
+                content_type: "text/markdown; charset=utf-8",
+                body: summary.into_bytes(),
+                location: None,
+            }
+        } else {
+            Response {
+                status: 200,
+                content_type: "text/html; charset=utf-8",
+                body: html.into_bytes(),
+                location: None,
+            }
+        })
+    }
+
+    fn load(&self, id: &str) -> Result<Option<RunResults>> {
+        if !is_run_id(id) {
+            return Ok(None);
+        }
+        results::load(&self.root, id)
+    }
+
+    fn job_id(&self) -> &str {
+        self.job.as_ref().map_or("", |job| job.id.as_str())
+    }
+
+    /// Note the result of the running validation once it has exited.
+    fn reap(&mut self) {
+        let Some(job) = &mut self.job else {
+            return;
+        };
+        if let Ok(Some(status)) = job.child.try_wait() {
+            self.finished.insert(job.id.clone(), status.success());
+            self.job = None;
+        }
+    }
+}
+
+/// Whether `id` can name a results file, and nothing outside the results
+/// directory.
+fn is_run_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !id.starts_with('.')
+}
+
+pub fn read_request(stream: &mut TcpStream) -> Result<Request> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        bail!("Malformed request line");
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (method, path, query) = (method.to_string(), path.to_string(), query.to_string());
+
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            break;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = value.trim().parse().context("Invalid Content-Length")?;
+        }
+    }
+    if length > MAX_BODY {
+        bail!("Request body is larger than {MAX_BODY} bytes");
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Request {
+        method,
+        path,
+        query,
+        body: String::from_utf8(body).context("Request body is not valid UTF-8")?,
+    })
+}
+
+pub fn write_response(stream: &mut TcpStream, response: &Response) -> Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        202 => "Accepted",
+        301 => "Moved Permanently",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        _ => "Internal Server Error",
+    };
+    let location = response
+        .location
+        .as_ref()
+        .map(|location| format!("Location: {location}\r\n"))
+        .unwrap_or_default();
+    write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{location}Connection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()?;
+    Ok(())
+}
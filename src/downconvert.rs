@@ -0,0 +1,424 @@
+use anyhow::{Context, Result};
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::results::ResultsStore;
+use crate::rules;
+use crate::template;
+use crate::util::{OAV_DIR, append_error, to_posix_path, write_atomic, write_log_header};
+
+/// The 3.0 conversion of a 3.1 spec, in `.oav`.
+pub const CONVERTED_SPEC: &str = "openapi-3.0.yaml";
+
+/// JSON Schema keywords OpenAPI 3.0 doesn't know, dropped from schemas.
+const UNSUPPORTED_KEYWORDS: [&str; 19] = [
+    "$schema",
+    "$id",
+    "$anchor",
+    "$defs",
+    "$comment",
+    "$dynamicRef",
+    "$dynamicAnchor",
+    "prefixItems",
+    "contains",
+    "minContains",
+    "maxContains",
+    "if",
+    "then",
+    "else",
+    "dependentSchemas",
+    "dependentRequired",
+    "unevaluatedProperties",
+    "unevaluatedItems",
+    "propertyNames",
+];
+
+/// A 3.0 conversion of the spec, written because it is OpenAPI 3.1 and
+/// some steps listed in `downconvert` can't read 3.1.
+pub struct Conversion {
+    /// The converted spec, relative to the workspace root.
+    pub path: PathBuf,
+    /// The steps that read it, of those the run has enabled.
+    pub steps: Vec<String>,
+    /// What the conversion dropped or approximated.
+    pub notes: Vec<String>,
+    log_path: PathBuf,
+}
+
+/// Write `.oav/openapi-3.0.yaml` if the spec is OpenAPI 3.1 and `downconvert`
+/// names any of `steps`, the run's enabled steps. Lint always reads the
+/// original.
+pub fn prepare(
+    root: &Path,
+    spec_path: &Path,
+    config: &Config,
+    steps: &[&str],
+) -> Result<Option<Conversion>> {
+    let steps: Vec<String> = steps
+        .iter()
+        .filter(|step| {
+            config.downconvert.iter().any(|entry| {
+                entry
+                    .split_once('.')
+                    .map_or(entry.as_str(), |(name, _)| name)
+                    == **step
+            })
+        })
+        .map(|step| step.to_string())
+        .collect();
+    if steps.is_empty() {
+        return Ok(None);
+    }
+    let doc = rules::load_document(&root.join(spec_path))?;
+    if rules::openapi_version(&doc) != Some((3, 1)) {
+        return Ok(None);
+    }
+
+    let (mut converted, notes) = convert(&doc);
+    // Relative references keep pointing at the original files.
+    let spec_dir = spec_path.parent().unwrap_or(Path::new(""));
+    if spec_dir != Path::new(OAV_DIR) {
+        let spec_dir = to_posix_path(spec_dir);
+        let prefix = if spec_dir.is_empty() {
+            "../".to_string()
+        } else {
+            format!("../{spec_dir}/")
+        };
+        template::rebase_refs(&mut converted, &prefix);
+    }
+    let path = Path::new(OAV_DIR).join(CONVERTED_SPEC);
+    write_atomic(&root.join(&path), &serde_yaml::to_string(&converted)?)
+        .context("Failed to write the OpenAPI 3.0 conversion")?;
+
+    let reports_dir = root.join(OAV_DIR).join("reports");
+    fs::create_dir_all(&reports_dir).context("Failed to create reports directory")?;
+    let log_path = reports_dir.join("downconvert.log");
+    let mut log = format!(
+        "Converted {} from OpenAPI 3.1 to 3.0 for: {}\n",
+        spec_path.display(),
+        steps.join(", ")
+    );
+    if notes.is_empty() {
+        log.push_str("Nothing was dropped.\n");
+    }
+    for note in &notes {
+        log.push_str(&format!("- {note}\n"));
+    }
+    write_log_header(&log_path, &format!("$ oav downconvert {}", path.display()))?;
+    append_error(&log_path, log.trim_end())?;
+    Ok(Some(Conversion {
+        path,
+        steps,
+        notes,
+        log_path,
+    }))
+}
+
+/// The spec `step` (`generate`, `contract`, or `mock`), or `generator` of
+/// the generate step, reads: the conversion if `downconvert` names it.
+pub fn spec_for<'a>(
+    conversion: Option<&'a Conversion>,
+    spec_path: &'a Path,
+    config: &Config,
+    step: &str,
+    generator: Option<&str>,
+) -> &'a Path {
+    match conversion {
+        Some(conversion) if config.downconverts(step, generator) => &conversion.path,
+        _ => spec_path,
+    }
+}
+
+impl Conversion {
+    pub fn log_path(&self) -> &Path {
+        &self.log_path
+    }
+
+    /// Record the conversion under each step that reads it, with its notes
+    /// as warnings.
+    pub fn record(&self, results: &ResultsStore) -> Result<()> {
+        for step in &self.steps {
+            results.record(step, "spec", "openapi-3.0", "ok", &self.log_path)?;
+            results.record_warnings(step, "spec", "openapi-3.0", self.notes.clone())?;
+        }
+        Ok(())
+    }
+}
+
+/// The document as OpenAPI 3.0.3, with notes on what didn't carry over.
+pub fn convert(doc: &Value) -> (Value, Vec<String>) {
+    let mut doc = doc.clone();
+    let mut notes = Vec::new();
+    let Some(root) = doc.as_mapping_mut() else {
+        return (doc, notes);
+    };
+    root.insert("openapi".into(), "3.0.3".into());
+    if root.remove("jsonSchemaDialect").is_some() {
+        notes.push("dropped jsonSchemaDialect".to_string());
+    }
+    if let Some(info) = root.get_mut("info").and_then(Value::as_mapping_mut) {
+        if info.remove("summary").is_some() {
+            notes.push("dropped info.summary".to_string());
+        }
+        if let Some(license) = info.get_mut("license").and_then(Value::as_mapping_mut)
+            && license.remove("identifier").is_some()
+        {
+            notes.push("dropped info.license.identifier".to_string());
+        }
+    }
+    if let Some(webhooks) = root.remove("webhooks") {
+        let count = webhooks.as_mapping().map_or(0, Mapping::len);
+        notes.push(format!(
+            "dropped {count} webhook(s), which 3.0 can't describe"
+        ));
+    }
+
+    // Path items under components are 3.1 only; inline the ones paths use.
+    let path_items = root
+        .get_mut("components")
+        .and_then(Value::as_mapping_mut)
+        .and_then(|components| components.remove("pathItems"));
+    if let Some(paths) = root.get_mut("paths").and_then(Value::as_mapping_mut) {
+        for (route, item) in paths.iter_mut() {
+            let Some(name) = item
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|reference| reference.strip_prefix("#/components/pathItems/"))
+            else {
+                continue;
+            };
+            match path_items.as_ref().and_then(|items| items.get(name)) {
+                Some(target) => *item = target.clone(),
+                None => notes.push(format!(
+                    "{} references a missing path item",
+                    route.as_str().unwrap_or_default()
+                )),
+            }
+        }
+    }
+    if !root.contains_key("paths") {
+        root.insert("paths".into(), Value::Mapping(Mapping::new()));
+    }
+    if let Some(schemes) = root
+        .get_mut("components")
+        .and_then(|components| components.get_mut("securitySchemes"))
+        .and_then(Value::as_mapping_mut)
+    {
+        schemes.retain(|name, scheme| {
+            let mutual_tls = scheme.get("type").and_then(Value::as_str) == Some("mutualTLS");
+            if mutual_tls {
+                notes.push(format!(
+                    "dropped mutualTLS security scheme {}",
+                    name.as_str().unwrap_or_default()
+                ));
+            }
+            !mutual_tls
+        });
+    }
+
+    walk(&mut doc, "#", false, &mut notes);
+    (doc, notes)
+}
+
+/// Convert every schema below `value`: `schema` values and the entries of
+/// `components/schemas`. Examples are data and skipped. Outside schemas,
+/// 3.0 references can't have siblings, so they are dropped.
+fn walk(value: &mut Value, path: &str, schemas: bool, notes: &mut Vec<String>) {
+    match value {
+        Value::Mapping(mapping) => {
+            if mapping.contains_key("$ref") {
+                mapping.retain(|key, _| key.as_str() == Some("$ref"));
+                return;
+            }
+            for (key, child) in mapping.iter_mut() {
+                let key = key.as_str().unwrap_or_default();
+                let child_path = rules::pointer(path, key);
+                if schemas {
+                    convert_schema(child, &child_path, notes);
+                    continue;
+                }
+                match key {
+                    "example" | "examples" => {}
+                    "schema" => convert_schema(child, &child_path, notes),
+                    _ => walk(
+                        child,
+                        &child_path,
+                        path == "#/components" && key == "schemas",
+                        notes,
+                    ),
+                }
+            }
+        }
+        Value::Sequence(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                walk(item, &rules::pointer(path, &idx.to_string()), false, notes);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn convert_schema(schema: &mut Value, path: &str, notes: &mut Vec<String>) {
+    let Some(mapping) = schema.as_mapping_mut() else {
+        return;
+    };
+
+    // 3.0 ignores the siblings of `$ref`.
+    if mapping.contains_key("$ref") && mapping.len() > 1 {
+        let reference = mapping.remove("$ref").unwrap_or_default();
+        let mut wrapped = Mapping::new();
+        wrapped.insert("$ref".into(), reference);
+        let mut all_of = vec![Value::Mapping(wrapped)];
+        if let Some(Value::Sequence(existing)) = mapping.remove("allOf") {
+            all_of.extend(existing);
+        }
+        mapping.insert("allOf".into(), Value::Sequence(all_of));
+    }
+
+    match mapping.get("type").cloned() {
+        Some(Value::Sequence(types)) => {
+            let nullable = types.iter().any(|kind| kind.as_str() == Some("null"));
+            let rest: Vec<Value> = types
+                .into_iter()
+                .filter(|kind| kind.as_str() != Some("null"))
+                .collect();
+            match rest.as_slice() {
+                [] => {
+                    mapping.remove("type");
+                }
+                // In place, keeping the key order.
+                [kind] => {
+                    mapping.insert("type".into(), kind.clone());
+                }
+                _ if mapping.contains_key("anyOf") => {
+                    mapping.remove("type");
+                    notes.push(format!(
+                        "{path}: dropped type list {}, since the schema already has anyOf",
+                        type_list(&rest)
+                    ));
+                }
+                _ => {
+                    mapping.remove("type");
+                    let branches = rest
+                        .iter()
+                        .map(|kind| {
+                            let mut branch = Mapping::new();
+                            branch.insert("type".into(), kind.clone());
+                            Value::Mapping(branch)
+                        })
+                        .collect();
+                    mapping.insert("anyOf".into(), Value::Sequence(branches));
+                }
+            }
+            if nullable {
+                mapping.insert("nullable".into(), true.into());
+            }
+        }
+        Some(Value::String(kind)) if kind == "null" => {
+            mapping.remove("type");
+            mapping.insert("nullable".into(), true.into());
+        }
+        _ => {}
+    }
+
+    // `oneOf: [X, {type: 'null'}]` is a nullable X.
+    for keyword in ["oneOf", "anyOf"] {
+        let Some(Value::Sequence(branches)) = mapping.get_mut(keyword) else {
+            continue;
+        };
+        let before = branches.len();
+        branches.retain(|branch| branch.get("type").and_then(Value::as_str) != Some("null"));
+        if branches.len() == before {
+            continue;
+        }
+        if branches.len() == 1
+            && !mapping.contains_key("allOf")
+            && let Some(branches) = mapping.remove(keyword)
+        {
+            mapping.insert("allOf".into(), branches);
+        }
+        mapping.insert("nullable".into(), true.into());
+    }
+
+    if let Some(value) = mapping.remove("const")
+        && !mapping.contains_key("enum")
+    {
+        mapping.insert("enum".into(), Value::Sequence(vec![value]));
+    }
+    if let Some(Value::Sequence(examples)) = mapping.get("examples").cloned() {
+        mapping.remove("examples");
+        if let Some(first) = examples.into_iter().next()
+            && !mapping.contains_key("example")
+        {
+            mapping.insert("example".into(), first);
+        }
+    }
+    for (exclusive, bound) in [
+        ("exclusiveMinimum", "minimum"),
+        ("exclusiveMaximum", "maximum"),
+    ] {
+        if let Some(Value::Number(limit)) = mapping.get(exclusive).cloned() {
+            mapping.insert(bound.into(), Value::Number(limit));
+            mapping.insert(exclusive.into(), true.into());
+        }
+    }
+    if let Some(encoding) = mapping.remove("contentEncoding") {
+        if encoding.as_str() == Some("base64") && !mapping.contains_key("format") {
+            mapping.insert("format".into(), "byte".into());
+        }
+        mapping.remove("contentMediaType");
+    } else if mapping.remove("contentMediaType").is_some() && !mapping.contains_key("format") {
+        mapping.insert("format".into(), "binary".into());
+    }
+    if mapping.remove("patternProperties").is_some()
+        && !mapping.contains_key("additionalProperties")
+    {
+        notes.push(format!(
+            "{path}: patternProperties became additionalProperties: true"
+        ));
+        mapping.insert("additionalProperties".into(), true.into());
+    }
+    for keyword in UNSUPPORTED_KEYWORDS {
+        if mapping.remove(keyword).is_some() {
+            notes.push(format!("{path}: dropped {keyword}"));
+        }
+    }
+
+    for (key, child) in mapping.iter_mut() {
+        let key = key.as_str().unwrap_or_default().to_string();
+        let child_path = rules::pointer(path, &key);
+        match key.as_str() {
+            "properties" => {
+                if let Some(properties) = child.as_mapping_mut() {
+                    for (name, property) in properties.iter_mut() {
+                        let name = name.as_str().unwrap_or_default();
+                        convert_schema(property, &rules::pointer(&child_path, name), notes);
+                    }
+                }
+            }
+            "items" | "additionalProperties" | "not" => convert_schema(child, &child_path, notes),
+            "allOf" | "oneOf" | "anyOf" => {
+                if let Some(branches) = child.as_sequence_mut() {
+                    for (idx, branch) in branches.iter_mut().enumerate() {
+                        convert_schema(
+                            branch,
+                            &rules::pointer(&child_path, &idx.to_string()),
+                            notes,
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn type_list(types: &[Value]) -> String {
+    types
+        .iter()
+        .filter_map(Value::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::{Mutex, OnceLock};
+
+use crate::util::{format_timestamp, unix_now};
+
+/// `--events` value that writes the events to stdout.
+const STDOUT: &str = "-";
+
+struct Sink {
+    writer: Mutex<Box<dyn Write + Send>>,
+    stdout: bool,
+}
+
+static SINK: OnceLock<Sink> = OnceLock::new();
+
+/// One pipeline event, written as a line of JSON with an `event` field
+/// naming it.
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    RunStarted {
+        run_id: &'a str,
+    },
+    StepStarted {
+        step: &'a str,
+    },
+    StepFinished {
+        step: &'a str,
+        success: bool,
+        duration_ms: u64,
+    },
+    TaskFinished {
+        stage: &'a str,
+        scope: &'a str,
+        target: &'a str,
+        status: &'a str,
+        log_path: &'a str,
+    },
+    Finding {
+        check: &'a str,
+        rule: &'a str,
+        severity: &'a str,
+        path: &'a str,
+        message: &'a str,
+    },
+    RunFinished {
+        run_id: &'a str,
+        passed: usize,
+        failed: usize,
+    },
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    #[serde(flatten)]
+    event: &'a Event<'a>,
+    time: String,
+}
+
+/// Send events to `target`, a file or `-` for stdout, for the rest of the
+/// process.
+pub fn init(target: &str) -> Result<()> {
+    let (writer, stdout): (Box<dyn Write + Send>, bool) = if target == STDOUT {
+        (Box::new(io::stdout()), true)
+    } else {
+        let file = File::create(target)
+            .with_context(|| format!("Failed to create events file {target}"))?;
+        (Box::new(file), false)
+    };
+    let _ = SINK.set(Sink {
+        writer: Mutex::new(writer),
+        stdout,
+    });
+    Ok(())
+}
+
+/// Whether events go to stdout, which then carries nothing else.
+pub fn to_stdout() -> bool {
+    SINK.get().is_some_and(|sink| sink.stdout)
+}
+
+/// Write `event` if `--events` was given. Events never fail the run.
+pub fn emit(event: &Event) {
+    let Some(sink) = SINK.get() else {
+        return;
+    };
+    let Ok(line) = serde_json::to_string(&Line {
+        event,
+        time: format_timestamp(unix_now()),
+    }) else {
+        return;
+    };
+    let mut writer = sink.writer.lock().unwrap_or_else(|err| err.into_inner());
+    let _ = writeln!(writer, "{line}").and_then(|_| writer.flush());
+}
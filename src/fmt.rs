@@ -0,0 +1,378 @@
+use anyhow::{Context, Result, bail};
+use serde_yaml::{Mapping, Value};
+
+/// Optional reorderings on top of the conventional key order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    pub sort_paths: bool,
+    pub sort_tags: bool,
+}
+
+const ROOT: &[&str] = &[
+    "openapi",
+    "info",
+    "jsonSchemaDialect",
+    "servers",
+    "security",
+    "tags",
+    "externalDocs",
+    "paths",
+    "webhooks",
+    "components",
+];
+const INFO: &[&str] = &[
+    "title",
+    "summary",
+    "description",
+    "termsOfService",
+    "contact",
+    "license",
+    "version",
+];
+const TAG: &[&str] = &["name", "description", "externalDocs"];
+const PATH_ITEM: &[&str] = &[
+    "$ref",
+    "summary",
+    "description",
+    "servers",
+    "parameters",
+    "get",
+    "put",
+    "post",
+    "delete",
+    "options",
+    "head",
+    "patch",
+    "trace",
+];
+const OPERATION: &[&str] = &[
+    "tags",
+    "summary",
+    "description",
+    "externalDocs",
+    "operationId",
+    "parameters",
+    "requestBody",
+    "responses",
+    "callbacks",
+    "deprecated",
+    "security",
+    "servers",
+];
+const PARAMETER: &[&str] = &[
+    "$ref",
+    "name",
+    "in",
+    "description",
+    "required",
+    "deprecated",
+    "allowEmptyValue",
+    "style",
+    "explode",
+    "allowReserved",
+    "schema",
+    "example",
+    "examples",
+    "content",
+];
+const REQUEST_BODY: &[&str] = &["$ref", "description", "required", "content"];
+const RESPONSE: &[&str] = &["$ref", "description", "headers", "content", "links"];
+const MEDIA_TYPE: &[&str] = &["schema", "example", "examples", "encoding"];
+const EXAMPLE: &[&str] = &["$ref", "summary", "description", "value", "externalValue"];
+const COMPONENTS: &[&str] = &[
+    "schemas",
+    "responses",
+    "parameters",
+    "examples",
+    "requestBodies",
+    "headers",
+    "securitySchemes",
+    "links",
+    "callbacks",
+    "pathItems",
+];
+const SCHEMA: &[&str] = &[
+    "$ref",
+    "$schema",
+    "$id",
+    "title",
+    "description",
+    "type",
+    "format",
+    "enum",
+    "const",
+    "default",
+    "nullable",
+    "readOnly",
+    "writeOnly",
+    "deprecated",
+    "discriminator",
+    "required",
+    "properties",
+    "additionalProperties",
+    "items",
+    "prefixItems",
+    "allOf",
+    "oneOf",
+    "anyOf",
+    "not",
+    "minimum",
+    "exclusiveMinimum",
+    "maximum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+    "minProperties",
+    "maxProperties",
+    "xml",
+    "externalDocs",
+    "example",
+    "examples",
+];
+
+/// OpenAPI object a mapping holds, which decides its key order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Root,
+    Info,
+    Tag,
+    /// `paths`: routes to path items, sortable by route.
+    Paths,
+    PathItem,
+    Operation,
+    Parameter,
+    RequestBody,
+    Responses,
+    Response,
+    MediaType,
+    Example,
+    Callback,
+    Components,
+    Schema,
+    /// Kept in order, with its children too.
+    Other,
+}
+
+/// What a key's value holds.
+enum Child {
+    /// An object of this kind, or a list of them.
+    One(Kind),
+    /// A map of names to objects of this kind.
+    Each(Kind),
+    /// Example or default data, left untouched.
+    Data,
+}
+
+/// The spec with keys in conventional OpenAPI order (unknown keys after
+/// known ones, `x-` extensions last) and responses by status code, emitted
+/// as two-space YAML, or indented JSON when `as_json`. Comments would be
+/// lost, so a YAML spec with comments is an error.
+pub fn format(content: &str, as_json: bool, options: Options) -> Result<String> {
+    if !as_json && has_comments(content) {
+        bail!(
+            "The spec has comments, which formatting would drop; move them into descriptions or x- extensions first"
+        );
+    }
+    let doc: Value = serde_yaml::from_str(content).context("Failed to parse spec")?;
+    let doc = reorder(&doc, Kind::Root, options);
+    if as_json {
+        serde_json::to_string_pretty(&doc)
+            .map(|json| json + "\n")
+            .context("Failed to serialize spec")
+    } else {
+        serde_yaml::to_string(&doc).context("Failed to serialize spec")
+    }
+}
+
+fn reorder(value: &Value, kind: Kind, options: Options) -> Value {
+    match value {
+        Value::Sequence(items) => {
+            let mut items: Vec<Value> = items
+                .iter()
+                .map(|item| reorder(item, kind, options))
+                .collect();
+            if kind == Kind::Tag && options.sort_tags {
+                items.sort_by_key(|tag| key_of(tag.get("name")));
+            }
+            Value::Sequence(items)
+        }
+        Value::Mapping(mapping) => Value::Mapping(reorder_mapping(mapping, kind, options)),
+        other => other.clone(),
+    }
+}
+
+fn reorder_mapping(mapping: &Mapping, kind: Kind, options: Options) -> Mapping {
+    let mut entries: Vec<(&Value, Value)> = mapping
+        .iter()
+        .map(|(key, value)| {
+            let value = match child(kind, &key_of(Some(key))) {
+                Child::One(kind) => reorder(value, kind, options),
+                Child::Each(kind) => match value {
+                    Value::Mapping(map) => Value::Mapping(
+                        map.iter()
+                            .map(|(name, item)| (name.clone(), reorder(item, kind, options)))
+                            .collect(),
+                    ),
+                    other => other.clone(),
+                },
+                Child::Data => value.clone(),
+            };
+            (key, value)
+        })
+        .collect();
+
+    let order = order(kind);
+    // Stable, so unknown keys and extensions keep their relative order.
+    entries.sort_by_key(|(key, _)| {
+        let key = key_of(Some(key));
+        match order.iter().position(|known| *known == key) {
+            Some(idx) => (0, idx),
+            None if key.starts_with("x-") => (2, 0),
+            None => (1, 0),
+        }
+    });
+    match kind {
+        Kind::Responses => entries.sort_by_key(|(key, _)| {
+            let key = key_of(Some(key));
+            (key == "default", key)
+        }),
+        Kind::Paths if options.sort_paths => {
+            entries.sort_by_key(|(key, _)| key_of(Some(key)));
+        }
+        _ => {}
+    }
+    entries
+        .into_iter()
+        .map(|(key, value)| (key.clone(), value))
+        .collect()
+}
+
+fn order(kind: Kind) -> &'static [&'static str] {
+    match kind {
+        Kind::Root => ROOT,
+        Kind::Info => INFO,
+        Kind::Tag => TAG,
+        Kind::PathItem => PATH_ITEM,
+        Kind::Operation => OPERATION,
+        Kind::Parameter => PARAMETER,
+        Kind::RequestBody => REQUEST_BODY,
+        Kind::Response => RESPONSE,
+        Kind::MediaType => MEDIA_TYPE,
+        Kind::Example => EXAMPLE,
+        Kind::Components => COMPONENTS,
+        Kind::Schema => SCHEMA,
+        Kind::Paths | Kind::Responses | Kind::Callback | Kind::Other => &[],
+    }
+}
+
+fn child(kind: Kind, key: &str) -> Child {
+    match (kind, key) {
+        (_, "example" | "examples" | "enum" | "const" | "default" | "value")
+            if matches!(kind, Kind::Schema | Kind::Example) =>
+        {
+            Child::Data
+        }
+        (Kind::Parameter | Kind::MediaType, "example") => Child::Data,
+        (Kind::Root, "info") => Child::One(Kind::Info),
+        (Kind::Root, "tags") => Child::One(Kind::Tag),
+        (Kind::Root, "paths") => Child::One(Kind::Paths),
+        (Kind::Paths, _) => Child::One(Kind::PathItem),
+        (Kind::Root, "webhooks") => Child::Each(Kind::PathItem),
+        (Kind::Root, "components") => Child::One(Kind::Components),
+        (Kind::PathItem, "parameters") | (Kind::Operation, "parameters") => {
+            Child::One(Kind::Parameter)
+        }
+        (Kind::PathItem, method) if PATH_ITEM[5..].contains(&method) => Child::One(Kind::Operation),
+        (Kind::Operation, "requestBody") => Child::One(Kind::RequestBody),
+        (Kind::Operation, "responses") => Child::One(Kind::Responses),
+        (Kind::Operation | Kind::Components, "callbacks") => Child::Each(Kind::Callback),
+        (Kind::Callback, _) => Child::One(Kind::PathItem),
+        (Kind::Parameter | Kind::MediaType, "schema") => Child::One(Kind::Schema),
+        (Kind::Parameter | Kind::RequestBody | Kind::Response, "content") => {
+            Child::Each(Kind::MediaType)
+        }
+        (Kind::Parameter | Kind::MediaType, "examples") => Child::Each(Kind::Example),
+        (Kind::Responses, _) => Child::One(Kind::Response),
+        (Kind::Response, "headers") => Child::Each(Kind::Parameter),
+        (Kind::Components, "schemas") => Child::Each(Kind::Schema),
+        (Kind::Components, "responses") => Child::Each(Kind::Response),
+        (Kind::Components, "parameters" | "headers") => Child::Each(Kind::Parameter),
+        (Kind::Components, "examples") => Child::Each(Kind::Example),
+        (Kind::Components, "requestBodies") => Child::Each(Kind::RequestBody),
+        (Kind::Components, "pathItems") => Child::Each(Kind::PathItem),
+        (Kind::Schema, "properties" | "patternProperties" | "$defs") => Child::Each(Kind::Schema),
+        (
+            Kind::Schema,
+            "items"
+            | "additionalProperties"
+            | "not"
+            | "allOf"
+            | "oneOf"
+            | "anyOf"
+            | "prefixItems"
+            | "contains"
+            | "if"
+            | "then"
+            | "else",
+        ) => Child::One(Kind::Schema),
+        _ => Child::One(Kind::Other),
+    }
+}
+
+fn key_of(key: Option<&Value>) -> String {
+    match key {
+        Some(Value::String(key)) => key.clone(),
+        Some(Value::Number(key)) => key.to_string(),
+        Some(Value::Bool(key)) => key.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Whether `content` has YAML comments: a `#` at the start of a token,
+/// outside quotes and block scalars.
+pub fn has_comments(content: &str) -> bool {
+    let mut block_indent: Option<usize> = None;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        match block_indent {
+            Some(block) if indent > block => continue,
+            _ => block_indent = None,
+        }
+        let mut quote = None;
+        let mut previous = ' ';
+        for c in trimmed.chars() {
+            match (quote, c) {
+                (None, '#') if previous.is_whitespace() => return true,
+                (None, '"' | '\'')
+                    if previous.is_whitespace() || matches!(previous, ':' | '[' | '{' | ',') =>
+                {
+                    quote = Some(c)
+                }
+                (Some(open), c) if c == open => quote = None,
+                _ => {}
+            }
+            previous = c;
+        }
+        let value = trimmed
+            .rsplit_once(": ")
+            .map_or(trimmed, |(_, value)| value);
+        let value = value.strip_prefix("- ").unwrap_or(value);
+        if value.starts_with(['|', '>'])
+            && value[1..]
+                .chars()
+                .all(|c| matches!(c, '+' | '-') || c.is_ascii_digit())
+        {
+            block_indent = Some(indent);
+        }
+    }
+    false
+}
@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::results::RunResults;
+use crate::util::{OAV_DIR, copy_dir};
+
+const HISTORY_DIR: &str = "history";
+const RESULTS_FILE: &str = "results.json";
+
+/// The history of the workspace at `root`.
+pub fn dir(root: &Path) -> PathBuf {
+    root.join(OAV_DIR).join(HISTORY_DIR)
+}
+
+/// Keep the finished run `run` as `.oav/history/<run-id>/`: its results,
+/// `status.tsv`, `run.json`, and a copy of `.oav/reports`, so later runs
+/// don't overwrite them. Only the newest `keep` runs are kept.
+pub fn archive(root: &Path, run: &RunResults, keep: usize) -> Result<()> {
+    if keep == 0 {
+        return Ok(());
+    }
+    let history = dir(root);
+    let run_dir = history.join(&run.run_id);
+    if run_dir.exists() {
+        fs::remove_dir_all(&run_dir)
+            .with_context(|| format!("Failed to remove {}", run_dir.display()))?;
+    }
+    fs::create_dir_all(&run_dir).context("Failed to create history directory")?;
+
+    let oav_dir = root.join(OAV_DIR);
+    let reports = oav_dir.join("reports");
+    if reports.is_dir() {
+        copy_dir(&reports, &run_dir.join("reports"))?;
+    }
+    for name in ["status.tsv", "run.json"] {
+        let path = oav_dir.join(name);
+        if path.is_file() {
+            fs::copy(&path, run_dir.join(name))
+                .with_context(|| format!("Failed to copy {}", path.display()))?;
+        }
+    }
+    fs::write(
+        run_dir.join(RESULTS_FILE),
+        serde_json::to_string_pretty(run)?,
+    )
+    .context("Failed to write run history")?;
+    prune(&history, keep)
+}
+
+/// The results of every run in the history, newest first.
+pub fn list(root: &Path) -> Result<Vec<RunResults>> {
+    let mut runs = Vec::new();
+    for run_dir in run_dirs(&dir(root))?.into_iter().rev() {
+        let Ok(content) = fs::read_to_string(run_dir.join(RESULTS_FILE)) else {
+            continue;
+        };
+        let run: RunResults = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", run_dir.display()))?;
+        runs.push(run);
+    }
+    Ok(runs)
+}
+
+/// The results of `run_id`, if the history keeps it.
+pub fn load(root: &Path, run_id: &str) -> Result<Option<RunResults>> {
+    let path = dir(root).join(run_id).join(RESULTS_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(None);
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Run directories, oldest first: run IDs start with their start time.
+fn run_dirs(history: &Path) -> Result<Vec<PathBuf>> {
+    let Ok(entries) = fs::read_dir(history) else {
+        return Ok(Vec::new());
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn prune(history: &Path, keep: usize) -> Result<()> {
+    let dirs = run_dirs(history)?;
+    if dirs.len() > keep {
+        for path in &dirs[..dirs.len() - keep] {
+            let _ = fs::remove_dir_all(path);
+        }
+    }
+    Ok(())
+}
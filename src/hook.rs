@@ -0,0 +1,159 @@
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli::HookKind;
+use crate::output::Output;
+
+const MARKER: &str = "# Installed by oav";
+const LINT_COMMAND: &str = "oav --quiet validate --skip-generate --skip-compile";
+const FULL_COMMAND: &str = "oav --quiet validate";
+
+pub fn install(
+    root: &Path,
+    output: &Output,
+    kind: HookKind,
+    full: bool,
+    force: bool,
+) -> Result<()> {
+    let command = if full { FULL_COMMAND } else { LINT_COMMAND };
+
+    if root.join(".pre-commit-config.yaml").is_file() {
+        output.println("Detected the pre-commit framework (.pre-commit-config.yaml).");
+        output.println("It manages .git/hooks itself; add this local hook to your config instead:");
+        output.println_always(&format!(
+            "\n  - repo: local\n    hooks:\n      - id: oav\n        name: oav\n        entry: {command}\n        language: system\n        pass_filenames: false\n        stages: [{}]\n",
+            kind.as_str()
+        ));
+        return Ok(());
+    }
+
+    if let Some(husky_dir) = husky_dir(root) {
+        let path = husky_dir.join(kind.as_str());
+        let mut content = fs::read_to_string(&path).unwrap_or_default();
+        if content.contains(MARKER) {
+            output.println(&format!("oav is already part of {}", path.display()));
+            return Ok(());
+        }
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(&format!("{MARKER}\n{command}\n"));
+        write_hook(&path, &content)?;
+        output.println(&format!("Detected husky; added oav to {}", path.display()));
+        return Ok(());
+    }
+
+    let path = hooks_dir(root)?.join(kind.as_str());
+    if path.exists() && !is_oav_hook(&path) && !force {
+        bail!(
+            "{} already exists and was not installed by oav. Use --force to overwrite it.",
+            path.display()
+        );
+    }
+    let content = format!(
+        "#!/bin/sh\n{MARKER}. Remove with `oav hook install {} --uninstall`.\nexec {command}\n",
+        kind.as_str()
+    );
+    write_hook(&path, &content)?;
+    output.println(&format!(
+        "Installed {} hook at {}",
+        kind.as_str(),
+        path.display()
+    ));
+    Ok(())
+}
+
+pub fn uninstall(root: &Path, output: &Output, kind: HookKind) -> Result<()> {
+    if let Some(husky_dir) = husky_dir(root) {
+        let path = husky_dir.join(kind.as_str());
+        if let Ok(content) = fs::read_to_string(&path)
+            && content.contains(MARKER)
+        {
+            let mut skip_next = false;
+            let kept: Vec<&str> = content
+                .lines()
+                .filter(|line| {
+                    if skip_next {
+                        skip_next = false;
+                        return false;
+                    }
+                    if line.starts_with(MARKER) {
+                        skip_next = true;
+                        return false;
+                    }
+                    true
+                })
+                .collect();
+            let mut new_content = kept.join("\n");
+            if !new_content.is_empty() {
+                new_content.push('\n');
+            }
+            fs::write(&path, new_content)
+                .with_context(|| format!("Failed to update {}", path.display()))?;
+            output.println(&format!("Removed oav from {}", path.display()));
+            return Ok(());
+        }
+    }
+
+    let path = hooks_dir(root)?.join(kind.as_str());
+    if !path.exists() {
+        output.println(&format!("No {} hook installed.", kind.as_str()));
+        return Ok(());
+    }
+    if !is_oav_hook(&path) {
+        bail!(
+            "{} was not installed by oav; leaving it in place.",
+            path.display()
+        );
+    }
+    fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    output.println(&format!("Removed {}", path.display()));
+    Ok(())
+}
+
+fn is_oav_hook(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|content| content.contains(MARKER))
+        .unwrap_or(false)
+}
+
+/// Husky keeps hooks in `.husky/` and points `core.hooksPath` at it.
+fn husky_dir(root: &Path) -> Option<PathBuf> {
+    let dir = root.join(".husky");
+    dir.is_dir().then_some(dir)
+}
+
+fn hooks_dir(root: &Path) -> Result<PathBuf> {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg("--git-path")
+        .arg("hooks")
+        .current_dir(root)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!("Not a git repository: {}", root.display());
+    }
+    let raw = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let path = PathBuf::from(raw);
+    let dir = if path.is_absolute() {
+        path
+    } else {
+        root.join(path)
+    };
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    Ok(dir)
+}
+
+fn write_hook(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to set permissions on {}", path.display()))?;
+    }
+    Ok(())
+}
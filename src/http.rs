@@ -0,0 +1,53 @@
+use anyhow::Result;
+use std::time::Duration;
+use ureq::Agent;
+use ureq::unversioned::multipart::Form;
+
+/// Largest response body read, well above any spec or tag listing.
+const MAX_BODY: u64 = 64 * 1024 * 1024;
+
+/// A client whose requests give up after `timeout`. Proxies are taken from
+/// `HTTPS_PROXY`, `HTTP_PROXY`, and `ALL_PROXY`, and error statuses are
+/// errors.
+fn agent(timeout: Duration) -> Agent {
+    Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .build()
+        .into()
+}
+
+/// The body of `url` as text.
+pub fn get(url: &str, timeout: Duration) -> Result<String> {
+    let mut response = agent(timeout).get(url).call()?;
+    let body = response
+        .body_mut()
+        .with_config()
+        .limit(MAX_BODY)
+        .read_to_string()?;
+    Ok(body)
+}
+
+/// POST the JSON document `json` to `url`.
+pub fn post_json(url: &str, json: &str, timeout: Duration) -> Result<()> {
+    agent(timeout)
+        .post(url)
+        .header("Content-Type", "application/json")
+        .send(json)?;
+    Ok(())
+}
+
+/// POST `form` to `url` as `multipart/form-data`, authorized by `token` as
+/// a bearer token if given, and return the response body.
+pub fn post_form(url: &str, form: Form, token: Option<&str>, timeout: Duration) -> Result<String> {
+    let mut request = agent(timeout).post(url);
+    if let Some(token) = token {
+        request = request.header("Authorization", format!("Bearer {token}"));
+    }
+    let mut response = request.send(form)?;
+    let body = response
+        .body_mut()
+        .with_config()
+        .limit(MAX_BODY)
+        .read_to_string()?;
+    Ok(body)
+}
@@ -0,0 +1,105 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+use crate::util::{format_timestamp, unix_now};
+
+/// Expected failures of steps past lint, kept next to `.oavc`.
+pub const FILE_NAME: &str = ".oav-known-issues.yaml";
+
+/// One expected failure: a generate, compile, test, smoke, or contract task, why it fails, and
+/// optionally the date (`YYYY-MM-DD`) after which it should be fixed.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct KnownIssue {
+    pub stage: String,
+    /// `server` or `client`; unset matches both.
+    #[serde(default)]
+    pub scope: Option<String>,
+    /// Generator name, or `<generator>@<version>` for one matrix variant.
+    pub target: String,
+    pub reason: String,
+    #[serde(default)]
+    pub expires: Option<String>,
+}
+
+impl KnownIssue {
+    /// Whether the issue covers a failure of `target` in `stage` and `scope`.
+    pub fn matches(&self, stage: &str, scope: &str, target: &str) -> bool {
+        let generator = target.split('@').next().unwrap_or(target);
+        self.stage == stage
+            && self.scope.as_deref().is_none_or(|s| s == scope)
+            && (self.target == target || self.target == generator)
+    }
+
+    /// Whether `expires` is before `today` (`YYYY-MM-DD`).
+    fn expired(&self, today: &str) -> bool {
+        self.expires
+            .as_deref()
+            .is_some_and(|expires| expires < today)
+    }
+}
+
+/// The issues listed in the known-issues file, split into active and expired
+/// ones. No file means no known issues.
+pub fn load(root: &Path) -> Result<(Vec<KnownIssue>, Vec<KnownIssue>)> {
+    let path = root.join(FILE_NAME);
+    if !path.is_file() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {FILE_NAME}"))?;
+    let issues: Vec<KnownIssue> = if content.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_yaml::from_str(&content).with_context(|| format!("Failed to parse {FILE_NAME}"))?
+    };
+    for issue in &issues {
+        check(issue).with_context(|| format!("Invalid entry in {FILE_NAME}"))?;
+    }
+    let today = &format_timestamp(unix_now())[..10];
+    Ok(issues.into_iter().partition(|issue| !issue.expired(today)))
+}
+
+fn check(issue: &KnownIssue) -> Result<()> {
+    if !matches!(
+        issue.stage.as_str(),
+        "generate" | "compile" | "test" | "smoke" | "contract"
+    ) {
+        bail!(
+            "{}: unknown stage {} (expected generate, compile, test, smoke, or contract)",
+            issue.target,
+            issue.stage
+        );
+    }
+    if let Some(scope) = &issue.scope
+        && !matches!(scope.as_str(), "server" | "client")
+    {
+        bail!(
+            "{}: unknown scope {scope} (expected server or client)",
+            issue.target
+        );
+    }
+    if issue.reason.trim().is_empty() {
+        bail!("{}: reason is required", issue.target);
+    }
+    if let Some(expires) = &issue.expires
+        && !is_date(expires)
+    {
+        bail!(
+            "{}: invalid expires {expires} (expected YYYY-MM-DD)",
+            issue.target
+        );
+    }
+    Ok(())
+}
+
+fn is_date(raw: &str) -> bool {
+    let bytes = raw.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(index, byte)| match index {
+            4 | 7 => *byte == b'-',
+            _ => byte.is_ascii_digit(),
+        })
+}
@@ -1,33 +1,92 @@
+mod baseline;
+mod ci;
 mod cli;
+mod completions;
 mod config;
+mod daemon;
 mod docker;
+mod downconvert;
+mod events;
+mod fmt;
+mod history;
+mod hook;
+mod http;
+mod known_issues;
+mod lifecycle;
+mod lock;
+mod lsp;
+mod man;
+mod manifest;
+mod mock;
+mod notify;
 mod output;
+mod plugins;
+mod publish;
+mod remote;
+mod results;
+mod rules;
+mod serve;
 mod steps;
+mod suppressions;
+mod template;
+mod updates;
 mod util;
 
 use anyhow::{Context, Result, bail};
-use clap::Parser;
+use clap::{CommandFactory, Parser};
+use clap_complete::CompleteEnv;
 use include_dir::{Dir, include_dir};
+use std::collections::HashSet;
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use cli::{Cli, Commands, ConfigCommand};
-use config::{CONFIG_FILE, Config};
+use cli::{
+    CiCommand, Cli, Commands, ConfigCommand, HookCommand, OutputFormat, Overrides, ReportCommand,
+    RunsCommand,
+};
+use config::Config;
+use lock::WorkspaceLock;
 use output::Output;
+use results::{MergedFinding, ResultsStore};
+use steps::StepOptions;
 use util::OAV_DIR;
 
 static ASSETS: Dir = include_dir!("$CARGO_MANIFEST_DIR/assets");
 
 pub fn run() -> Result<()> {
+    // Completion scripts run `COMPLETE=<shell> oav -- <words>`; answer and exit.
+    CompleteEnv::with_factory(Cli::command)
+        .var(completions::VAR)
+        .complete();
     let cli = Cli::parse();
     let root = env::current_dir().context("Failed to determine current directory")?;
-    let output = Output::new(cli.verbose, cli.quiet);
+    // Compact output is the findings alone, for editors to parse.
+    let compact = matches!(
+        cli.command,
+        Commands::Validate {
+            format: OutputFormat::Compact,
+            ..
+        } | Commands::Lint {
+            format: OutputFormat::Compact,
+            ..
+        }
+    );
+    if let Some(target) = &cli.events {
+        events::init(target)?;
+    }
+    // The language server speaks its protocol on stdout, and so may events.
+    let quiet =
+        cli.quiet || compact || events::to_stdout() || matches!(cli.command, Commands::Lsp { .. });
+    let output = Output::new(cli.verbose && !quiet, quiet);
 
     match cli.command {
         Commands::Init {
             spec,
+            from_url,
+            preset,
             mode,
             server_generators,
             client_generators,
@@ -36,39 +95,115 @@ pub fn run() -> Result<()> {
             &root,
             &output,
             spec,
+            from_url,
+            preset,
             mode,
             server_generators,
             client_generators,
             ignore_config,
         ),
         Commands::Validate {
-            spec,
-            mode,
-            server_generators,
-            client_generators,
-            skip_lint,
-            skip_generate,
-            skip_compile,
+            overrides,
+            no_cache,
+            fail_fast,
+            strict,
+            wait,
+            format,
         } => cmd_validate(
+            &root, &output, overrides, no_cache, fail_fast, strict, wait, format,
+        ),
+        Commands::Lint {
+            spec,
+            update_baseline,
+            fix,
+            out,
+            strict,
+            wait,
+            format,
+        } => cmd_lint(
             &root,
             &output,
             spec,
-            mode,
-            server_generators,
-            client_generators,
-            skip_lint,
-            skip_generate,
-            skip_compile,
+            update_baseline,
+            fix.then_some(out),
+            strict,
+            wait,
+            format,
+        ),
+        Commands::Generate {
+            spec,
+            only,
+            out,
+            strict,
+            no_cache,
+            wait,
+        } => cmd_generate(&root, &output, spec, only, out, strict, no_cache, wait),
+        Commands::Report { run, pdf, command } => match command {
+            Some(ReportCommand::Diff { before, after, out }) => {
+                cmd_report_diff(&root, &output, &before, &after, out)
+            }
+            None => cmd_report(&root, &output, run, pdf),
+        },
+        Commands::Runs { command } => cmd_runs(&root, &output, command),
+        Commands::Publish {
+            endpoint,
+            run,
+            reports,
+        } => cmd_publish(&root, &output, endpoint, run, reports),
+        Commands::Mock {
+            spec,
+            port,
+            dynamic,
+        } => cmd_mock(&root, &output, spec, port, dynamic),
+        Commands::Lsp { lint } => cmd_lsp(&root, &output, lint),
+        Commands::Serve { daemon, port, host } => cmd_serve(&root, &output, daemon, port, host),
+        Commands::Fmt {
+            spec,
+            check,
+            sort_paths,
+            sort_tags,
+        } => cmd_fmt(
+            &root,
+            &output,
+            spec,
+            check,
+            fmt::Options {
+                sort_paths,
+                sort_tags,
+            },
         ),
         Commands::Config { command } => cmd_config(&root, &output, command),
-        Commands::Clean => cmd_clean(&root, &output),
+        Commands::Clean {
+            reports,
+            generated,
+            cache,
+            compile_cache,
+            all,
+            dry_run,
+        } => cmd_clean(
+            &root,
+            &output,
+            reports,
+            generated,
+            cache,
+            compile_cache,
+            all,
+            dry_run,
+        ),
+        Commands::Hook { command } => cmd_hook(&root, &output, command),
+        Commands::Ci { command } => cmd_ci(&root, &output, command),
+        Commands::Man { out } => man::write(&out, &output),
+        Commands::Completions { shell } => completions::write_script(shell),
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn cmd_init(
     root: &Path,
     output: &Output,
     spec: Option<String>,
+    from_url: Option<String>,
+    preset: Option<cli::Preset>,
     mode: Option<cli::Mode>,
     server_generators: Option<Vec<String>>,
     client_generators: Option<Vec<String>>,
@@ -78,14 +213,37 @@ fn cmd_init(
     util::ensure_oav_dir(root)?;
     util::add_gitignore_entries(root, &[".oav/"])?;
     if cfg.manage_gitignore && ignore_config {
-        util::add_gitignore_entries(root, &[".oavc"])?;
+        util::add_gitignore_entries(root, &[&config::file_name(root)])?;
     }
-    if let Some(s) = spec {
+    if let Some(url) = from_url {
+        let saved = util::download_spec(root, &url, spec.as_deref())?;
+        output.println(&format!("Downloaded {url} to {saved}"));
+        cfg.spec = Some(saved);
+    } else if let Some(s) = spec {
         cfg.spec = Some(s);
     }
     if cfg.spec.is_none() {
         cfg.spec = util::discover_spec(root)?;
     }
+    if let Some(preset) = preset {
+        config::apply_preset(&mut cfg, preset);
+    }
+    let (redocly_config, spectral_config) = steps::find_lint_configs(root);
+    if cfg.lint_config.is_none()
+        && let Some(path) = redocly_config
+    {
+        cfg.lint_config = Some(path.to_string());
+        output.println(&format!("Using {path} for linting (lint_config)"));
+    }
+    if !cfg.custom_steps.contains_key("spectral")
+        && let Some(path) = spectral_config
+    {
+        cfg.custom_steps
+            .insert("spectral".to_string(), steps::spectral_step(path));
+        output.println(&format!(
+            "Using {path} for linting with Spectral (custom_steps.spectral)"
+        ));
+    }
     if let Some(m) = mode {
         cfg.mode = m;
     }
@@ -106,128 +264,1140 @@ fn cmd_init(
     util::extract_assets(root, &ASSETS)?;
 
     output.println("Initialized OpenAPI Validator.");
-    output.println(&format!("Config: {}", root.join(CONFIG_FILE).display()));
+    output.println(&format!("Config: {}", config::path(root).display()));
     output.println(&format!("Workspace: {}", root.join(OAV_DIR).display()));
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn cmd_validate(
     root: &Path,
     output: &Output,
-    spec_override: Option<String>,
-    mode_override: Option<cli::Mode>,
-    server_generators: Option<Vec<String>>,
-    client_generators: Option<Vec<String>>,
-    skip_lint: bool,
-    skip_generate: bool,
-    skip_compile: bool,
+    overrides: Overrides,
+    no_cache: bool,
+    fail_fast: bool,
+    strict: bool,
+    wait: bool,
+    format: OutputFormat,
 ) -> Result<()> {
-    let mut cfg = config::load(root)?;
+    let (mut cfg, overlays) = config::load_effective(root, overrides.profile.as_deref())?;
     util::ensure_oav_dir(root)?;
+    let _lock = WorkspaceLock::acquire(root, wait, output)?;
     util::add_gitignore_entries(root, &[".oav/"])?;
     util::extract_assets(root, &ASSETS)?;
-    if let Some(s) = spec_override {
-        cfg.spec = Some(s);
-    }
-    if let Some(m) = mode_override {
-        cfg.mode = m;
-    }
-    if let Some(gens) = server_generators {
-        cfg.server_generators = gens;
+    if let Some(profile) = &overrides.profile {
+        output.println(&format!("Using profile {profile}"));
     }
-    if let Some(gens) = client_generators {
-        cfg.client_generators = gens;
+    if !overlays.env_keys().is_empty() {
+        output.println(&format!(
+            "Using {} from the environment",
+            overlays
+                .env_keys()
+                .iter()
+                .map(|key| config::env_var(key))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
     }
-    if skip_lint {
-        cfg.lint = false;
+    apply_overrides(&mut cfg, overrides);
+
+    let spec_path = resolve_spec(root, &mut cfg)?;
+    let conversion = downconvert::prepare(
+        root,
+        &spec_path,
+        &cfg,
+        &[("generate", cfg.generate), ("contract", cfg.contract)]
+            .into_iter()
+            .filter_map(|(step, enabled)| enabled.then_some(step))
+            .collect::<Vec<_>>(),
+    )?;
+    let mut offline = false;
+    if cfg.lint || cfg.generate || cfg.compile || cfg.contract || !cfg.custom_steps.is_empty() {
+        match select_engine(&cfg, output) {
+            Ok(()) => {}
+            Err(err) if cfg.lint && cfg.offline_fallback && !(strict || cfg.strict) => {
+                output.print_warning(&format!(
+                    "{err:#} Falling back to offline validation: native checks only, no Redocly, generate, or compile."
+                ));
+                offline = true;
+            }
+            Err(err) => return Err(err),
+        }
     }
-    if skip_generate {
+
+    util::prepare_runtime_dirs(root)?;
+    config::write(root, &overlays.strip(&cfg)?)?;
+    cfg.expand_generator_groups()?;
+    cfg.strict |= strict;
+    cfg.apply_strict();
+    if offline {
         cfg.generate = false;
-    }
-    if skip_compile {
         cfg.compile = false;
+        cfg.test = false;
+        cfg.smoke = false;
+        cfg.contract = false;
+        cfg.custom_steps.clear();
     }
-
-    let spec = if let Some(s) = cfg.spec.clone() {
-        s
-    } else if let Some(s) = util::discover_spec(root)? {
-        s
+    let conversion = conversion.filter(|_| !offline);
+    let results = ResultsStore::create(root)?
+        .with_fail_fast(fail_fast)
+        .with_allowed_failures(cfg.allowed_failures.clone())
+        .with_warn_stages(cfg.warn_stages())
+        .with_known_issues(known_issues(root, &cfg, output)?)
+        .with_baseline(baseline::load(root)?)
+        .with_suppressions(suppressions(root, &cfg)?);
+    if let Some(conversion) = &conversion {
+        announce_conversion(conversion, output);
+        conversion.record(&results)?;
+    }
+    if !lifecycle::run(root, &cfg, "pre_validate", &results, output)? {
+        std::process::exit(1);
+    }
+    let remote_workspace = if !offline
+        && (cfg.lint || cfg.generate || cfg.compile || cfg.contract || !cfg.custom_steps.is_empty())
+    {
+        remote::prepare(
+            root,
+            &remote_inputs(root, &spec_path, &cfg)?,
+            &results.run_id(),
+            output,
+        )?
     } else {
-        bail!("No OpenAPI spec found. Pass --spec or set spec in .oavc.");
+        None
     };
 
-    let spec_path = util::normalize_spec_path(root, &spec)?;
-    cfg.spec = Some(spec_path.to_string_lossy().to_string());
+    let mut failures = 0;
 
-    if cfg.lint || cfg.generate || cfg.compile {
-        docker::ensure_available()?;
+    if !offline
+        && (cfg.lint || cfg.generate || cfg.contract || !cfg.custom_steps.is_empty())
+        && !steps::pull(root, &cfg, &results, output)?
+    {
+        output.println("Some images failed to pull; tasks using them will fail");
     }
 
-    util::prepare_runtime_dirs(root)?;
-    config::write(root, &cfg)?;
-
-    let mut failures = 0;
-
     if cfg.lint {
-        let success = steps::run_step(output, "Lint", true, true, || {
-            steps::lint(root, &spec_path, &cfg.redocly_image, output)
-        })?;
-        if !success {
+        let success = steps::run_step(
+            output,
+            &results,
+            "Lint",
+            StepOptions {
+                warn_only: cfg.warn_only("lint"),
+                ..StepOptions::SUMMARY
+            },
+            || {
+                let linted = lint(root, &spec_path, &cfg, offline, &results, output)?;
+                if results.should_stop() {
+                    return Ok(false);
+                }
+                let hook = lifecycle::run(root, &cfg, "post_lint", &results, output)?;
+                Ok(linted && hook)
+            },
+        )?;
+        if !success && !cfg.warn_only("lint") {
+            failures += 1;
+        }
+    }
+    if !custom_steps(root, &spec_path, &cfg, "lint", &results, output)? {
+        failures += 1;
+    }
+
+    if cfg.security && !results.should_stop() {
+        let success = steps::run_step(
+            output,
+            &results,
+            "Security",
+            StepOptions {
+                warn_only: cfg.warn_only("security"),
+                ..StepOptions::SUMMARY
+            },
+            || steps::security(root, &spec_path, &cfg, &results),
+        )?;
+        if !success && !cfg.warn_only("security") {
             failures += 1;
         }
     }
 
-    if cfg.generate {
+    if cfg.generate && !results.should_stop() {
         output.phase_header("Generate");
-        let success = steps::run_step(output, "Generate", false, false, || {
-            steps::generate(root, &spec_path, &cfg, output)
+        let success = steps::run_step(output, &results, "Generate", StepOptions::QUIET, || {
+            steps::generate(
+                root,
+                &spec_path,
+                conversion.as_ref(),
+                &cfg,
+                !no_cache,
+                &results,
+                output,
+            )
         })?;
-        if !success {
+        let hook =
+            results.should_stop() || lifecycle::run(root, &cfg, "post_generate", &results, output)?;
+        if !(success && hook) && results.stage_failed("generate") {
             failures += 1;
         }
     }
+    if !custom_steps(root, &spec_path, &cfg, "generate", &results, output)? {
+        failures += 1;
+    }
 
-    if cfg.compile {
+    if cfg.compile && !results.should_stop() {
         if cfg.generate {
             output.phase_header("Compile");
-            let success = steps::run_step(output, "Compile", false, false, || {
-                steps::compile(root, &cfg, output)
+            let success = steps::run_step(output, &results, "Compile", StepOptions::QUIET, || {
+                steps::compile(root, &cfg, &results, output)
             })?;
-            if !success {
+            if !success && results.stage_failed("compile") {
                 failures += 1;
             }
         } else {
             output.println("Skipping compile (generate disabled)");
         }
     }
+    if !custom_steps(root, &spec_path, &cfg, "compile", &results, output)? {
+        failures += 1;
+    }
+
+    if cfg.test && !results.should_stop() {
+        if cfg.generate {
+            output.phase_header("Test");
+            let success = steps::run_step(output, &results, "Test", StepOptions::QUIET, || {
+                steps::test(root, &cfg, &results, output)
+            })?;
+            if !success && results.stage_failed("test") {
+                failures += 1;
+            }
+        } else {
+            output.println("Skipping test (generate disabled)");
+        }
+    }
+    if !custom_steps(root, &spec_path, &cfg, "test", &results, output)? {
+        failures += 1;
+    }
+
+    if cfg.smoke && !results.should_stop() {
+        if cfg.generate {
+            output.phase_header("Smoke");
+            let success = steps::run_step(output, &results, "Smoke", StepOptions::QUIET, || {
+                steps::smoke(root, &cfg, &results, output)
+            })?;
+            if !success && results.stage_failed("smoke") {
+                failures += 1;
+            }
+        } else {
+            output.println("Skipping smoke (generate disabled)");
+        }
+    }
+    if !custom_steps(root, &spec_path, &cfg, "smoke", &results, output)? {
+        failures += 1;
+    }
 
-    let _ = steps::run_step(output, "Report", true, true, || steps::report(root, output));
+    if cfg.contract && !results.should_stop() {
+        if cfg.generate || cfg.contract_url.is_some() {
+            output.phase_header("Contract");
+            let success =
+                steps::run_step(output, &results, "Contract", StepOptions::QUIET, || {
+                    let spec_path = downconvert::spec_for(
+                        conversion.as_ref(),
+                        &spec_path,
+                        &cfg,
+                        "contract",
+                        None,
+                    );
+                    steps::contract(root, spec_path, &cfg, &results, output)
+                })?;
+            if !success && results.stage_failed("contract") {
+                failures += 1;
+            }
+        } else {
+            output.println("Skipping contract (generate disabled and no contract_url)");
+        }
+    }
+    if !custom_steps(root, &spec_path, &cfg, "contract", &results, output)? {
+        failures += 1;
+    }
+
+    if results.should_stop() {
+        output.println("Stopped after the first failure (--fail-fast)");
+    }
+    drop(remote_workspace);
+
+    let _ = steps::run_step(output, &results, "Report", StepOptions::SUMMARY, || {
+        steps::report(root, &cfg, output)
+    });
+    if !lifecycle::run(root, &cfg, "post_validate", &results, output)? {
+        failures += 1;
+    }
 
     // Summary
-    let status_path = root.join(OAV_DIR).join("status.tsv");
-    let entries = steps::load_status_entries(&status_path).unwrap_or_default();
-    let passed = entries.iter().filter(|e| e.status == "ok").count();
-    let failed = entries.iter().filter(|e| e.status == "fail").count();
+    let run = results.snapshot();
+    manifest::write(root, &cfg, &spec_path, &run)?;
+    history::archive(root, &run, cfg.history_runs)?;
+    output.print_summary(run.passed(), run.failed());
+    events::emit(&events::Event::RunFinished {
+        run_id: &run.run_id,
+        passed: run.passed(),
+        failed: run.failed(),
+    });
+    if run.allowed_failures() > 0 {
+        output.println(&format!(
+            "{} allowed failure(s) in allowed_failures",
+            run.allowed_failures()
+        ));
+    }
+    if run.known_failures() > 0 {
+        output.println(&format!(
+            "{} known failure(s) in {}",
+            run.known_failures(),
+            known_issues::FILE_NAME
+        ));
+    }
+    if run.warned() > 0 {
+        output.println(&format!(
+            "{} failure(s) in warn-level steps (severity)",
+            run.warned()
+        ));
+    }
+
+    if format == OutputFormat::Compact {
+        print_compact(root, &cfg, &run.findings);
+    } else if !events::to_stdout() {
+        println!();
+        output.println_always(&format!(
+            "Dashboard: {}",
+            root.join(OAV_DIR)
+                .join("reports")
+                .join("dashboard.html")
+                .display()
+        ));
+    }
+    notify::send(root, &cfg, &run, output);
+    updates::check(root, &cfg, output);
+
+    if failures > 0 {
+        output.print_error("Validation failed. See dashboard for details.");
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Run the `custom_steps` placed after the `after` stage. Returns false if
+/// one failed.
+fn custom_steps(
+    root: &Path,
+    spec_path: &Path,
+    cfg: &Config,
+    after: &str,
+    results: &ResultsStore,
+    output: &Output,
+) -> Result<bool> {
+    if results.should_stop() || !steps::custom_steps_after(cfg, after) {
+        return Ok(true);
+    }
+    output.phase_header("Custom steps");
+    steps::run_step(output, results, "Custom steps", StepOptions::QUIET, || {
+        steps::custom(root, spec_path, cfg, after, results, output)
+    })
+}
+
+/// Active entries of the known-issues file, warning about expired ones.
+/// `strict` ignores the file.
+fn known_issues(
+    root: &Path,
+    cfg: &Config,
+    output: &Output,
+) -> Result<Vec<known_issues::KnownIssue>> {
+    if cfg.strict {
+        return Ok(Vec::new());
+    }
+    let (active, expired) = known_issues::load(root)?;
+    for issue in &expired {
+        output.print_warning(&format!(
+            "Known issue for {} {} expired on {}: {}",
+            issue.stage,
+            issue.target,
+            issue.expires.as_deref().unwrap_or_default(),
+            issue.reason
+        ));
+    }
+    Ok(active)
+}
 
-    output.print_summary(passed, failed);
+/// Apply `validate`'s command-line settings to `cfg`. Returns the keys they set.
+fn apply_overrides(cfg: &mut Config, overrides: Overrides) -> Vec<&'static str> {
+    let mut keys = Vec::new();
+    if let Some(s) = overrides.spec {
+        cfg.spec = Some(s);
+        keys.push("spec");
+    }
+    if let Some(m) = overrides.mode {
+        cfg.mode = m;
+        keys.push("mode");
+    }
+    if let Some(gens) = overrides.server_generators {
+        cfg.server_generators = gens;
+        keys.push("server_generators");
+    }
+    if let Some(gens) = overrides.client_generators {
+        cfg.client_generators = gens;
+        keys.push("client_generators");
+    }
+    if overrides.skip_lint {
+        cfg.lint = false;
+        keys.push("lint");
+    }
+    if overrides.skip_security {
+        cfg.security = false;
+        keys.push("security");
+    }
+    if overrides.skip_generate {
+        cfg.generate = false;
+        keys.push("generate");
+    }
+    if overrides.skip_compile {
+        cfg.compile = false;
+        keys.push("compile");
+    }
+    if overrides.skip_test {
+        cfg.test = false;
+        keys.push("test");
+    }
+    if overrides.skip_smoke {
+        cfg.smoke = false;
+        keys.push("smoke");
+    }
+    if overrides.skip_contract {
+        cfg.contract = false;
+        keys.push("contract");
+    }
+    keys
+}
 
-    println!();
+/// The lint step: the native checks, then Redocly, or the offline structure
+/// check without a container engine, and the spell check if enabled.
+fn lint(
+    root: &Path,
+    spec_path: &Path,
+    cfg: &Config,
+    offline: bool,
+    results: &ResultsStore,
+    output: &Output,
+) -> Result<bool> {
+    // Malformed YAML stops here, before the parsing checks.
+    if cfg.yaml_style && !steps::check_yaml_style(root, spec_path, cfg, results)? {
+        return Ok(false);
+    }
+    let native = steps::check(root, spec_path, cfg, results)?;
+    if results.should_stop() {
+        return Ok(false);
+    }
+    let structure = if offline {
+        steps::check_offline(root, spec_path, cfg, results)?
+    } else {
+        steps::lint(root, spec_path, cfg, results, output)?
+    };
+    // cspell needs a container engine, like Redocly.
+    let spelling = if cfg.spellcheck && !offline {
+        steps::spellcheck(root, spec_path, cfg, results, output)?
+    } else {
+        true
+    };
+    Ok(native && structure && spelling)
+}
+
+/// Run the lint step alone. With `update_baseline`, write every finding to
+/// the baseline instead of failing on it. With `fix`, apply the safe fixes
+/// first, writing the spec in place or to the given path, and lint that.
+#[allow(clippy::too_many_arguments)]
+fn cmd_lint(
+    root: &Path,
+    output: &Output,
+    spec_override: Option<String>,
+    update_baseline: bool,
+    fix: Option<Option<PathBuf>>,
+    strict: bool,
+    wait: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    let (mut cfg, _) = config::load_effective(root, None)?;
+    util::ensure_oav_dir(root)?;
+    let _lock = WorkspaceLock::acquire(root, wait, output)?;
+    util::add_gitignore_entries(root, &[".oav/"])?;
+    util::extract_assets(root, &ASSETS)?;
+    if let Some(s) = spec_override {
+        cfg.spec = Some(s);
+    }
+    cfg.lint = true;
+    cfg.generate = false;
+    cfg.compile = false;
+    cfg.test = false;
+    cfg.smoke = false;
+    cfg.contract = false;
+    cfg.custom_steps.clear();
+    cfg.strict |= strict;
+    cfg.apply_strict();
+
+    if let Some(out) = fix {
+        let source = spec_source(root, &mut cfg)?;
+        let fixed = fix_spec(root, &source, out.as_deref(), output)?;
+        cfg.spec = Some(util::to_posix_path(&fixed));
+    }
+    let spec_path = resolve_spec(root, &mut cfg)?;
+    let offline = match select_engine(&cfg, output) {
+        Ok(()) => false,
+        // A baseline written offline would miss Redocly's findings.
+        Err(err) if cfg.offline_fallback && !cfg.strict && !update_baseline => {
+            output.print_warning(&format!(
+                "{err:#} Falling back to offline validation: native checks only, no Redocly."
+            ));
+            true
+        }
+        Err(err) => return Err(err),
+    };
+    util::prepare_runtime_dirs(root)?;
+    let baseline = if update_baseline {
+        Vec::new()
+    } else {
+        baseline::load(root)?
+    };
+    let results = ResultsStore::create(root)?
+        .with_warn_stages(cfg.warn_stages())
+        .with_baseline(baseline)
+        .with_suppressions(suppressions(root, &cfg)?);
+    let remote_workspace = if offline {
+        None
+    } else {
+        remote::prepare(
+            root,
+            &remote_inputs(root, &spec_path, &cfg)?,
+            &results.run_id(),
+            output,
+        )?
+    };
+    if !offline && !steps::pull(root, &cfg, &results, output)? {
+        output.println("Some images failed to pull; tasks using them will fail");
+    }
+
+    let success = steps::run_step(
+        output,
+        &results,
+        "Lint",
+        StepOptions {
+            warn_only: cfg.warn_only("lint"),
+            ..StepOptions::SUMMARY
+        },
+        || lint(root, &spec_path, &cfg, offline, &results, output),
+    )?;
+    drop(remote_workspace);
+
+    let _ = steps::run_step(output, &results, "Report", StepOptions::SUMMARY, || {
+        steps::report(root, &cfg, output)
+    });
+    let run = results.snapshot();
+    manifest::write(root, &cfg, &spec_path, &run)?;
+    history::archive(root, &run, cfg.history_runs)?;
+    output.print_summary(run.passed(), run.failed());
+    events::emit(&events::Event::RunFinished {
+        run_id: &run.run_id,
+        passed: run.passed(),
+        failed: run.failed(),
+    });
+    if format == OutputFormat::Compact {
+        print_compact(root, &cfg, &run.findings);
+    }
+
+    if update_baseline {
+        let accepted = baseline::write(root, results.lint_findings())?;
+        output.println_always(&format!(
+            "Wrote {accepted} finding(s) to {}",
+            baseline::FILE_NAME
+        ));
+        return Ok(());
+    }
+    if !success && !cfg.warn_only("lint") {
+        output.print_error("Lint failed. See dashboard for details.");
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Print `findings` as `file:line:column: severity[rule] message`, the
+/// format of compilers that editors and `vim -q` read. JSON pointers are
+/// located in the spec as written, at the closest node that exists there.
+fn print_compact(root: &Path, cfg: &Config, findings: &[MergedFinding]) {
+    let spec = cfg.spec.as_deref().unwrap_or_default();
+    let starts = fs::read_to_string(root.join(spec))
+        .map(|content| rules::node_starts(&content))
+        .unwrap_or_default();
+    for finding in findings {
+        let location = if finding.path.starts_with('#') {
+            let (line, column) = rules::locate(&starts, &finding.path).unwrap_or((1, 1));
+            format!("{spec}:{line}:{column}")
+        } else if is_source_location(&finding.path) {
+            finding.path.clone()
+        } else {
+            format!("{spec}:1:1")
+        };
+        println!(
+            "{location}: {}[{}] {}",
+            finding.severity,
+            finding.rule,
+            finding.message.replace('\n', " ")
+        );
+    }
+}
+
+/// Whether `path` is already `file:line:column`, as the YAML style checks
+/// report.
+fn is_source_location(path: &str) -> bool {
+    let mut parts = path.rsplitn(3, ':');
+    let numeric = |part: Option<&str>| {
+        part.is_some_and(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+    };
+    numeric(parts.next()) && numeric(parts.next()) && parts.next().is_some()
+}
+
+/// Apply the safe fixes to `spec_path`, writing the result over it or to
+/// `out`. Returns the fixed spec.
+fn fix_spec(root: &Path, spec_path: &Path, out: Option<&Path>, output: &Output) -> Result<PathBuf> {
+    let path = root.join(spec_path);
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read spec {}", path.display()))?;
+    let as_json = path.extension().is_some_and(|ext| ext == "json");
+    if out.is_none() && !as_json && fmt::has_comments(&content) {
+        bail!(
+            "The spec has comments, which rewriting it would drop; use --out to write the fixed spec elsewhere"
+        );
+    }
+    let mut doc: serde_yaml::Value = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse spec {}", spec_path.display()))?;
+    let fixes = rules::fix_document(&mut doc);
+    for fix in &fixes {
+        output.println(&format!("Fixed {fix}"));
+    }
+    if fixes.is_empty() {
+        output.println("Nothing to fix");
+    }
+
+    let target = out.unwrap_or(spec_path);
+    if !fixes.is_empty() || out.is_some() {
+        let fixed = if as_json {
+            serde_json::to_string_pretty(&doc)? + "\n"
+        } else {
+            serde_yaml::to_string(&doc).context("Failed to serialize spec")?
+        };
+        fs::write(root.join(target), fixed)
+            .with_context(|| format!("Failed to write {}", target.display()))?;
+        output.println_always(&format!(
+            "Wrote {} fix(es) to {}",
+            fixes.len(),
+            target.display()
+        ));
+    }
+    Ok(target.to_path_buf())
+}
+
+/// Run the generate step alone. Unlike `validate`, overrides given here are
+/// not written back to `.oavc`.
+#[allow(clippy::too_many_arguments)]
+fn cmd_generate(
+    root: &Path,
+    output: &Output,
+    spec_override: Option<String>,
+    only: Vec<String>,
+    out: Option<PathBuf>,
+    strict: bool,
+    no_cache: bool,
+    wait: bool,
+) -> Result<()> {
+    let (mut cfg, _) = config::load_effective(root, None)?;
+    util::ensure_oav_dir(root)?;
+    let _lock = WorkspaceLock::acquire(root, wait, output)?;
+    util::add_gitignore_entries(root, &[".oav/"])?;
+    util::extract_assets(root, &ASSETS)?;
+    if let Some(s) = spec_override {
+        cfg.spec = Some(s);
+    }
+    cfg.expand_generator_groups()?;
+    if !only.is_empty() {
+        select_generators(root, &mut cfg, &only)?;
+    }
+    cfg.lint = false;
+    cfg.generate = true;
+    cfg.compile = false;
+    cfg.test = false;
+    cfg.smoke = false;
+    cfg.contract = false;
+    cfg.strict |= strict;
+    cfg.apply_strict();
+
+    let spec_path = resolve_spec(root, &mut cfg)?;
+    let conversion = downconvert::prepare(root, &spec_path, &cfg, &["generate"])?;
+    select_engine(&cfg, output)?;
+    util::prepare_runtime_dirs(root)?;
+    let results = ResultsStore::create(root)?
+        .with_allowed_failures(cfg.allowed_failures.clone())
+        .with_warn_stages(cfg.warn_stages())
+        .with_known_issues(known_issues(root, &cfg, output)?);
+    if let Some(conversion) = &conversion {
+        announce_conversion(conversion, output);
+        conversion.record(&results)?;
+    }
+    let remote_workspace = remote::prepare(
+        root,
+        &remote_inputs(root, &spec_path, &cfg)?,
+        &results.run_id(),
+        output,
+    )?;
+
+    if !steps::pull(root, &cfg, &results, output)? {
+        output.println("Some images failed to pull; tasks using them will fail");
+    }
+    output.phase_header("Generate");
+    let success = steps::run_step(output, &results, "Generate", StepOptions::QUIET, || {
+        steps::generate(
+            root,
+            &spec_path,
+            conversion.as_ref(),
+            &cfg,
+            !no_cache,
+            &results,
+            output,
+        )
+    })?;
+    drop(remote_workspace);
+
+    let _ = steps::run_step(output, &results, "Report", StepOptions::SUMMARY, || {
+        steps::report(root, &cfg, output)
+    });
+    let run = results.snapshot();
+    manifest::write(root, &cfg, &spec_path, &run)?;
+    history::archive(root, &run, cfg.history_runs)?;
+    output.print_summary(run.passed(), run.failed());
+    events::emit(&events::Event::RunFinished {
+        run_id: &run.run_id,
+        passed: run.passed(),
+        failed: run.failed(),
+    });
+
+    let location = match out {
+        Some(out) => {
+            let out = root.join(out);
+            let exported = steps::export_generated(root, &cfg, &results, &out)?;
+            format!(
+                "Copied {} generated project(s) to {}",
+                exported.len(),
+                out.display()
+            )
+        }
+        None => format!(
+            "Generated code: {}",
+            root.join(OAV_DIR).join("generated").display()
+        ),
+    };
+    if !events::to_stdout() {
+        println!();
+        output.println_always(&location);
+    }
+
+    if !success && results.stage_failed("generate") {
+        output.print_error("Generation failed. See dashboard for details.");
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn cmd_report(root: &Path, output: &Output, run: Option<String>, pdf: bool) -> Result<()> {
+    let (cfg, _) = config::load_effective(root, None)?;
+    if !root.join(OAV_DIR).is_dir() {
+        bail!("No results found. Run `oav validate` first.");
+    }
+    let _lock = WorkspaceLock::acquire(root, false, output)?;
+    if !steps::rebuild_report(root, &cfg, run.as_deref(), pdf, output)? {
+        bail!("Failed to write reports");
+    }
+    let reports_dir = root.join(OAV_DIR).join("reports");
     output.println_always(&format!(
         "Dashboard: {}",
-        root.join(OAV_DIR)
-            .join("reports")
-            .join("dashboard.html")
-            .display()
+        reports_dir.join("dashboard.html").display()
     ));
+    if pdf {
+        output.println_always(&format!(
+            "PDF: {}",
+            reports_dir.join("report.pdf").display()
+        ));
+    }
+    Ok(())
+}
 
-    if failures > 0 {
-        output.print_error("Validation failed. See dashboard for details.");
+fn cmd_report_diff(
+    root: &Path,
+    output: &Output,
+    before: &str,
+    after: &str,
+    out: Option<PathBuf>,
+) -> Result<()> {
+    let before = stored_run(root, before)?;
+    let after = stored_run(root, after)?;
+    let out = out.unwrap_or_else(|| root.join(OAV_DIR).join("reports").join("diff.md"));
+    steps::diff_runs(&before, &after, &out, output)
+}
+
+/// The results of `run`: a run ID kept in the history or results, `latest`,
+/// or a results JSON file such as one archived by CI.
+fn stored_run(root: &Path, run: &str) -> Result<results::RunResults> {
+    if run == "latest" {
+        return results::load_latest(root)?.context("No results found. Run `oav validate` first.");
+    }
+    let path = Path::new(run);
+    if path.is_file() {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        return serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()));
+    }
+    match history::load(root, run)? {
+        Some(results) => Ok(results),
+        None => results::load(root, run)?.with_context(|| {
+            format!("No results found for run {run}; `oav runs list` shows the kept runs")
+        }),
+    }
+}
+
+/// Upload `run`, or the latest run, to `endpoint` or `publish_endpoint`.
+fn cmd_publish(
+    root: &Path,
+    output: &Output,
+    endpoint: Option<String>,
+    run: Option<String>,
+    reports: bool,
+) -> Result<()> {
+    let (cfg, _) = config::load_effective(root, None)?;
+    let endpoint = endpoint
+        .or_else(|| cfg.publish_endpoint.clone())
+        .context("No endpoint to publish to. Pass --endpoint or set publish_endpoint.")?;
+    let run = stored_run(root, run.as_deref().unwrap_or("latest"))?;
+    publish::publish(
+        root,
+        &endpoint,
+        &run,
+        reports || cfg.publish_reports,
+        output,
+    )
+}
+
+fn cmd_runs(root: &Path, output: &Output, command: RunsCommand) -> Result<()> {
+    match command {
+        RunsCommand::List => {
+            let runs = history::list(root)?;
+            if runs.is_empty() {
+                output.println_always(&format!(
+                    "No runs in {} yet. Run `oav validate` first.",
+                    history::dir(root).display()
+                ));
+                return Ok(());
+            }
+            println!(
+                "{:<24} {:<20} {:>6} {:>6}  RESULT",
+                "RUN ID", "STARTED", "PASSED", "FAILED"
+            );
+            for run in runs {
+                println!(
+                    "{:<24} {:<20} {:>6} {:>6}  {}",
+                    run.run_id,
+                    run.started_at,
+                    run.passed(),
+                    run.failed(),
+                    if run.failed() > 0 { "failed" } else { "passed" }
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cmd_mock(
+    root: &Path,
+    output: &Output,
+    spec_override: Option<String>,
+    port: Option<u16>,
+    dynamic: bool,
+) -> Result<()> {
+    let (mut cfg, _) = config::load_effective(root, None)?;
+    util::ensure_oav_dir(root)?;
+    util::add_gitignore_entries(root, &[".oav/"])?;
+    if let Some(s) = spec_override {
+        cfg.spec = Some(s);
+    }
+    let spec_path = resolve_spec(root, &mut cfg)?;
+    let conversion = downconvert::prepare(root, &spec_path, &cfg, &["mock"])?;
+    if let Some(conversion) = &conversion {
+        announce_conversion(conversion, output);
+    }
+    let spec_path =
+        downconvert::spec_for(conversion.as_ref(), &spec_path, &cfg, "mock", None).to_path_buf();
+    select_engine(&cfg, output)?;
+    let _remote_workspace = remote::prepare(
+        root,
+        &remote_inputs(root, &spec_path, &cfg)?,
+        &format!("mock-{}", std::process::id()),
+        output,
+    )?;
+
+    let port = port.unwrap_or(cfg.mock_port);
+    if !mock::serve(root, &spec_path, &cfg, port, dynamic, output)? {
+        bail!(
+            "Mock server exited with an error. See {}",
+            root.join(OAV_DIR)
+                .join("reports")
+                .join("mock")
+                .join("prism.log")
+                .display()
+        );
+    }
+    Ok(())
+}
+
+/// Serve the language server. Without a container engine, `lint` is turned
+/// off and the editor told why.
+fn cmd_lsp(root: &Path, output: &Output, lint: bool) -> Result<()> {
+    let (cfg, _) = config::load_effective(root, None)?;
+    let (lint, notice) = match lint.then(|| select_engine(&cfg, output)) {
+        Some(Ok(())) => (true, None),
+        Some(Err(err)) => (
+            false,
+            Some(format!("{err:#} Only the native checks will run.")),
+        ),
+        None => (false, None),
+    };
+    lsp::serve(root, cfg, lint, notice)
+}
+
+/// Serve the reports for a browser, or with `daemon` the HTTP API for other
+/// tooling to start validations and fetch their results.
+fn cmd_serve(
+    root: &Path,
+    output: &Output,
+    daemon: bool,
+    port: Option<u16>,
+    host: IpAddr,
+) -> Result<()> {
+    let (cfg, _) = config::load_effective(root, None)?;
+    util::ensure_oav_dir(root)?;
+    util::add_gitignore_entries(root, &[".oav/"])?;
+    let port = port.unwrap_or(cfg.serve_port);
+    if daemon {
+        daemon::serve(root, cfg, port, output)
+    } else {
+        serve::serve(root, host, port, output)
+    }
+}
+
+/// Format the spec in place, or with `check` only report whether it is
+/// formatted.
+fn cmd_fmt(
+    root: &Path,
+    output: &Output,
+    spec_override: Option<String>,
+    check: bool,
+    options: fmt::Options,
+) -> Result<()> {
+    let (mut cfg, _) = config::load_effective(root, None)?;
+    if let Some(s) = spec_override {
+        cfg.spec = Some(s);
+    }
+    let spec_path = spec_source(root, &mut cfg)?;
+    let path = root.join(&spec_path);
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read spec {}", path.display()))?;
+    let as_json = path.extension().is_some_and(|ext| ext == "json");
+    let formatted = fmt::format(&content, as_json, options)
+        .with_context(|| format!("Failed to format {}", spec_path.display()))?;
+
+    if formatted == content {
+        output.println(&format!("{} is formatted", spec_path.display()));
+        return Ok(());
+    }
+    if check {
+        let line = content
+            .lines()
+            .zip(formatted.lines())
+            .position(|(old, new)| old != new)
+            .unwrap_or_else(|| content.lines().count().min(formatted.lines().count()))
+            + 1;
+        output.print_error(&format!(
+            "{} is not formatted (first difference at line {line}); run `oav fmt`",
+            spec_path.display()
+        ));
         std::process::exit(1);
     }
+    util::write_atomic(&path, &formatted)?;
+    output.println(&format!("Formatted {}", spec_path.display()));
+    Ok(())
+}
+
+/// Narrow the configured generators to `only`, switching `mode` to the
+/// scopes they belong to. A `@group` selects its members in both scopes.
+fn select_generators(root: &Path, cfg: &mut Config, only: &[String]) -> Result<()> {
+    let mut names = Vec::new();
+    for name in only
+        .iter()
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+    {
+        if name.starts_with('@') {
+            let group = [name.to_string()];
+            match (
+                cfg.expand_groups("server", &group),
+                cfg.expand_groups("client", &group),
+            ) {
+                (Ok(server), Ok(client)) => names.extend(server.into_iter().chain(client)),
+                (Ok(members), Err(_)) | (Err(_), Ok(members)) => names.extend(members),
+                (Err(err), Err(_)) => return Err(err),
+            }
+        } else {
+            names.push(name.to_string());
+        }
+    }
+    let mut seen = HashSet::new();
+    names.retain(|name| seen.insert(name.clone()));
+    let mut server = Vec::new();
+    let mut client = Vec::new();
+    for name in &names {
+        let name = name.as_str();
+        match steps::scope_of(root, cfg, name) {
+            Some("server") => server.push(name.to_string()),
+            Some(_) => client.push(name.to_string()),
+            None => bail!("Unknown generator: {name}"),
+        }
+    }
+    cfg.mode = match (server.is_empty(), client.is_empty()) {
+        (false, false) => cli::Mode::Both,
+        (false, true) => cli::Mode::Server,
+        (true, false) => cli::Mode::Client,
+        (true, true) => bail!("No generators given to --only"),
+    };
+    cfg.server_generators = server;
+    cfg.client_generators = client;
+    Ok(())
+}
+
+/// Findings to leave out: `.oavignore` and the spec's `# oav-ignore` comments.
+fn suppressions(root: &Path, cfg: &Config) -> Result<Vec<suppressions::Suppression>> {
+    let mut suppressions = suppressions::load(root)?;
+    // The spec as written: templating drops comments.
+    if let Some(spec) = &cfg.spec
+        && let Ok(content) = fs::read_to_string(root.join(spec))
+    {
+        suppressions.extend(suppressions::inline(&content));
+    }
+    Ok(suppressions)
+}
+
+/// The spec to validate: the configured one or the one discovered in `root`,
+/// normalized and stored back into `cfg.spec`. Under `spec_template` the
+/// returned path is the substituted copy in `.oav`.
+fn resolve_spec(root: &Path, cfg: &mut Config) -> Result<PathBuf> {
+    let spec_path = spec_source(root, cfg)?;
+    template::apply(root, cfg, spec_path)
+}
 
+/// The configured or discovered spec as written, normalized and stored back
+/// into `cfg.spec`.
+fn spec_source(root: &Path, cfg: &mut Config) -> Result<PathBuf> {
+    let spec = if let Some(s) = cfg.spec.clone() {
+        s
+    } else if let Some(s) = util::discover_spec(root)? {
+        s
+    } else {
+        bail!("No OpenAPI spec found. Pass --spec or set spec in .oavc.");
+    };
+
+    let spec_path = util::normalize_spec_path(root, &spec)?;
+    cfg.spec = Some(spec_path.to_string_lossy().to_string());
+    Ok(spec_path)
+}
+
+fn select_engine(cfg: &Config, output: &Output) -> Result<()> {
+    docker::select(cfg.container_engine, cfg.docker_context.clone());
+    docker::engine().ensure_available()?;
+    if let Some(platform) = &cfg.platform
+        && *platform != docker::host_platform()
+    {
+        output.println(&format!(
+            "Running {platform} containers on a {} host; expect emulation to be slower",
+            docker::host_platform()
+        ));
+    }
     Ok(())
 }
 
+/// Tell the user which steps read the 3.0 conversion of a 3.1 spec, and
+/// where its notes on lossy constructs are logged.
+fn announce_conversion(conversion: &downconvert::Conversion, output: &Output) {
+    output.println(&format!(
+        "OpenAPI 3.1 spec: {} read{} the 3.0 conversion in {}",
+        conversion.steps.join(", "),
+        if conversion.steps.len() == 1 { "s" } else { "" },
+        conversion.path.display()
+    ));
+    if !conversion.notes.is_empty() {
+        output.println(&format!(
+            "The conversion dropped or approximated {} construct(s); see {}",
+            conversion.notes.len(),
+            conversion.log_path().display()
+        ));
+    }
+}
+
+/// Files containers read from the repository: the spec, files it references,
+/// generator configs outside `.oav`, custom templates, and the lint config,
+/// relative to `root`.
+fn remote_inputs(root: &Path, spec_path: &Path, cfg: &Config) -> Result<Vec<PathBuf>> {
+    let canonical_root = root.canonicalize().context("Failed to resolve root path")?;
+    let mut inputs: Vec<PathBuf> = steps::spec_files(root, spec_path)
+        .into_iter()
+        .filter_map(|path| {
+            path.strip_prefix(&canonical_root)
+                .ok()
+                .map(Path::to_path_buf)
+        })
+        .collect();
+    inputs.extend(
+        cfg.generator_config_paths()
+            .values()
+            .chain(&cfg.lint_config)
+            .map(PathBuf::from)
+            .filter(|path| root.join(path).is_file()),
+    );
+    for path in steps::lint_project_files(root) {
+        if !inputs.contains(&path) {
+            inputs.push(path);
+        }
+    }
+    inputs.extend(
+        cfg.templates
+            .values()
+            .map(PathBuf::from)
+            .filter(|path| root.join(path).is_dir()),
+    );
+    for dir in ["server", "client"]
+        .into_iter()
+        .filter_map(|scope| cfg.generator_config_dir(scope))
+    {
+        let Ok(entries) = fs::read_dir(root.join(&dir)) else {
+            continue;
+        };
+        inputs.extend(
+            entries
+                .flatten()
+                .map(|entry| Path::new(&dir).join(entry.file_name()))
+                .filter(|path| root.join(path).is_file()),
+        );
+    }
+    Ok(inputs)
+}
+
 fn cmd_config(root: &Path, output: &Output, command: Option<ConfigCommand>) -> Result<()> {
     match command.unwrap_or(ConfigCommand::Print) {
         ConfigCommand::Get { key } => {
@@ -238,10 +1408,34 @@ fn cmd_config(root: &Path, output: &Output, command: Option<ConfigCommand>) -> R
             let mut cfg = config::load(root)?;
             config::set_value(&mut cfg, &key, value)?;
             config::write(root, &cfg)?;
-            output.println(&format!("Updated {}", root.join(CONFIG_FILE).display()));
+            output.println(&format!("Updated {}", config::path(root).display()));
+        }
+        ConfigCommand::Add { key, values } => {
+            let mut cfg = config::load(root)?;
+            if config::selects_all(&cfg, &key) {
+                config::add_values(&mut cfg, &key, &values)?;
+                output.println(&format!(
+                    "{key} already selects every supported generator; left unchanged. To select only these, run `oav config set {key} {}`",
+                    values.join(",")
+                ));
+                return Ok(());
+            }
+            for value in config::add_values(&mut cfg, &key, &values)? {
+                output.println(&format!("{value} is already in {key}"));
+            }
+            config::write(root, &cfg)?;
+            output.println(&format!("Updated {}", config::path(root).display()));
+        }
+        ConfigCommand::Remove { key, values } => {
+            let mut cfg = config::load(root)?;
+            for value in config::remove_values(&mut cfg, &key, &values)? {
+                output.println(&format!("{value} is not in {key}"));
+            }
+            config::write(root, &cfg)?;
+            output.println(&format!("Updated {}", config::path(root).display()));
         }
         ConfigCommand::Edit => {
-            let path = root.join(CONFIG_FILE);
+            let path = config::path(root);
             if !path.exists() {
                 config::write(root, &Config::default())?;
             }
@@ -256,36 +1450,196 @@ fn cmd_config(root: &Path, output: &Output, command: Option<ConfigCommand>) -> R
         }
         ConfigCommand::Print => {
             let cfg = config::load(root)?;
-            let yaml = serde_yaml::to_string(&cfg).context("Failed to serialize config")?;
-            print!("{yaml}");
+            print!("{}", config::to_string(root, &cfg)?);
+        }
+        ConfigCommand::Check => {
+            let problems = config::check(root)?;
+            if !problems.is_empty() {
+                for problem in &problems {
+                    output.print_error(problem);
+                }
+                bail!(
+                    "{} has {} problem(s)",
+                    config::file_name(root),
+                    problems.len()
+                );
+            }
+            output.println(&format!("{} is valid", config::file_name(root)));
+        }
+        ConfigCommand::Migrate { dry_run } => {
+            let name = config::file_name(root);
+            let Some((old, new)) = config::migrate(root)? else {
+                output.println(&format!(
+                    "{name} is already at version {}",
+                    config::CONFIG_VERSION
+                ));
+                return Ok(());
+            };
+            print!("{}", util::line_diff(&old, &new));
+            if dry_run {
+                output.println(&format!("Would rewrite {name} (--dry-run)"));
+            } else {
+                util::write_atomic(&config::path(root), &new)?;
+                output.println(&format!(
+                    "Migrated {name} to version {}",
+                    config::CONFIG_VERSION
+                ));
+            }
+        }
+        ConfigCommand::Resolve { overrides } => {
+            let (mut cfg, overlays) = config::load_effective(root, overrides.profile.as_deref())?;
+            let flags = apply_overrides(&mut cfg, overrides);
+            print!("{}", config::resolve(root, &cfg, &overlays, &flags)?);
         }
         ConfigCommand::Ignore => {
             util::ensure_gitignore(root, true)?;
             let mut cfg = config::load(root)?;
             cfg.manage_gitignore = true;
             config::write(root, &cfg)?;
-            output.println("Added .oavc to .gitignore and enabled automatic gitignore management.");
+            output.println(&format!(
+                "Added {} to .gitignore and enabled automatic gitignore management.",
+                config::file_name(root)
+            ));
         }
         ConfigCommand::Unignore => {
-            util::remove_gitignore_entries(root, &[".oavc"])?;
+            util::remove_gitignore_entries(root, &[&config::file_name(root)])?;
             let mut cfg = config::load(root)?;
             cfg.manage_gitignore = false;
             config::write(root, &cfg)?;
-            output.println(
-                "Removed .oavc from .gitignore and disabled automatic gitignore management.",
-            );
+            output.println(&format!(
+                "Removed {} from .gitignore and disabled automatic gitignore management.",
+                config::file_name(root)
+            ));
         }
     }
     Ok(())
 }
 
-fn cmd_clean(root: &Path, output: &Output) -> Result<()> {
-    let path = root.join(OAV_DIR);
-    if path.exists() {
-        fs::remove_dir_all(&path).context("Failed to remove .oav directory")?;
-        output.println(&format!("Removed {}", path.display()));
-    } else {
+#[allow(clippy::too_many_arguments)]
+fn cmd_clean(
+    root: &Path,
+    output: &Output,
+    reports: bool,
+    generated: bool,
+    cache: bool,
+    compile_cache: bool,
+    all: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let oav_dir = root.join(OAV_DIR);
+    if !oav_dir.exists() {
         output.println("No .oav directory found.");
+        return Ok(());
     }
+
+    let mut paths = Vec::new();
+    if all || !(reports || generated || cache || compile_cache) {
+        paths.push(oav_dir.clone());
+    } else {
+        if reports {
+            paths.extend(
+                [
+                    "reports",
+                    "results",
+                    "history",
+                    "status.tsv",
+                    "run.json",
+                    "notifications",
+                    "publish",
+                ]
+                .map(|name| oav_dir.join(name)),
+            );
+        }
+        if generated {
+            paths.extend(["generated", "batch"].map(|name| oav_dir.join(name)));
+        }
+        if cache {
+            paths.push(oav_dir.join("cache"));
+        }
+    }
+    if (cache || compile_cache)
+        && let Some(dir) = config::load_effective(root, None)?.0.compile_cache_dir
+    {
+        let dir = root.join(dir);
+        // `..` could lead anywhere, so such paths count as outside.
+        let inside = dir.starts_with(&oav_dir)
+            && !dir
+                .components()
+                .any(|component| component == std::path::Component::ParentDir);
+        if compile_cache || inside {
+            if !paths.iter().any(|path| dir.starts_with(path)) {
+                paths.push(dir);
+            }
+        } else if dir.exists() {
+            output.println(&format!(
+                "Keeping compile_cache_dir {}, which is outside .oav; pass --compile-cache to remove it.",
+                dir.display()
+            ));
+        }
+    }
+    paths.retain(|path| path.exists());
+    if paths.is_empty() {
+        output.println("Nothing to remove.");
+        return Ok(());
+    }
+
+    let _lock = (!dry_run)
+        .then(|| WorkspaceLock::acquire(root, false, output))
+        .transpose()?;
+    let mut total = 0;
+    for path in &paths {
+        let size = util::disk_usage(path);
+        total += size;
+        if dry_run {
+            output.println(&format!(
+                "Would remove {} ({})",
+                path.display(),
+                util::format_bytes(size)
+            ));
+            continue;
+        }
+        if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        }
+        .with_context(|| format!("Failed to remove {}", path.display()))?;
+        output.println(&format!("Removed {}", path.display()));
+    }
+    output.println(&format!(
+        "{} {}",
+        if dry_run {
+            "Would reclaim"
+        } else {
+            "Reclaimed"
+        },
+        util::format_bytes(total)
+    ));
     Ok(())
 }
+
+fn cmd_hook(root: &Path, output: &Output, command: HookCommand) -> Result<()> {
+    match command {
+        HookCommand::Install {
+            kind,
+            full,
+            force,
+            uninstall,
+        } => {
+            if uninstall {
+                hook::uninstall(root, output, kind)
+            } else {
+                hook::install(root, output, kind, full, force)
+            }
+        }
+    }
+}
+
+fn cmd_ci(root: &Path, output: &Output, command: CiCommand) -> Result<()> {
+    match command {
+        CiCommand::Init { provider, force } => {
+            let cfg = config::load(root)?;
+            ci::init(root, output, &cfg, provider, force)
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::Config;
+use crate::docker;
+use crate::output::Output;
+use crate::results::{ResultsStore, RunResults};
+use crate::util::{OAV_DIR, write_log_header};
+
+/// Points in `oav validate` where a `hooks` command can run.
+pub const EVENTS: [&str; 4] = [
+    "pre_validate",
+    "post_lint",
+    "post_generate",
+    "post_validate",
+];
+
+/// What a hook receives on stdin.
+#[derive(Serialize)]
+struct Payload<'a> {
+    event: &'a str,
+    root: String,
+    spec: Option<&'a str>,
+    /// Results recorded so far in this run.
+    run: RunResults,
+}
+
+/// Run the `hooks.<event>` command, if any, in the workspace root with the
+/// run's state as JSON on stdin (also saved next to its log). Output goes
+/// to `.oav/reports/hooks/<event>.log`, and `post_lint` and `post_generate`
+/// are recorded in their stage. Returns whether it succeeded; no hook
+/// counts as success.
+pub fn run(
+    root: &Path,
+    config: &Config,
+    event: &str,
+    results: &ResultsStore,
+    output: &Output,
+) -> Result<bool> {
+    let Some(command) = config.hooks.get(event) else {
+        return Ok(true);
+    };
+    let hooks_dir = root.join(OAV_DIR).join("reports").join("hooks");
+    fs::create_dir_all(&hooks_dir).context("Failed to create hooks reports directory")?;
+    let log_path = hooks_dir.join(format!("{event}.log"));
+    let payload_path = hooks_dir.join(format!("{event}.json"));
+
+    let payload = Payload {
+        event,
+        root: root.display().to_string(),
+        spec: config.spec.as_deref(),
+        run: results.snapshot(),
+    };
+    fs::write(&payload_path, serde_json::to_string_pretty(&payload)?)
+        .context("Failed to write hook payload")?;
+    if let Some(stage) = stage(event) {
+        results.start(stage, "hook", event);
+    }
+    write_log_header(&log_path, &format!("$ {command}"))?;
+
+    let mut shell = shell(command);
+    shell
+        .current_dir(root)
+        .env("OAV_HOOK", event)
+        .env("OAV_RUN_ID", &payload.run.run_id)
+        .stdin(File::open(&payload_path).context("Failed to open hook payload")?);
+    let (status, _) = docker::run_with_logging(&mut shell, &log_path, output, None, None)
+        .with_context(|| format!("Failed to run the {event} hook"))?;
+    if let Some(stage) = stage(event) {
+        results.record(stage, "hook", event, status.as_str(), &log_path)?;
+    }
+    if !status.success() {
+        output.print_error(&format!(
+            "The {event} hook failed. See {}",
+            log_path.display()
+        ));
+    }
+    Ok(status.success())
+}
+
+/// Stage whose results include the hook's.
+fn stage(event: &str) -> Option<&'static str> {
+    match event {
+        "post_lint" => Some("lint"),
+        "post_generate" => Some("generate"),
+        _ => None,
+    }
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(not(unix))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
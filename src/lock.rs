@@ -0,0 +1,71 @@
+use anyhow::{Context, Result, bail};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::output::Output;
+use crate::util::OAV_DIR;
+
+const LOCK_FILE: &str = "lock";
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Advisory lock on `.oav/lock`, held for the lifetime of the value. The OS
+/// releases it when the process exits, so a crashed run never leaves a stale lock.
+pub struct WorkspaceLock {
+    _file: File,
+}
+
+impl WorkspaceLock {
+    /// Lock the workspace, failing if another run holds it unless `wait` is set.
+    pub fn acquire(root: &Path, wait: bool, output: &Output) -> Result<Self> {
+        let path = root.join(OAV_DIR).join(LOCK_FILE);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+
+        let mut announced = false;
+        while !try_lock(&file)? {
+            let holder = holder(&mut file);
+            if !wait {
+                bail!(
+                    "Another oav run{holder} is using {}. Wait for it to finish or pass --wait.",
+                    root.join(OAV_DIR).display()
+                );
+            }
+            if !announced {
+                output.println(&format!("Waiting for another oav run{holder} to finish..."));
+                announced = true;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{}", std::process::id())?;
+        Ok(Self { _file: file })
+    }
+}
+
+fn holder(file: &mut File) -> String {
+    let mut content = String::new();
+    let _ = file.seek(SeekFrom::Start(0));
+    let _ = file.read_to_string(&mut content);
+    match content.trim() {
+        "" => String::new(),
+        pid => format!(" (pid {pid})"),
+    }
+}
+
+fn try_lock(file: &File) -> Result<bool> {
+    match file.try_lock() {
+        Ok(()) => Ok(true),
+        Err(TryLockError::WouldBlock) => Ok(false),
+        Err(TryLockError::Error(err)) => Err(err).context("Failed to lock workspace"),
+    }
+}
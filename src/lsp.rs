@@ -0,0 +1,499 @@
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::output::Output;
+use crate::rules::{self, Finding, Severity};
+use crate::steps;
+use crate::suppressions::{self, Suppression};
+use crate::util::OAV_DIR;
+
+/// Redocly runs once saves have paused this long.
+const LINT_DELAY: Duration = Duration::from_millis(1500);
+/// JSON-RPC error code for requests the server doesn't handle.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// An open spec and its latest findings.
+struct Document {
+    text: String,
+    native: Vec<Finding>,
+    redocly: Vec<Finding>,
+}
+
+struct Server {
+    root: PathBuf,
+    config: Config,
+    /// `.oavignore`; `# oav-ignore` comments are read from each document.
+    suppressions: Vec<Suppression>,
+    documents: Mutex<HashMap<String, Document>>,
+    stdout: Mutex<io::Stdout>,
+}
+
+#[derive(Serialize)]
+struct Diagnostic {
+    range: Range,
+    severity: u8,
+    code: String,
+    source: &'static str,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct Range {
+    start: Position,
+    end: Position,
+}
+
+#[derive(Serialize)]
+struct Position {
+    line: usize,
+    character: usize,
+}
+
+/// Serve the Language Server Protocol on stdin and stdout until the client
+/// exits. Open OpenAPI documents get the native checks' findings on every
+/// change; with `lint`, Redocly's too, once saves pause. `notice` is shown
+/// to the user after initialization.
+pub fn serve(root: &Path, config: Config, lint: bool, notice: Option<String>) -> Result<()> {
+    let server = Arc::new(Server {
+        root: root.to_path_buf(),
+        suppressions: suppressions::load(root)?,
+        config,
+        documents: Mutex::new(HashMap::new()),
+        stdout: Mutex::new(io::stdout()),
+    });
+    let saves = lint.then(|| {
+        let (sender, receiver) = mpsc::channel();
+        let server = Arc::clone(&server);
+        thread::spawn(move || lint_saves(&server, &receiver));
+        sender
+    });
+
+    let mut stdin = io::stdin().lock();
+    let mut shutdown = false;
+    while let Some(message) = read_message(&mut stdin)? {
+        let method = message.get("method").and_then(Value::as_str);
+        let id = message.get("id").cloned();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let text_document = params.get("textDocument");
+        let uri = text_document
+            .and_then(|document| document.get("uri"))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        match (method, id) {
+            (Some("initialize"), Some(id)) => server.respond(id, &capabilities())?,
+            (Some("initialized"), _) => {
+                if let Some(notice) = &notice {
+                    server.notify("window/showMessage", &show_message(2, notice))?;
+                }
+            }
+            (Some("shutdown"), Some(id)) => {
+                shutdown = true;
+                server.respond(id, &Value::Null)?;
+            }
+            (Some("exit"), _) => break,
+            (Some("textDocument/didOpen"), _) => {
+                let text = text_document
+                    .and_then(|document| document.get("text"))
+                    .and_then(Value::as_str);
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    server.update(&uri, text.to_string())?;
+                }
+            }
+            (Some("textDocument/didChange"), _) => {
+                // Full sync: the last change is the whole document.
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Value::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str);
+                if let (Some(uri), Some(text)) = (uri, text) {
+                    server.update(&uri, text.to_string())?;
+                }
+            }
+            (Some("textDocument/didSave"), _) => {
+                if let (Some(saves), Some(uri)) = (&saves, uri) {
+                    let _ = saves.send(uri);
+                }
+            }
+            (Some("textDocument/didClose"), _) => {
+                if let Some(uri) = uri {
+                    server.lock_documents().remove(&uri);
+                    server.publish(&uri, Vec::new())?;
+                }
+            }
+            (Some(method), Some(id)) => server.respond_error(
+                id,
+                METHOD_NOT_FOUND,
+                &format!("Unsupported method {method}"),
+            )?,
+            _ => {}
+        }
+    }
+    if !shutdown {
+        bail!("The client exited without shutting down the server");
+    }
+    Ok(())
+}
+
+impl Server {
+    /// Re-check the document at `uri` with the native checks and publish
+    /// its diagnostics, keeping Redocly's last findings.
+    fn update(&self, uri: &str, text: String) -> Result<()> {
+        if !is_openapi(&text) {
+            return Ok(());
+        }
+        let native = self.check(uri, &text);
+        let diagnostics = {
+            let mut documents = self.lock_documents();
+            let redocly = documents
+                .remove(uri)
+                .map(|document| document.redocly)
+                .unwrap_or_default();
+            let document = Document {
+                text,
+                native,
+                redocly,
+            };
+            let diagnostics = self.diagnostics(&document);
+            documents.insert(uri.to_string(), document);
+            diagnostics
+        };
+        self.publish(uri, diagnostics)
+    }
+
+    /// The fast native checks of `text`: the YAML style checks, the
+    /// structure check, the native lint checks, and the security audit when
+    /// `security` is on.
+    fn check(&self, uri: &str, text: &str) -> Vec<Finding> {
+        let path = uri_path(uri);
+        let label = path
+            .as_deref()
+            .map(|path| {
+                path.strip_prefix(&self.root)
+                    .unwrap_or(path)
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .unwrap_or_else(|| uri.to_string());
+        let yaml = !text.trim_start().starts_with('{');
+        let mut findings = rules::check_yaml_style(&label, text.as_bytes(), yaml);
+        let doc = match serde_yaml::from_str::<serde_yaml::Value>(text) {
+            Ok(doc) => doc,
+            Err(err) => {
+                let location = err.location().map_or(format!("{label}:1:1"), |location| {
+                    format!("{label}:{}:{}", location.line(), location.column())
+                });
+                findings.push(Finding::error("parse", location, err.to_string()));
+                return findings;
+            }
+        };
+        let base_dir = path.as_deref().and_then(Path::parent).unwrap_or(&self.root);
+        findings.extend(rules::check_structure(&doc, base_dir));
+        for (_, check) in steps::native_checks(&doc, &self.config) {
+            findings.extend(check);
+        }
+        if self.config.security {
+            findings.extend(rules::check_security(
+                &doc,
+                &steps::security_levels(&self.config),
+            ));
+        }
+        findings
+    }
+
+    /// Diagnostics for the document's findings that aren't suppressed, one
+    /// per problem when several checks report it.
+    fn diagnostics(&self, document: &Document) -> Vec<Diagnostic> {
+        let inline = suppressions::inline(&document.text);
+        let starts = rules::node_starts(&document.text);
+        let mut seen = HashSet::new();
+        document
+            .native
+            .iter()
+            .chain(&document.redocly)
+            .filter(|finding| {
+                !self
+                    .suppressions
+                    .iter()
+                    .chain(&inline)
+                    .any(|suppression| suppression.matches(&finding.rule, &finding.path))
+            })
+            .filter(|finding| {
+                seen.insert((
+                    rules::canonical_rule(&finding.rule).to_string(),
+                    finding.path.clone(),
+                ))
+            })
+            .map(|finding| {
+                let (line, column) = if finding.path.starts_with('#') {
+                    rules::locate(&starts, &finding.path)
+                } else {
+                    source_location(&finding.path)
+                }
+                .unwrap_or((1, 1));
+                Diagnostic {
+                    range: range(&document.text, line, column),
+                    severity: match finding.severity {
+                        Severity::Error => 1,
+                        Severity::Warning => 2,
+                    },
+                    code: finding.rule.clone(),
+                    source: "oav",
+                    message: finding.message.clone(),
+                }
+            })
+            .collect()
+    }
+
+    fn publish(&self, uri: &str, diagnostics: Vec<Diagnostic>) -> Result<()> {
+        #[derive(Serialize)]
+        struct Params<'a> {
+            uri: &'a str,
+            diagnostics: Vec<Diagnostic>,
+        }
+        self.notify(
+            "textDocument/publishDiagnostics",
+            &Params { uri, diagnostics },
+        )
+    }
+
+    fn respond<T: Serialize>(&self, id: Value, result: &T) -> Result<()> {
+        #[derive(Serialize)]
+        struct Response<'a, T> {
+            jsonrpc: &'static str,
+            id: Value,
+            result: &'a T,
+        }
+        self.send(&Response {
+            jsonrpc: "2.0",
+            id,
+            result,
+        })
+    }
+
+    fn respond_error(&self, id: Value, code: i64, message: &str) -> Result<()> {
+        #[derive(Serialize)]
+        struct Error<'a> {
+            code: i64,
+            message: &'a str,
+        }
+        #[derive(Serialize)]
+        struct Response<'a> {
+            jsonrpc: &'static str,
+            id: Value,
+            error: Error<'a>,
+        }
+        self.send(&Response {
+            jsonrpc: "2.0",
+            id,
+            error: Error { code, message },
+        })
+    }
+
+    fn notify<T: Serialize>(&self, method: &str, params: &T) -> Result<()> {
+        #[derive(Serialize)]
+        struct Notification<'a, T> {
+            jsonrpc: &'static str,
+            method: &'a str,
+            params: &'a T,
+        }
+        self.send(&Notification {
+            jsonrpc: "2.0",
+            method,
+            params,
+        })
+    }
+
+    fn send<T: Serialize>(&self, message: &T) -> Result<()> {
+        let body = serde_json::to_string(message)?;
+        let mut stdout = self.stdout.lock().unwrap_or_else(|err| err.into_inner());
+        write!(stdout, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+        stdout.flush().context("Failed to write to the client")
+    }
+
+    fn lock_documents(&self) -> std::sync::MutexGuard<'_, HashMap<String, Document>> {
+        self.documents.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Lint saved documents with Redocly once saves pause for `LINT_DELAY`,
+/// and publish their diagnostics again with its findings.
+fn lint_saves(server: &Server, saves: &Receiver<String>) {
+    let output = Output::new(false, true);
+    let log_dir = server.root.join(OAV_DIR).join("lsp");
+    let _ = fs::create_dir_all(&log_dir);
+    while let Ok(uri) = saves.recv() {
+        let mut pending = vec![uri];
+        while let Ok(uri) = saves.recv_timeout(LINT_DELAY) {
+            if !pending.contains(&uri) {
+                pending.push(uri);
+            }
+        }
+        for uri in pending {
+            // Redocly sees the workspace, so only specs inside it are linted.
+            let Some(spec_path) = uri_path(&uri)
+                .and_then(|path| path.strip_prefix(&server.root).ok().map(Path::to_path_buf))
+            else {
+                continue;
+            };
+            if !server.lock_documents().contains_key(&uri) {
+                continue;
+            }
+            let log_path = log_dir.join("redocly.log");
+            let problems = match steps::redocly(
+                &server.root,
+                &spec_path,
+                &server.config,
+                &log_path,
+                &output,
+            ) {
+                Ok(Some(run)) => run.problems,
+                Ok(None) | Err(_) => {
+                    let message = format!(
+                        "Redocly failed to lint {}. See {}",
+                        spec_path.display(),
+                        log_path.display()
+                    );
+                    let _ = server.notify("window/showMessage", &show_message(1, &message));
+                    continue;
+                }
+            };
+            let diagnostics = {
+                let mut documents = server.lock_documents();
+                let Some(document) = documents.get_mut(&uri) else {
+                    continue;
+                };
+                document.redocly = problems;
+                server.diagnostics(document)
+            };
+            let _ = server.publish(&uri, diagnostics);
+        }
+    }
+}
+
+/// The next message from the client, or `None` at the end of input.
+fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = Some(
+                value
+                    .trim()
+                    .parse::<usize>()
+                    .context("Invalid Content-Length header")?,
+            );
+        }
+    }
+    let Some(length) = length else {
+        bail!("Message without a Content-Length header");
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8(body).context("Message is not valid UTF-8")?;
+    serde_json::from_str(&body)
+        .map(Some)
+        .context("Message is not valid JSON")
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            // Full document sync.
+            "textDocumentSync": { "openClose": true, "change": 1, "save": true },
+        },
+        "serverInfo": { "name": "oav", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+/// `window/showMessage` params; `kind` 1 is an error, 2 a warning.
+fn show_message(kind: u8, message: &str) -> Value {
+    json!({ "type": kind, "message": message })
+}
+
+/// Whether `text` looks like an OpenAPI or Swagger document, so other YAML
+/// and JSON files the editor opens are left alone.
+fn is_openapi(text: &str) -> bool {
+    text.lines().any(|line| {
+        ["openapi", "swagger", "\"openapi\"", "\"swagger\""]
+            .iter()
+            .any(|key| {
+                line.trim_start()
+                    .strip_prefix(key)
+                    .is_some_and(|rest| rest.trim_start().starts_with(':'))
+            })
+    })
+}
+
+/// The local path of a `file://` URI.
+fn uri_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = (bytes[idx] == b'%')
+            .then(|| path.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+/// `(line, column)` of a `file:line:column` finding path.
+fn source_location(path: &str) -> Option<(usize, usize)> {
+    let mut parts = path.rsplitn(3, ':');
+    let column = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    Some((line, column))
+}
+
+/// From the 1-based `line` and byte `column` to the end of that line, in
+/// the protocol's 0-based lines and UTF-16 characters.
+fn range(text: &str, line: usize, column: usize) -> Range {
+    let content = text.lines().nth(line.saturating_sub(1)).unwrap_or_default();
+    let prefix = content.get(..column.saturating_sub(1)).unwrap_or_default();
+    let start = prefix.encode_utf16().count();
+    let end = content.encode_utf16().count().max(start);
+    let line = line.saturating_sub(1);
+    Range {
+        start: Position {
+            line,
+            character: start,
+        },
+        end: Position {
+            line,
+            character: end,
+        },
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::{Context, Result};
+use clap::{Command, CommandFactory};
+use clap_mangen::Man;
+use std::fs;
+use std::path::Path;
+
+use crate::cli::Cli;
+use crate::output::Output;
+
+/// Write a section 1 man page for `oav` and each of its subcommands
+/// (`oav.1`, `oav-validate.1`, `oav-config-set.1`, ...) to `dir`.
+pub fn write(dir: &Path, output: &Output) -> Result<()> {
+    let mut root = Cli::command().disable_help_subcommand(true);
+    root.build();
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    write_pages(dir, &root, output)
+}
+
+fn write_pages(dir: &Path, command: &Command, output: &Output) -> Result<()> {
+    let man = Man::new(command.clone()).source(concat!("oav ", env!("CARGO_PKG_VERSION")));
+    let path = dir.join(man.get_filename());
+    let mut page = Vec::new();
+    man.render(&mut page)?;
+    fs::write(&path, page).with_context(|| format!("Failed to write {}", path.display()))?;
+    output.println(&format!("Wrote {}", path.display()));
+
+    for subcommand in command.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        write_pages(dir, subcommand, output)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::env::consts;
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::results::RunResults;
+use crate::util::{OAV_DIR, format_timestamp, to_posix_path, unix_now, write_atomic};
+
+const FILE_NAME: &str = "run.json";
+
+/// What a run ran with, to reproduce it or bisect a regression.
+#[derive(Serialize)]
+struct Manifest<'a> {
+    run_id: &'a str,
+    oav_version: &'static str,
+    started_at: &'a str,
+    finished_at: String,
+    host: Host,
+    spec: Spec,
+    /// Image reference -> resolved digest of every image the run used.
+    images: &'a BTreeMap<String, String>,
+    /// The config after profiles, environment overrides, and flags.
+    config: &'a Config,
+}
+
+#[derive(Serialize)]
+struct Host {
+    os: &'static str,
+    arch: &'static str,
+}
+
+#[derive(Serialize)]
+struct Spec {
+    path: String,
+    sha256: String,
+}
+
+/// Write `.oav/run.json` for `run`, which validated `spec_path` with `config`.
+pub fn write(root: &Path, config: &Config, spec_path: &Path, run: &RunResults) -> Result<()> {
+    let content = fs::read(root.join(spec_path))
+        .with_context(|| format!("Failed to read {}", spec_path.display()))?;
+    let mut hasher = Sha256::new();
+    hasher.update(&content);
+    let manifest = Manifest {
+        run_id: &run.run_id,
+        oav_version: env!("CARGO_PKG_VERSION"),
+        started_at: &run.started_at,
+        finished_at: format_timestamp(unix_now()),
+        host: Host {
+            os: consts::OS,
+            arch: consts::ARCH,
+        },
+        spec: Spec {
+            path: to_posix_path(spec_path),
+            sha256: format!("{:x}", hasher.finalize()),
+        },
+        images: &run.images,
+        config,
+    };
+    write_atomic(
+        &root.join(OAV_DIR).join(FILE_NAME),
+        &serde_json::to_string_pretty(&manifest)?,
+    )
+}
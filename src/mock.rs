@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::Config;
+use crate::docker;
+use crate::output::Output;
+use crate::remote;
+use crate::util::{OAV_DIR, to_posix_path, write_log_header};
+
+/// Port Prism listens on inside its container.
+const PRISM_PORT: u16 = 4010;
+
+/// Serve a Prism mock of `spec_path` on host port `port` until interrupted.
+/// Prism's output goes to the terminal and `.oav/reports/mock/prism.log`.
+/// Ctrl-C reaches Prism through `docker run` (`--init` makes it stop
+/// promptly) while oav waits for the container to exit and removes it.
+pub fn serve(
+    root: &Path,
+    spec_path: &Path,
+    config: &Config,
+    port: u16,
+    dynamic: bool,
+    output: &Output,
+) -> Result<bool> {
+    let reports_dir = root.join(OAV_DIR).join("reports").join("mock");
+    fs::create_dir_all(&reports_dir).context("Failed to create mock reports directory")?;
+    let log_path = reports_dir.join("prism.log");
+
+    let container = docker::container_name("mock-prism");
+    let mut args: Vec<String> = vec![
+        "run".to_string(),
+        "--rm".to_string(),
+        "--init".to_string(),
+        "--name".to_string(),
+        container.clone(),
+    ];
+    args.extend(docker::user_args(config));
+    args.extend(docker::platform_args(config.platform.as_deref()));
+    args.extend(docker::env_args(config));
+    args.extend([
+        "-p".to_string(),
+        format!("{port}:{PRISM_PORT}"),
+        "-v".to_string(),
+        remote::work_mount(root, config),
+        "-w".to_string(),
+        format!("/work/{OAV_DIR}"),
+        config.prism_image.clone(),
+        "mock".to_string(),
+        "-h".to_string(),
+        "0.0.0.0".to_string(),
+    ]);
+    if dynamic {
+        args.push("--dynamic".to_string());
+    }
+    args.push(format!("/work/{}", to_posix_path(spec_path)));
+    write_log_header(
+        &log_path,
+        &format!("$ {} {}", docker::engine().binary(), args.join(" ")),
+    )?;
+
+    output.println(&format!(
+        "Mock server for {} on http://localhost:{port} (Ctrl-C to stop)",
+        spec_path.display()
+    ));
+    output.println(&format!("Log: {}", log_path.display()));
+    // Stream Prism's request log even without --verbose.
+    let tee = Output::new(!output.quiet, output.quiet);
+    let mut command = docker::engine().command();
+    command.args(&args);
+    outlive_interrupt();
+    let (status, _) = docker::run_with_logging(&mut command, &log_path, &tee, None, None)?;
+
+    // Normally gone already; a failed start can leave it behind.
+    let _ = docker::engine()
+        .command()
+        .args(["rm", "-f", &container])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    Ok(status.success() || INTERRUPTED.load(Ordering::Relaxed))
+}
+
+/// Set once Ctrl-C was pressed, after which Prism's exit status is expected.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Keep oav alive on Ctrl-C so it can clean up after the container, which
+/// gets the same interrupt. A handler, unlike `SIG_IGN`, isn't inherited by
+/// the `docker` child.
+#[cfg(unix)]
+fn outlive_interrupt() {
+    extern "C" fn interrupted(_: libc::c_int) {
+        INTERRUPTED.store(true, Ordering::Relaxed);
+    }
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            interrupted as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+}
+
+#[cfg(not(unix))]
+fn outlive_interrupt() {}
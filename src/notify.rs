@@ -0,0 +1,158 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::{Config, NotifyOn};
+use crate::http;
+use crate::output::Output;
+use crate::results::RunResults;
+use crate::util::{OAV_DIR, expand_env_vars, write_atomic};
+
+/// Failed tasks listed in a notification; the report has the rest.
+const FAILED_TASKS: usize = 10;
+
+/// What `webhook_url` receives.
+#[derive(Serialize)]
+struct Summary<'a> {
+    run_id: &'a str,
+    started_at: &'a str,
+    /// `passed` or `failed`.
+    result: &'static str,
+    passed: usize,
+    failed: usize,
+    findings: usize,
+    report: &'a str,
+    failed_tasks: Vec<FailedTask<'a>>,
+}
+
+#[derive(Serialize)]
+struct FailedTask<'a> {
+    stage: &'a str,
+    scope: &'a str,
+    target: &'a str,
+    status: &'a str,
+}
+
+#[derive(Serialize)]
+struct SlackMessage {
+    text: String,
+}
+
+/// Post the summary of the finished `run` to the configured webhooks, when
+/// it failed or with `on: always` for every run. A webhook that can't be
+/// reached is reported without failing the run.
+pub fn send(root: &Path, config: &Config, run: &RunResults, output: &Output) {
+    let notifications = &config.notifications;
+    if run.failed() == 0 && notifications.on == NotifyOn::Failure {
+        return;
+    }
+    let dashboard = root.join(OAV_DIR).join("reports").join("dashboard.html");
+    let report = match &notifications.report_url {
+        Some(url) => expand_env_vars(url),
+        None => dashboard.display().to_string(),
+    };
+    let summary = summary(run, &report);
+    let targets = [
+        (
+            "webhook",
+            &notifications.webhook_url,
+            serde_json::to_string(&summary),
+        ),
+        (
+            "slack",
+            &notifications.slack_webhook_url,
+            serde_json::to_string(&SlackMessage {
+                text: slack_text(&summary),
+            }),
+        ),
+    ];
+    for (name, url, payload) in targets {
+        let Some(url) = url else {
+            continue;
+        };
+        let sent = payload
+            .map_err(anyhow::Error::from)
+            .and_then(|payload| post(root, name, &expand_env_vars(url), &payload));
+        match sent {
+            Ok(()) => output.println(&format!("Notified {name}")),
+            Err(err) => output.print_warning(&format!("Notification to {name} failed: {err:#}")),
+        }
+    }
+}
+
+fn summary<'a>(run: &'a RunResults, report: &'a str) -> Summary<'a> {
+    Summary {
+        run_id: &run.run_id,
+        started_at: &run.started_at,
+        result: if run.failed() > 0 { "failed" } else { "passed" },
+        passed: run.passed(),
+        failed: run.failed(),
+        findings: run.findings.len(),
+        report,
+        failed_tasks: run
+            .entries
+            .iter()
+            .filter(|entry| entry.failed())
+            .map(|entry| FailedTask {
+                stage: &entry.stage,
+                scope: &entry.scope,
+                target: &entry.target,
+                status: &entry.status,
+            })
+            .collect(),
+    }
+}
+
+/// The summary in Slack's mrkdwn, with at most [`FAILED_TASKS`] failed tasks.
+fn slack_text(summary: &Summary) -> String {
+    let icon = if summary.failed > 0 {
+        ":x:"
+    } else {
+        ":white_check_mark:"
+    };
+    let mut text = format!(
+        "{icon} OpenAPI validation {}: {} passed, {} failed, {} finding(s) (`{}`)",
+        summary.result,
+        summary.passed,
+        summary.failed,
+        summary.findings,
+        slack_escape(summary.run_id)
+    );
+    for task in summary.failed_tasks.iter().take(FAILED_TASKS) {
+        text.push_str(&format!(
+            "\n• {} {} {}: {}",
+            slack_escape(task.stage),
+            slack_escape(task.scope),
+            slack_escape(task.target),
+            slack_escape(task.status)
+        ));
+    }
+    if summary.failed_tasks.len() > FAILED_TASKS {
+        text.push_str(&format!(
+            "\n• and {} more",
+            summary.failed_tasks.len() - FAILED_TASKS
+        ));
+    }
+    if summary.report.starts_with("http://") || summary.report.starts_with("https://") {
+        text.push_str(&format!("\n<{}|Report>", summary.report));
+    } else {
+        text.push_str(&format!("\nReport: {}", slack_escape(summary.report)));
+    }
+    text
+}
+
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// POST `payload` to `url`, keeping it as `.oav/notifications/<name>.json`.
+fn post(root: &Path, name: &str, url: &str, payload: &str) -> Result<()> {
+    let dir = root.join(OAV_DIR).join("notifications");
+    fs::create_dir_all(&dir).context("Failed to create notifications directory")?;
+    write_atomic(&dir.join(format!("{name}.json")), payload)?;
+    http::post_json(url, payload, Duration::from_secs(10))
+}
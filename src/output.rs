@@ -1,4 +1,4 @@
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use owo_colors::OwoColorize;
 use std::env;
 use std::io::{self, Write};
@@ -52,6 +52,34 @@ impl Output {
         println!("{} {label}", self.status_icon(success));
     }
 
+    /// Like `finish_spinner` for a failed step whose `severity` is `warn`.
+    pub fn finish_spinner_warn(&self, spinner: Option<&ProgressBar>, label: &str) {
+        if let Some(spinner) = spinner {
+            spinner.finish_and_clear();
+        }
+        if self.quiet {
+            return;
+        }
+        println!("{} {label}", self.warn_icon());
+    }
+
+    /// Container for concurrent progress bars, when progress is shown.
+    pub fn multi_progress(&self) -> Option<MultiProgress> {
+        self.progress.then(MultiProgress::new)
+    }
+
+    pub fn add_progress_bar(&self, multi: &MultiProgress, label: &str) -> ProgressBar {
+        let bar = multi.add(ProgressBar::new(0));
+        let style = ProgressStyle::with_template("  {spinner} {prefix} [{bar:24}] {msg}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .tick_strings(&["-", "\\", "|", "/", " "])
+            .progress_chars("=> ");
+        bar.set_style(style);
+        bar.set_prefix(label.to_string());
+        bar.enable_steady_tick(Duration::from_millis(100));
+        bar
+    }
+
     pub fn phase_header(&self, label: &str) {
         if self.quiet {
             return;
@@ -88,6 +116,19 @@ impl Output {
         }
     }
 
+    /// Like `substep_finish` for a failed task of a `warn`-level stage.
+    pub fn substep_warn(&self, label: &str) {
+        if self.quiet {
+            return;
+        }
+        let status = self.warn_icon();
+        if self.progress {
+            print!("\r{status}   {label}\x1B[K\n");
+        } else {
+            println!("{status}   {label}");
+        }
+    }
+
     pub fn println(&self, message: &str) {
         if !self.quiet {
             println!("{message}");
@@ -106,6 +147,23 @@ impl Output {
         }
     }
 
+    /// Warning on stderr. Clears an in-progress substep line first; the
+    /// substep's result line replaces it.
+    pub fn print_warning(&self, message: &str) {
+        if self.quiet {
+            return;
+        }
+        if self.progress {
+            print!("\r\x1B[K");
+            let _ = io::stdout().flush();
+        }
+        if self.color {
+            eprintln!("{} {}", "warning:".yellow().bold(), message);
+        } else {
+            eprintln!("warning: {message}");
+        }
+    }
+
     pub fn print_summary(&self, passed: usize, failed: usize) {
         if self.quiet {
             return;
@@ -137,4 +195,12 @@ impl Output {
             "FAIL".to_string()
         }
     }
+
+    fn warn_icon(&self) -> String {
+        if self.color {
+            "!".yellow().to_string()
+        } else {
+            "WARN".to_string()
+        }
+    }
 }
@@ -0,0 +1,129 @@
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use wasmi::{Config as WasmConfig, Engine, Linker, Module, Store};
+
+use crate::rules::{Finding, Severity};
+
+/// Instructions (roughly) a plugin may execute per spec before it is stopped.
+const FUEL: u64 = 10_000_000_000;
+
+/// A custom rule set compiled to WebAssembly.
+///
+/// A plugin exports `memory`, `alloc(len: i32) -> i32`, and
+/// `check(ptr: i32, len: i32) -> i64`. oav writes the spec as JSON to a
+/// buffer from `alloc` and calls `check`, which returns the JSON findings'
+/// location as `ptr << 32 | len`. Plugins can't import anything.
+pub struct Plugin {
+    pub name: String,
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PluginFinding {
+    rule: String,
+    severity: String,
+    #[serde(default = "root_path")]
+    path: String,
+    message: String,
+}
+
+fn root_path() -> String {
+    "#".to_string()
+}
+
+/// The `*.wasm` files in `dir`, by name. A missing directory is an error,
+/// since the config names it.
+pub fn load(dir: &Path) -> Result<Vec<Plugin>> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read plugin_dir {}", dir.display()))?;
+    let mut plugins: Vec<Plugin> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            Some(Plugin { name, path })
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(plugins)
+}
+
+impl Plugin {
+    /// Run the plugin on the parsed spec and return its findings.
+    pub fn run(&self, doc: &Value) -> Result<Vec<Finding>> {
+        let wasm = fs::read(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+        let mut config = WasmConfig::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, &wasm).map_err(|err| anyhow!("Invalid module: {err}"))?;
+        let mut store = Store::new(&engine, ());
+        store
+            .set_fuel(FUEL)
+            .map_err(|err| anyhow!("Failed to set fuel: {err}"))?;
+        let instance = Linker::<()>::new(&engine)
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|err| {
+                anyhow!("Failed to instantiate (plugins can't import anything): {err}")
+            })?;
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| anyhow!("Plugin does not export memory"))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|err| anyhow!("Plugin does not export alloc(i32) -> i32: {err}"))?;
+        let check = instance
+            .get_typed_func::<(i32, i32), i64>(&store, "check")
+            .map_err(|err| anyhow!("Plugin does not export check(i32, i32) -> i64: {err}"))?;
+
+        let input = serde_json::to_string(doc)?;
+        let len = i32::try_from(input.len()).context("Spec too large for a plugin")?;
+        let ptr = alloc
+            .call(&mut store, len)
+            .map_err(|err| anyhow!("alloc failed: {err}"))?;
+        memory
+            .write(&mut store, ptr as u32 as usize, input.as_bytes())
+            .map_err(|err| anyhow!("alloc returned an invalid buffer: {err}"))?;
+        let packed = check
+            .call(&mut store, (ptr, len))
+            .map_err(|err| anyhow!("check failed: {err}"))?;
+
+        let (out_ptr, out_len) = ((packed as u64 >> 32) as usize, packed as u32 as usize);
+        let mut output = vec![0; out_len];
+        memory
+            .read(&store, out_ptr, &mut output)
+            .map_err(|err| anyhow!("check returned an invalid buffer: {err}"))?;
+        let output = String::from_utf8(output).context("Plugin output is not UTF-8")?;
+        let findings: Vec<PluginFinding> =
+            serde_json::from_str(&output).context("Plugin output is not a list of findings")?;
+        findings
+            .into_iter()
+            .map(PluginFinding::into_finding)
+            .collect()
+    }
+}
+
+impl PluginFinding {
+    fn into_finding(self) -> Result<Finding> {
+        let severity = match self.severity.as_str() {
+            "error" => Severity::Error,
+            "warning" | "warn" => Severity::Warning,
+            other => bail!(
+                "Finding {} has severity {other} (expected error or warning)",
+                self.rule
+            ),
+        };
+        Ok(Finding {
+            rule: self.rule,
+            severity,
+            path: self.path,
+            message: self.message,
+        })
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::{Context, Result, bail};
+use std::env;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use ureq::unversioned::multipart::{Form, Part};
+use walkdir::WalkDir;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use crate::history;
+use crate::http;
+use crate::output::Output;
+use crate::results::{self, RunResults};
+use crate::util::{OAV_DIR, expand_env_vars, to_posix_path};
+
+/// Environment variable holding the token sent as `Authorization: Bearer`.
+pub const TOKEN_VAR: &str = "OAV_PUBLISH_TOKEN";
+
+/// Upload `run` to `endpoint` as a multipart form: `manifest` (its
+/// `run.json`, when kept), `results`, and with `reports` a zip of its
+/// reports. The latest run's files are the workspace's own; earlier runs'
+/// come from `.oav/history`.
+pub fn publish(
+    root: &Path,
+    endpoint: &str,
+    run: &RunResults,
+    reports: bool,
+    output: &Output,
+) -> Result<()> {
+    let latest = results::load_latest(root)?.is_some_and(|latest| latest.run_id == run.run_id);
+    let run_dir = if latest {
+        root.join(OAV_DIR)
+    } else {
+        history::dir(root).join(&run.run_id)
+    };
+
+    let manifest = fs::read_to_string(run_dir.join("run.json")).ok();
+    let results = serde_json::to_string_pretty(run)?;
+    let mut names = Vec::new();
+    let mut form = Form::new();
+    if let Some(manifest) = &manifest {
+        form = form.part("manifest", json_part(manifest, "run.json")?);
+        names.push("manifest");
+    }
+    form = form.part("results", json_part(&results, "results.json")?);
+    names.push("results");
+    if reports {
+        let reports_dir = run_dir.join("reports");
+        if !reports_dir.is_dir() {
+            bail!("No reports kept for run {}", run.run_id);
+        }
+        let staging = root.join(OAV_DIR).join("publish");
+        fs::create_dir_all(&staging).context("Failed to create publish directory")?;
+        let archive = staging.join("reports.zip");
+        zip_dir(&reports_dir, &archive)?;
+        let part = Part::file(&archive)
+            .with_context(|| format!("Failed to read {}", archive.display()))?
+            .mime_str("application/zip")?;
+        form = form.part("reports", part);
+        names.push("reports");
+    }
+
+    let token = env::var(TOKEN_VAR).ok().filter(|token| !token.is_empty());
+    let response = http::post_form(
+        &expand_env_vars(endpoint),
+        form,
+        token.as_deref(),
+        Duration::from_secs(120),
+    )
+    .with_context(|| format!("Failed to publish run {} to {endpoint}", run.run_id))?;
+
+    output.println_always(&format!(
+        "Published {} to {endpoint} ({})",
+        run.run_id,
+        names.join(", ")
+    ));
+    if !response.trim().is_empty() {
+        output.println(response.trim());
+    }
+    Ok(())
+}
+
+fn json_part<'a>(json: &'a str, file_name: &str) -> Result<Part<'a>> {
+    Ok(Part::text(json)
+        .file_name(file_name)
+        .mime_str("application/json")?)
+}
+
+/// Write the files under `dir` to the zip archive `path`, named relative to
+/// `dir`.
+fn zip_dir(dir: &Path, path: &Path) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+    for entry in WalkDir::new(dir).min_depth(1).sort_by_file_name() {
+        let entry = entry?;
+        let name = to_posix_path(entry.path().strip_prefix(dir)?);
+        if entry.file_type().is_dir() {
+            zip.add_directory(name, options)?;
+        } else {
+            zip.start_file(name, options)?;
+            let mut source = File::open(entry.path())
+                .with_context(|| format!("Failed to read {}", entry.path().display()))?;
+            io::copy(&mut source, &mut zip)?;
+        }
+    }
+    zip.finish()
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
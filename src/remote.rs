@@ -0,0 +1,235 @@
+use anyhow::{Context, Result, bail};
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::OnceLock;
+
+use crate::config::Config;
+use crate::docker;
+use crate::output::Output;
+use crate::util::{OAV_DIR, to_posix_path};
+
+const HELPER_IMAGE: &str = "busybox:1.37";
+/// Compose-file key for the workspace volume.
+const COMPOSE_VOLUME: &str = "oav-work";
+/// `.oav` entries produced by runs rather than read by containers.
+const LOCAL_ONLY: [&str; 5] = ["generated", "reports", "results", "cache", "lock"];
+
+/// Workspace copy in a volume on a remote daemon, where bind-mounting the
+/// local checkout is impossible. A helper container keeps the volume mounted
+/// so files can be copied in and out with `cp`.
+pub struct RemoteWorkspace {
+    volume: String,
+    helper: String,
+}
+
+static REMOTE: OnceLock<RemoteWorkspace> = OnceLock::new();
+
+/// Removes the helper container and volume when dropped.
+pub struct RemoteGuard;
+
+impl Drop for RemoteGuard {
+    fn drop(&mut self) {
+        if let Some(remote) = active() {
+            let _ = docker::engine()
+                .command()
+                .args(["rm", "--force", &remote.helper])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+            let _ = docker::engine()
+                .command()
+                .args(["volume", "rm", "--force", &remote.volume])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+        }
+    }
+}
+
+pub fn active() -> Option<&'static RemoteWorkspace> {
+    REMOTE.get()
+}
+
+/// Mount argument exposing the workspace at `/work`.
+pub fn work_mount(root: &Path, config: &Config) -> String {
+    match active() {
+        Some(remote) => format!("{}:/work", remote.volume),
+        None => docker::label_mount(config, &format!("{}:/work", root.display())),
+    }
+}
+
+/// If the engine is remote, create the workspace volume and upload `inputs`
+/// (paths relative to `root`) plus the `.oav` assets.
+pub fn prepare(
+    root: &Path,
+    inputs: &[PathBuf],
+    run_id: &str,
+    output: &Output,
+) -> Result<Option<RemoteGuard>> {
+    if !docker::engine().is_remote() {
+        return Ok(None);
+    }
+    output.println("Remote container engine detected; uploading the workspace to a volume");
+
+    let id = run_id.to_lowercase();
+    let remote = RemoteWorkspace {
+        volume: format!("oav-work-{id}"),
+        helper: format!("oav-work-{id}-helper"),
+    };
+    run(
+        &["volume", "create", &remote.volume],
+        "create workspace volume",
+    )?;
+    let _ = REMOTE.set(remote);
+    let guard = RemoteGuard;
+    let remote = active().context("Remote workspace was not registered")?;
+    run(
+        &[
+            "run",
+            "-d",
+            "--name",
+            &remote.helper,
+            "-v",
+            &format!("{}:/work", remote.volume),
+            HELPER_IMAGE,
+            "sleep",
+            "86400",
+        ],
+        "start workspace helper container",
+    )?;
+
+    let mut paths = inputs.to_vec();
+    for entry in fs::read_dir(root.join(OAV_DIR)).context("Failed to read .oav directory")? {
+        let name = entry?.file_name();
+        if !LOCAL_ONLY.contains(&name.to_string_lossy().as_ref()) {
+            paths.push(Path::new(OAV_DIR).join(name));
+        }
+    }
+    remote.push(root, &paths)?;
+    for dir in ["generated", "reports"] {
+        run(
+            &[
+                "exec",
+                &remote.helper,
+                "mkdir",
+                "-p",
+                &format!("/work/{OAV_DIR}/{dir}"),
+            ],
+            "prepare workspace volume",
+        )?;
+    }
+    // Copied files belong to root; containers run as the invoking user.
+    run(
+        &["exec", &remote.helper, "chmod", "-R", "a+rwX", "/work"],
+        "prepare workspace volume",
+    )?;
+    Ok(Some(guard))
+}
+
+impl RemoteWorkspace {
+    /// Upload files or directories (relative to `root`) into the volume by
+    /// streaming a tar archive into `cp`.
+    pub fn push(&self, root: &Path, paths: &[PathBuf]) -> Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let mut tar = std::process::Command::new("tar")
+            .arg("-C")
+            .arg(root)
+            .arg("-cf")
+            .arg("-")
+            .args(paths)
+            .stdout(Stdio::piped())
+            .spawn()
+            .context("Failed to run tar")?;
+        let archive = tar.stdout.take().context("Missing tar output")?;
+        let status = docker::engine()
+            .command()
+            .args(["cp", "-", &format!("{}:/work", self.helper)])
+            .stdin(Stdio::from(archive))
+            .status()
+            .context("Failed to copy workspace into volume")?;
+        let tar_status = tar.wait().context("Failed to wait for tar")?;
+        if !status.success() || !tar_status.success() {
+            bail!("Failed to upload workspace files to the remote volume");
+        }
+        // Later uploads (such as batch configs) need the same permissions.
+        run(
+            &["exec", &self.helper, "chmod", "-R", "a+rwX", "/work"],
+            "prepare workspace volume",
+        )
+    }
+
+    /// Replace a local directory (relative to `root`) with its copy from the volume.
+    pub fn pull(&self, root: &Path, path: &Path) -> Result<()> {
+        let local = root.join(path);
+        if local.exists() {
+            fs::remove_dir_all(&local)
+                .with_context(|| format!("Failed to remove {}", local.display()))?;
+        }
+        let parent = local.parent().unwrap_or(root);
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+        let source = format!("{}:/work/{}", self.helper, to_posix_path(path));
+        run(
+            &["cp", &source, &parent.display().to_string()],
+            "copy generated files from the remote volume",
+        )
+    }
+
+    /// Compose volume entry for a `./<path>` bind mount relative to `.oav`,
+    /// as a subpath of the workspace volume.
+    fn compose_mount(&self, source: &str, target: &str) -> Value {
+        let mut options = Mapping::new();
+        options.insert(
+            "subpath".into(),
+            format!("{OAV_DIR}/{}", source.trim_start_matches("./")).into(),
+        );
+        let mut mount = Mapping::new();
+        mount.insert("type".into(), "volume".into());
+        mount.insert("source".into(), COMPOSE_VOLUME.into());
+        mount.insert("target".into(), target.into());
+        mount.insert("volume".into(), Value::Mapping(options));
+        Value::Mapping(mount)
+    }
+}
+
+/// Compose volume entry for `volume`, rewriting relative bind mounts to the
+/// workspace volume when the engine is remote.
+pub fn compose_volume(volume: &str) -> Value {
+    if let Some(remote) = active()
+        && let Some((source, target)) = volume.split_once(':')
+        && source.starts_with("./")
+    {
+        return remote.compose_mount(source, target.split(':').next().unwrap_or(target));
+    }
+    Value::String(volume.to_string())
+}
+
+/// Declare the workspace volume in a compose file's top-level `volumes`.
+pub fn declare_compose_volume(volumes: &mut Mapping) {
+    if let Some(remote) = active() {
+        let mut definition = Mapping::new();
+        definition.insert("external".into(), true.into());
+        definition.insert("name".into(), remote.volume.clone().into());
+        volumes.insert(COMPOSE_VOLUME.into(), Value::Mapping(definition));
+    }
+}
+
+fn run(args: &[&str], action: &str) -> Result<()> {
+    let output = docker::engine()
+        .command()
+        .args(args)
+        .stdout(Stdio::null())
+        .output()
+        .with_context(|| format!("Failed to {action}"))?;
+    if !output.status.success() {
+        bail!(
+            "Failed to {action}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
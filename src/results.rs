@@ -0,0 +1,619 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::baseline;
+use crate::events::{self, Event};
+use crate::known_issues::KnownIssue;
+use crate::rules::{self, Finding, Severity};
+use crate::suppressions::Suppression;
+use crate::util::{OAV_DIR, format_timestamp, unix_now, write_atomic};
+
+const RESULTS_DIR: &str = "results";
+const LATEST_FILE: &str = "latest";
+const KEEP_RESULTS: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StatusEntry {
+    pub stage: String,
+    pub scope: String,
+    pub target: String,
+    pub status: String,
+    pub log_path: String,
+    /// Resource use of the task's own container, when it had one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<TaskMetrics>,
+    /// When the task started, if it was timed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    /// Wall-clock time of the task, when it was timed from its start.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Warnings the tool printed, such as unsupported generator features.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// A failure of a target in `allowed_failures`, reported but not failing the run.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allowed_failure: bool,
+    /// Reason of the `.oav-known-issues.yaml` entry covering this failure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub known_issue: Option<String>,
+    /// A failure of a `warn`-level stage (`severity`), reported but not failing the run.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub warn_only: bool,
+}
+
+/// A lint finding left out by an `.oavignore` entry.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SuppressedFinding {
+    pub check: String,
+    pub rule: String,
+    pub path: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// A problem in the spec, reported by one or more checks. Findings of
+/// different checks with equivalent rules at the same location, or one below
+/// the other, are one problem.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MergedFinding {
+    /// The native rule, if the checks' rules are equivalent to one.
+    pub rule: String,
+    /// `error` if any check reported an error, else `warning`.
+    pub severity: String,
+    pub path: String,
+    /// The first check's message.
+    pub message: String,
+    /// Checks that reported it, in the order they ran.
+    pub checks: Vec<String>,
+}
+
+impl MergedFinding {
+    fn covers(&self, finding: &Finding) -> bool {
+        let below = |path: &str, base: &str| {
+            path == base
+                || path
+                    .strip_prefix(base)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        };
+        self.rule == rules::canonical_rule(&finding.rule)
+            && (below(&finding.path, &self.path) || below(&self.path, &finding.path))
+    }
+}
+
+/// The findings of one lint check, sorted by what decides pass or fail.
+pub struct LintFindings {
+    /// Findings that count.
+    pub findings: Vec<Finding>,
+    /// Findings in `.oav-baseline.json`, logged but not counted.
+    pub accepted: Vec<Finding>,
+    /// How many `.oavignore` left out.
+    pub suppressed: usize,
+}
+
+/// Peak memory and CPU time of a task's container, sampled while it ran.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct TaskMetrics {
+    pub peak_memory_bytes: u64,
+    pub cpu_seconds: f64,
+}
+
+impl StatusEntry {
+    /// `cached` tasks passed on an earlier run with identical inputs.
+    pub fn passed(&self) -> bool {
+        matches!(self.status.as_str(), "ok" | "cached")
+    }
+
+    pub fn failed(&self) -> bool {
+        !self.allowed_failure
+            && self.known_issue.is_none()
+            && !self.warn_only
+            && self.failed_status()
+    }
+
+    fn failed_status(&self) -> bool {
+        matches!(self.status.as_str(), "fail" | "timeout" | "oom")
+    }
+
+    /// `status`, marked when the failure is allowed, known, or only a warning.
+    pub fn status_label(&self) -> String {
+        if self.allowed_failure {
+            format!("{} (allowed)", self.status)
+        } else if self.known_issue.is_some() {
+            format!("{} (known)", self.status)
+        } else if self.warn_only {
+            format!("{} (warn)", self.status)
+        } else {
+            self.status.clone()
+        }
+    }
+
+    /// Dashboard badge class: `warn` for failures of `warn`-level stages.
+    pub fn badge(&self) -> &str {
+        if self.warn_only { "warn" } else { &self.status }
+    }
+}
+
+/// Wall-clock time of one step of a run, such as `Lint` or `Generate`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StepTiming {
+    pub step: String,
+    pub success: bool,
+    pub duration_ms: u64,
+}
+
+/// Results of a single `oav validate` run, persisted as `.oav/results/<run_id>.json`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RunResults {
+    pub run_id: String,
+    pub started_at: String,
+    pub entries: Vec<StatusEntry>,
+    /// Image reference -> resolved digest of every image the run used.
+    #[serde(default)]
+    pub images: BTreeMap<String, String>,
+    /// Lint findings left out by `.oavignore`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressed: Vec<SuppressedFinding>,
+    /// Findings that count, merged across checks.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<MergedFinding>,
+    /// Steps that ran, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<StepTiming>,
+}
+
+impl RunResults {
+    pub fn passed(&self) -> usize {
+        self.entries.iter().filter(|e| e.passed()).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.entries.iter().filter(|e| e.failed()).count()
+    }
+
+    pub fn allowed_failures(&self) -> usize {
+        self.entries.iter().filter(|e| e.allowed_failure).count()
+    }
+
+    pub fn known_failures(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.known_issue.is_some())
+            .count()
+    }
+
+    pub fn warned(&self) -> usize {
+        self.entries.iter().filter(|e| e.warn_only).count()
+    }
+}
+
+/// Records task results for the current run. Every record rewrites the run's
+/// JSON file atomically under a lock, so concurrent tasks can't interleave
+/// writes, and refreshes the `status.tsv` export kept for existing consumers.
+pub struct ResultsStore {
+    root: PathBuf,
+    path: PathBuf,
+    results: Mutex<RunResults>,
+    fail_fast: bool,
+    allowed_failures: Vec<String>,
+    warn_stages: Vec<String>,
+    known_issues: Vec<KnownIssue>,
+    baseline: Vec<baseline::Entry>,
+    suppressions: Vec<Suppression>,
+    /// Every lint finding seen, for `oav lint --update-baseline`.
+    lint_findings: Mutex<Vec<baseline::Entry>>,
+    /// When each running task started, by `stage/scope/target`, as a clock
+    /// reading for its duration and a Unix time.
+    started: Mutex<HashMap<String, (Instant, u64)>>,
+}
+
+impl ResultsStore {
+    pub fn create(root: &Path) -> Result<Self> {
+        let dir = root.join(OAV_DIR).join(RESULTS_DIR);
+        fs::create_dir_all(&dir).context("Failed to create results directory")?;
+
+        let now = unix_now();
+        let run_id = format!(
+            "{}-{}",
+            format_timestamp(now).replace(['-', ':'], ""),
+            std::process::id()
+        );
+        let store = Self {
+            root: root.to_path_buf(),
+            path: dir.join(format!("{run_id}.json")),
+            results: Mutex::new(RunResults {
+                run_id: run_id.clone(),
+                started_at: format_timestamp(now),
+                entries: Vec::new(),
+                images: BTreeMap::new(),
+                suppressed: Vec::new(),
+                findings: Vec::new(),
+                steps: Vec::new(),
+            }),
+            fail_fast: false,
+            allowed_failures: Vec::new(),
+            warn_stages: Vec::new(),
+            known_issues: Vec::new(),
+            baseline: Vec::new(),
+            suppressions: Vec::new(),
+            lint_findings: Mutex::new(Vec::new()),
+            started: Mutex::new(HashMap::new()),
+        };
+        store.persist(&store.snapshot())?;
+        write_atomic(&dir.join(LATEST_FILE), &run_id)?;
+        prune(&dir)?;
+        events::emit(&Event::RunStarted { run_id: &run_id });
+        Ok(store)
+    }
+
+    pub fn run_id(&self) -> String {
+        self.snapshot().run_id
+    }
+
+    /// Stop scheduling tasks once one has failed (`--fail-fast`).
+    pub fn with_fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// Generators whose failures past lint don't fail the run
+    /// (`allowed_failures`).
+    pub fn with_allowed_failures(mut self, allowed_failures: Vec<String>) -> Self {
+        self.allowed_failures = allowed_failures;
+        self
+    }
+
+    /// Stages whose failures are recorded as warnings (`severity`).
+    pub fn with_warn_stages(mut self, warn_stages: Vec<String>) -> Self {
+        self.warn_stages = warn_stages;
+        self
+    }
+
+    /// Expected failures from `.oav-known-issues.yaml`.
+    pub fn with_known_issues(mut self, known_issues: Vec<KnownIssue>) -> Self {
+        self.known_issues = known_issues;
+        self
+    }
+
+    /// Lint findings accepted in `.oav-baseline.json`.
+    pub fn with_baseline(mut self, baseline: Vec<baseline::Entry>) -> Self {
+        self.baseline = baseline;
+        self
+    }
+
+    /// Lint findings to leave out, from `.oavignore`.
+    pub fn with_suppressions(mut self, suppressions: Vec<Suppression>) -> Self {
+        self.suppressions = suppressions;
+        self
+    }
+
+    /// Drop the findings of lint check `check` that `.oavignore` suppresses,
+    /// recording them for the dashboard, and split the rest into new ones
+    /// and ones in the baseline. Those are kept for [`Self::lint_findings`];
+    /// new ones are merged into the run's findings.
+    pub fn filter_findings(&self, check: &str, findings: Vec<Finding>) -> Result<LintFindings> {
+        let mut suppressed = Vec::new();
+        let mut kept = Vec::new();
+        for finding in findings {
+            match self
+                .suppressions
+                .iter()
+                .find(|suppression| suppression.matches(&finding.rule, &finding.path))
+            {
+                Some(suppression) => suppressed.push(SuppressedFinding {
+                    check: check.to_string(),
+                    rule: finding.rule,
+                    path: finding.path,
+                    message: finding.message,
+                    reason: suppression.reason.clone(),
+                }),
+                None => kept.push(finding),
+            }
+        }
+        let suppressed_count = suppressed.len();
+
+        let mut seen = self
+            .lint_findings
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let (findings, accepted): (Vec<Finding>, Vec<Finding>) =
+            kept.into_iter().partition(|finding| {
+                let entry = baseline::Entry::new(check, finding);
+                let accepted = self.baseline.contains(&entry);
+                seen.push(entry);
+                !accepted
+            });
+
+        if !suppressed.is_empty() || !findings.is_empty() {
+            let mut results = self
+                .results
+                .lock()
+                .map_err(|_| anyhow::anyhow!("Results lock poisoned"))?;
+            results.suppressed.extend(suppressed);
+            for finding in &findings {
+                merge_finding(&mut results.findings, check, finding);
+                events::emit(&Event::Finding {
+                    check,
+                    rule: &finding.rule,
+                    severity: finding.severity.as_str(),
+                    path: &finding.path,
+                    message: &finding.message,
+                });
+            }
+            self.persist(&results)?;
+        }
+        Ok(LintFindings {
+            findings,
+            accepted,
+            suppressed: suppressed_count,
+        })
+    }
+
+    /// The lint findings the run has seen, accepted or not.
+    pub fn lint_findings(&self) -> Vec<baseline::Entry> {
+        self.lint_findings
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Whether a step past lint has failures that aren't allowed.
+    pub fn stage_failed(&self, stage: &str) -> bool {
+        self.snapshot()
+            .entries
+            .iter()
+            .any(|entry| entry.stage == stage && entry.failed())
+    }
+
+    /// Whether remaining tasks should be skipped.
+    pub fn should_stop(&self) -> bool {
+        self.fail_fast && self.snapshot().failed() > 0
+    }
+
+    /// Note that a task starts now, so its entry records how long it took.
+    pub fn start(&self, stage: &str, scope: &str, target: &str) {
+        self.started
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(task_key(stage, scope, target), (Instant::now(), unix_now()));
+    }
+
+    pub fn record(
+        &self,
+        stage: &str,
+        scope: &str,
+        target: &str,
+        status: &str,
+        log_path: &Path,
+    ) -> Result<()> {
+        self.record_with_metrics(stage, scope, target, status, log_path, None)
+    }
+
+    pub fn record_with_metrics(
+        &self,
+        stage: &str,
+        scope: &str,
+        target: &str,
+        status: &str,
+        log_path: &Path,
+        metrics: Option<TaskMetrics>,
+    ) -> Result<()> {
+        let started = self
+            .started
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(&task_key(stage, scope, target));
+        let mut results = self
+            .results
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Results lock poisoned"))?;
+        let mut entry = StatusEntry {
+            stage: stage.to_string(),
+            scope: scope.to_string(),
+            target: target.to_string(),
+            status: status.to_string(),
+            log_path: log_path.display().to_string(),
+            metrics,
+            started_at: started.map(|(_, at)| format_timestamp(at)),
+            duration_ms: started.map(|(clock, _)| clock.elapsed().as_millis() as u64),
+            warnings: Vec::new(),
+            allowed_failure: false,
+            known_issue: None,
+            warn_only: false,
+        };
+        // Matrix targets are `<generator>@<version>`.
+        let generator = target.split('@').next().unwrap_or(target);
+        entry.allowed_failure = matches!(
+            stage,
+            "generate" | "compile" | "test" | "smoke" | "contract"
+        ) && entry.failed_status()
+            && self.allowed_failures.iter().any(|name| name == generator);
+        if entry.failed_status() && !entry.allowed_failure {
+            entry.known_issue = self
+                .known_issues
+                .iter()
+                .find(|issue| issue.matches(stage, scope, target))
+                .map(|issue| issue.reason.clone());
+        }
+        entry.warn_only = !entry.allowed_failure
+            && entry.known_issue.is_none()
+            && entry.failed_status()
+            && self.warn_stages.iter().any(|name| name == stage);
+        events::emit(&Event::TaskFinished {
+            stage,
+            scope,
+            target,
+            status,
+            log_path: &entry.log_path,
+        });
+        results.entries.push(entry);
+        self.persist(&results)
+    }
+
+    /// Attach `warnings` to the latest entry recorded for the task.
+    pub fn record_warnings(
+        &self,
+        stage: &str,
+        scope: &str,
+        target: &str,
+        warnings: Vec<String>,
+    ) -> Result<()> {
+        if warnings.is_empty() {
+            return Ok(());
+        }
+        let mut results = self
+            .results
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Results lock poisoned"))?;
+        if let Some(entry) = results
+            .entries
+            .iter_mut()
+            .rev()
+            .find(|e| e.stage == stage && e.scope == scope && e.target == target)
+        {
+            entry.warnings = warnings;
+        }
+        self.persist(&results)
+    }
+
+    pub fn record_step(&self, step: &str, success: bool, duration_ms: u64) -> Result<()> {
+        let mut results = self
+            .results
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Results lock poisoned"))?;
+        results.steps.push(StepTiming {
+            step: step.to_string(),
+            success,
+            duration_ms,
+        });
+        self.persist(&results)
+    }
+
+    pub fn record_image(&self, image: &str, digest: &str) -> Result<()> {
+        let mut results = self
+            .results
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Results lock poisoned"))?;
+        results.images.insert(image.to_string(), digest.to_string());
+        self.persist(&results)
+    }
+
+    pub fn snapshot(&self) -> RunResults {
+        self.results
+            .lock()
+            .map(|results| results.clone())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().clone())
+    }
+
+    fn persist(&self, results: &RunResults) -> Result<()> {
+        write_atomic(&self.path, &serde_json::to_string_pretty(results)?)?;
+        write_atomic(
+            &self.root.join(OAV_DIR).join("status.tsv"),
+            &to_tsv(&results.entries),
+        )
+    }
+}
+
+fn task_key(stage: &str, scope: &str, target: &str) -> String {
+    format!("{stage}/{scope}/{target}")
+}
+
+/// Add `finding` of `check` to `findings`, as another check of a finding
+/// from a different check it duplicates, or as a new one.
+fn merge_finding(findings: &mut Vec<MergedFinding>, check: &str, finding: &Finding) {
+    let severity = finding.severity.as_str();
+    match findings
+        .iter_mut()
+        .find(|merged| !merged.checks.iter().any(|other| other == check) && merged.covers(finding))
+    {
+        Some(merged) => {
+            merged.checks.push(check.to_string());
+            if finding.severity == Severity::Error {
+                merged.severity = severity.to_string();
+            }
+        }
+        None => findings.push(MergedFinding {
+            rule: rules::canonical_rule(&finding.rule).to_string(),
+            severity: severity.to_string(),
+            path: finding.path.clone(),
+            message: finding.message.clone(),
+            checks: vec![check.to_string()],
+        }),
+    }
+}
+
+/// Load the results of the most recent run, if any.
+pub fn load_latest(root: &Path) -> Result<Option<RunResults>> {
+    let dir = root.join(OAV_DIR).join(RESULTS_DIR);
+    let Ok(run_id) = fs::read_to_string(dir.join(LATEST_FILE)) else {
+        return Ok(None);
+    };
+    load(root, run_id.trim())
+}
+
+/// Load the results of the run `run_id`, if they are still kept.
+pub fn load(root: &Path, run_id: &str) -> Result<Option<RunResults>> {
+    let path = root
+        .join(OAV_DIR)
+        .join(RESULTS_DIR)
+        .join(format!("{run_id}.json"));
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let results = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(Some(results))
+}
+
+/// IDs of the runs whose results are kept, oldest first.
+pub fn run_ids(root: &Path) -> Result<Vec<String>> {
+    let dir = root.join(OAV_DIR).join(RESULTS_DIR);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut ids: Vec<String> = fs::read_dir(&dir)
+        .context("Failed to read results directory")?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect();
+    ids.sort();
+    Ok(ids)
+}
+
+/// Legacy `stage\tscope\ttarget\tstatus\tlog_path` export.
+pub fn to_tsv(entries: &[StatusEntry]) -> String {
+    entries
+        .iter()
+        .map(|e| {
+            format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                e.stage, e.scope, e.target, e.status, e.log_path
+            )
+        })
+        .collect()
+}
+
+/// Keep only the most recent result files; run IDs sort chronologically.
+fn prune(dir: &Path) -> Result<()> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .context("Failed to read results directory")?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .collect();
+    files.sort();
+    if files.len() > KEEP_RESULTS {
+        for path in &files[..files.len() - KEEP_RESULTS] {
+            let _ = fs::remove_file(path);
+        }
+    }
+    Ok(())
+}
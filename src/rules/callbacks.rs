@@ -0,0 +1,170 @@
+use serde_yaml::Value;
+
+use super::{Finding, entries, openapi_version, operations, pointer};
+
+const RUNTIME_EXPRESSION_PREFIXES: [&str; 5] =
+    ["$url", "$method", "$statusCode", "$request.", "$response."];
+
+/// Check `webhooks` and operation/component `callbacks` definitions.
+pub fn check(doc: &Value) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    if let Some(webhooks) = doc.get("webhooks") {
+        if openapi_version(doc).is_some_and(|version| version < (3, 1)) {
+            findings.push(Finding::error(
+                "webhooks-version",
+                "#/webhooks".to_string(),
+                "webhooks require OpenAPI 3.1; generators ignore them in 3.0 documents",
+            ));
+        }
+        for (name, path_item) in entries(Some(webhooks)) {
+            let path = pointer("#/webhooks", name);
+            check_path_item(path_item, &path, "webhook", &mut findings);
+        }
+    }
+
+    for (route, path_item) in entries(doc.get("paths")) {
+        let route_path = pointer("#/paths", route);
+        for (method, operation) in operations(path_item) {
+            let op_path = pointer(&route_path, method);
+            check_callbacks(operation.get("callbacks"), &op_path, &mut findings);
+        }
+    }
+
+    if let Some(components) = doc.get("components") {
+        for (name, callback) in entries(components.get("callbacks")) {
+            let path = pointer("#/components/callbacks", name);
+            check_callback(callback, &path, &mut findings);
+        }
+    }
+
+    findings
+}
+
+/// Names of webhook and callback operations, used to check generator coverage.
+/// Prefers `operationId` and falls back to the webhook/callback name.
+pub fn event_operations(doc: &Value) -> Vec<String> {
+    let mut names = Vec::new();
+
+    for (name, path_item) in entries(doc.get("webhooks")) {
+        push_operation_names(path_item, name, &mut names);
+    }
+    for (_, path_item) in entries(doc.get("paths")) {
+        for (_, operation) in operations(path_item) {
+            for (name, callback) in entries(operation.get("callbacks")) {
+                for (_, callback_item) in entries(Some(callback)) {
+                    push_operation_names(callback_item, name, &mut names);
+                }
+            }
+        }
+    }
+
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn push_operation_names(path_item: &Value, fallback: &str, names: &mut Vec<String>) {
+    for (_, operation) in operations(path_item) {
+        let name = operation
+            .get("operationId")
+            .and_then(Value::as_str)
+            .unwrap_or(fallback);
+        names.push(name.to_string());
+    }
+}
+
+fn check_callbacks(callbacks: Option<&Value>, base: &str, findings: &mut Vec<Finding>) {
+    let base = pointer(base, "callbacks");
+    for (name, callback) in entries(callbacks) {
+        check_callback(callback, &pointer(&base, name), findings);
+    }
+}
+
+fn check_callback(callback: &Value, path: &str, findings: &mut Vec<Finding>) {
+    if callback.get("$ref").is_some() {
+        return;
+    }
+    let Some(mapping) = callback.as_mapping() else {
+        findings.push(Finding::error(
+            "callback-structure",
+            path.to_string(),
+            "callback must be a map of runtime expressions to path items",
+        ));
+        return;
+    };
+    if mapping.is_empty() {
+        findings.push(Finding::error(
+            "callback-structure",
+            path.to_string(),
+            "callback defines no expressions",
+        ));
+    }
+    for (expression, path_item) in entries(Some(callback)) {
+        let item_path = pointer(path, expression);
+        if let Err(message) = validate_expression(expression) {
+            findings.push(Finding::error(
+                "callback-expression",
+                item_path.clone(),
+                message,
+            ));
+        }
+        check_path_item(path_item, &item_path, "callback", findings);
+    }
+}
+
+fn check_path_item(path_item: &Value, path: &str, kind: &str, findings: &mut Vec<Finding>) {
+    if path_item.get("$ref").is_some() {
+        return;
+    }
+    let ops: Vec<_> = operations(path_item).collect();
+    if ops.is_empty() {
+        findings.push(Finding::error(
+            "event-operations",
+            path.to_string(),
+            format!("{kind} defines no operations"),
+        ));
+    }
+    for (method, operation) in ops {
+        let op_path = pointer(path, method);
+        if operation.get("operationId").is_none() {
+            findings.push(Finding::warning(
+                "event-operation-id",
+                op_path.clone(),
+                format!("{kind} operation has no operationId; generated names will be unstable"),
+            ));
+        }
+        if operation.get("responses").is_none() {
+            findings.push(Finding::warning(
+                "event-responses",
+                op_path,
+                format!("{kind} operation declares no responses for the receiver"),
+            ));
+        }
+    }
+}
+
+/// Validate a callback key such as `{$request.body#/callbackUrl}/events`.
+fn validate_expression(expression: &str) -> Result<(), String> {
+    let mut rest = expression;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            return Err(format!("unterminated runtime expression in '{expression}'"));
+        };
+        let inner = &after[..end];
+        if !RUNTIME_EXPRESSION_PREFIXES
+            .iter()
+            .any(|prefix| inner.starts_with(prefix))
+        {
+            return Err(format!(
+                "'{{{inner}}}' is not a runtime expression (expected $url, $method, $statusCode, $request.* or $response.*)"
+            ));
+        }
+        rest = &after[end + 1..];
+    }
+    if rest.contains('}') {
+        return Err(format!("unbalanced braces in '{expression}'"));
+    }
+    Ok(())
+}
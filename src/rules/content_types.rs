@@ -0,0 +1,227 @@
+use serde_yaml::Value;
+
+use super::{Finding, entries, follow, operations, pointer};
+
+/// Content types that generators are known to handle poorly or not at all.
+/// Parameters are ignored, so `application/xml` also covers `application/xml; charset=utf-8`.
+///
+/// Sources are the feature tables openapi-generator publishes per generator
+/// (<https://openapi-generator.tech/docs/generators>, `docs/generators/<name>.md`
+/// in its repository) and what the generated code does:
+/// - `application/xml`, `text/xml`: "Wire Format Feature" lacks `XML`, so
+///   bodies are (de)serialized as JSON regardless of the declared type.
+/// - `multipart/form-data`, `application/x-www-form-urlencoded`: "Parameter
+///   Feature" lacks `FormMultipart`/`FormUnencoded`; form fields aren't
+///   generated as parameters.
+/// - `multipart/mixed`: no feature covers it; generators that build multipart
+///   bodies only build `multipart/form-data`.
+/// - `application/x-ndjson`, `text/event-stream`: no feature covers them;
+///   generated clients read a response body as one document, so the stream
+///   is buffered to the end and then fails to parse as JSON.
+const UNSUPPORTED: [(&str, &[&str]); 7] = [
+    (
+        "application/xml",
+        &[
+            "typescript-axios",
+            "typescript-fetch",
+            "typescript-node",
+            "typescript-nestjs",
+            "python-fastapi",
+            "go-server",
+        ],
+    ),
+    (
+        "text/xml",
+        &[
+            "typescript-axios",
+            "typescript-fetch",
+            "typescript-node",
+            "typescript-nestjs",
+            "python-fastapi",
+            "go-server",
+        ],
+    ),
+    ("multipart/form-data", &["typescript-nestjs", "go-server"]),
+    (
+        "multipart/mixed",
+        &[
+            "typescript-axios",
+            "typescript-fetch",
+            "typescript-node",
+            "typescript-nestjs",
+            "python-fastapi",
+            "go-server",
+            "go",
+            "csharp",
+        ],
+    ),
+    ("application/x-www-form-urlencoded", &["typescript-nestjs"]),
+    (
+        "application/x-ndjson",
+        &[
+            "typescript-axios",
+            "typescript-fetch",
+            "typescript-node",
+            "java",
+            "kotlin",
+            "csharp",
+        ],
+    ),
+    (
+        "text/event-stream",
+        &[
+            "typescript-axios",
+            "typescript-fetch",
+            "typescript-node",
+            "java",
+            "kotlin",
+            "csharp",
+            "go",
+            "python",
+        ],
+    ),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Request,
+    Response,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Request => "request",
+            Direction::Response => "response",
+        }
+    }
+}
+
+/// One content type declared by an operation.
+#[derive(Debug, Clone)]
+pub struct ContentTypeUse {
+    /// `METHOD /path`, e.g. `POST /pets`.
+    pub operation: String,
+    pub direction: Direction,
+    pub content_type: String,
+    pub path: String,
+}
+
+/// Collect every request/response content type declared per operation.
+pub fn matrix(doc: &Value) -> Vec<ContentTypeUse> {
+    let mut uses = Vec::new();
+    for (route, path_item) in entries(doc.get("paths")) {
+        let route_path = pointer("#/paths", route);
+        for (method, operation) in operations(path_item) {
+            let label = format!("{} {route}", method.to_uppercase());
+            let op_path = pointer(&route_path, method);
+
+            if let Some(body) = operation.get("requestBody") {
+                let body = follow(doc, body);
+                let body_path = pointer(&op_path, "requestBody");
+                push_content(body, &label, Direction::Request, &body_path, &mut uses);
+            }
+            for (status, response) in entries(operation.get("responses")) {
+                let response = follow(doc, response);
+                let response_path = pointer(&pointer(&op_path, "responses"), status);
+                push_content(
+                    response,
+                    &label,
+                    Direction::Response,
+                    &response_path,
+                    &mut uses,
+                );
+            }
+        }
+    }
+    uses
+}
+
+/// Flag content types outside `allowed` (when non-empty) and content types that
+/// one of the active generators is known not to support.
+pub fn check(doc: &Value, allowed: &[String], generators: &[String]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for entry in matrix(doc) {
+        if !is_allowed(&entry.content_type, allowed) {
+            findings.push(Finding::error(
+                "content-type-allowed",
+                entry.path.clone(),
+                format!(
+                    "{} {} uses {}, which is not in allowed_content_types",
+                    entry.operation,
+                    entry.direction.as_str(),
+                    entry.content_type
+                ),
+            ));
+        }
+        let unsupported = unsupported_by(&entry.content_type, generators);
+        if !unsupported.is_empty() {
+            findings.push(Finding::warning(
+                "content-type-generator",
+                entry.path,
+                format!(
+                    "{} is not supported by: {}",
+                    entry.content_type,
+                    unsupported.join(", ")
+                ),
+            ));
+        }
+    }
+    findings
+}
+
+pub fn is_allowed(content_type: &str, allowed: &[String]) -> bool {
+    allowed.is_empty()
+        || allowed
+            .iter()
+            .any(|candidate| media_type(candidate) == media_type(content_type))
+}
+
+pub fn unsupported_by<'a>(content_type: &str, generators: &'a [String]) -> Vec<&'a str> {
+    let media = media_type(content_type);
+    UNSUPPORTED
+        .iter()
+        .filter(|(known, _)| *known == media)
+        .flat_map(|(_, names)| names.iter())
+        .filter_map(|name| {
+            generators
+                .iter()
+                .find(|generator| generator.as_str() == *name)
+                .map(String::as_str)
+        })
+        .collect()
+}
+
+fn media_type(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase()
+}
+
+fn push_content(
+    holder: &Value,
+    label: &str,
+    direction: Direction,
+    base: &str,
+    uses: &mut Vec<ContentTypeUse>,
+) {
+    let content_path = pointer(base, "content");
+    for (content_type, _) in entries(holder.get("content")) {
+        if uses.iter().any(|existing| {
+            existing.operation == label
+                && existing.direction == direction
+                && existing.content_type == content_type
+        }) {
+            continue;
+        }
+        uses.push(ContentTypeUse {
+            operation: label.to_string(),
+            direction,
+            content_type: content_type.to_string(),
+            path: pointer(&content_path, content_type),
+        });
+    }
+}
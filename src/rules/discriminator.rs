@@ -0,0 +1,195 @@
+use serde_yaml::Value;
+use std::collections::BTreeSet;
+
+use super::{Finding, entries, pointer, resolve_ref};
+
+/// Check `discriminator` mappings and `oneOf`/`anyOf` branch distinguishability.
+pub fn check(doc: &Value) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    walk(doc, doc, "#", &mut findings);
+    findings
+}
+
+fn walk(doc: &Value, value: &Value, path: &str, findings: &mut Vec<Finding>) {
+    match value {
+        Value::Mapping(_) => {
+            if value.get("discriminator").is_some_and(Value::is_mapping) {
+                check_discriminator(doc, value, path, findings);
+            } else {
+                for keyword in ["oneOf", "anyOf"] {
+                    if let Some(branches) = value.get(keyword).and_then(Value::as_sequence) {
+                        check_branches(doc, branches, &pointer(path, keyword), findings);
+                    }
+                }
+            }
+            for (key, child) in entries(Some(value)) {
+                if matches!(key, "example" | "examples" | "enum" | "const" | "default") {
+                    continue;
+                }
+                let child_path = pointer(path, key);
+                // Keys of these maps are names, so a property called
+                // `discriminator` or `oneOf` is not a keyword.
+                if matches!(key, "properties" | "patternProperties" | "schemas") {
+                    for (name, schema) in entries(Some(child)) {
+                        walk(doc, schema, &pointer(&child_path, name), findings);
+                    }
+                    continue;
+                }
+                walk(doc, child, &child_path, findings);
+            }
+        }
+        Value::Sequence(items) => {
+            for (idx, child) in items.iter().enumerate() {
+                walk(doc, child, &pointer(path, &idx.to_string()), findings);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_discriminator(doc: &Value, schema: &Value, path: &str, findings: &mut Vec<Finding>) {
+    let disc_path = pointer(path, "discriminator");
+    let discriminator = &schema["discriminator"];
+    let Some(property) = discriminator.get("propertyName").and_then(Value::as_str) else {
+        findings.push(Finding::error(
+            "discriminator-property",
+            disc_path,
+            "discriminator has no propertyName",
+        ));
+        return;
+    };
+
+    let branches: Vec<&str> = ["oneOf", "anyOf"]
+        .iter()
+        .filter_map(|keyword| schema.get(*keyword).and_then(Value::as_sequence))
+        .flatten()
+        .filter_map(|branch| branch.get("$ref").and_then(Value::as_str))
+        .collect();
+
+    let Some(mapping) = discriminator.get("mapping") else {
+        findings.push(Finding::error(
+            "discriminator-mapping",
+            disc_path,
+            "discriminator has no mapping; generators fall back to schema names, which rarely match payload values",
+        ));
+        return;
+    };
+
+    let mut mapped = BTreeSet::new();
+    for (value, target) in entries(Some(mapping)) {
+        let entry_path = pointer(&pointer(&disc_path, "mapping"), value);
+        let Some(reference) = target.as_str() else {
+            findings.push(Finding::error(
+                "discriminator-mapping",
+                entry_path,
+                "mapping target must be a schema reference",
+            ));
+            continue;
+        };
+        let reference = normalize_ref(reference);
+        mapped.insert(reference.clone());
+
+        let Some(target_schema) = resolve_ref(doc, &reference) else {
+            findings.push(Finding::error(
+                "discriminator-target",
+                entry_path,
+                format!("mapped schema {reference} does not exist"),
+            ));
+            continue;
+        };
+        if !collect_properties(doc, target_schema, 0).contains(property) {
+            findings.push(Finding::error(
+                "discriminator-target",
+                entry_path,
+                format!("mapped schema {reference} does not define the discriminator property '{property}'"),
+            ));
+        }
+    }
+
+    for branch in branches {
+        if !mapped.contains(branch) {
+            findings.push(Finding::error(
+                "discriminator-mapping",
+                disc_path.clone(),
+                format!("{branch} is a oneOf/anyOf branch but is missing from the mapping"),
+            ));
+        }
+    }
+}
+
+/// Without a discriminator, generators must tell branches apart by shape alone.
+fn check_branches(doc: &Value, branches: &[Value], path: &str, findings: &mut Vec<Finding>) {
+    let mut seen_refs = BTreeSet::new();
+    let mut shapes: Vec<(usize, Option<&str>, BTreeSet<String>)> = Vec::new();
+
+    for (idx, branch) in branches.iter().enumerate() {
+        let branch_path = pointer(path, &idx.to_string());
+        let resolved = match branch.get("$ref").and_then(Value::as_str) {
+            Some(reference) => {
+                if !seen_refs.insert(reference) {
+                    findings.push(Finding::error(
+                        "polymorphism-duplicate",
+                        branch_path,
+                        format!("{reference} appears more than once"),
+                    ));
+                    continue;
+                }
+                match resolve_ref(doc, reference) {
+                    Some(schema) => schema,
+                    None => continue,
+                }
+            }
+            None => branch,
+        };
+
+        let kind = resolved.get("type").and_then(Value::as_str);
+        let properties = collect_properties(doc, resolved, 0);
+        if let Some((other, _, _)) = shapes
+            .iter()
+            .find(|(_, other_kind, other_props)| *other_kind == kind && *other_props == properties)
+        {
+            findings.push(Finding::warning(
+                "polymorphism-ambiguous",
+                branch_path,
+                format!(
+                    "branch {idx} has the same type and properties as branch {other}; add a discriminator or distinguishing properties"
+                ),
+            ));
+        }
+        shapes.push((idx, kind, properties));
+    }
+}
+
+/// Property names of a schema, following `allOf` and local `$ref`s.
+fn collect_properties(doc: &Value, schema: &Value, depth: usize) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    if depth > 16 {
+        return names;
+    }
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str)
+        && let Some(target) = resolve_ref(doc, reference)
+    {
+        names.extend(collect_properties(doc, target, depth + 1));
+    }
+    for (name, _) in entries(schema.get("properties")) {
+        names.insert(name.to_string());
+    }
+    for part in schema
+        .get("allOf")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+    {
+        names.extend(collect_properties(doc, part, depth + 1));
+    }
+    names
+}
+
+/// Mapping values may be bare schema names instead of references.
+fn normalize_ref(reference: &str) -> String {
+    if reference.contains('#') || reference.contains('/') {
+        reference.to_string()
+    } else {
+        format!("#/components/schemas/{reference}")
+    }
+}
@@ -0,0 +1,481 @@
+use serde_yaml::Value;
+
+use super::{Finding, entries, follow, operations, pointer, resolve_ref, responses};
+
+/// Deeper than any sensible schema; stops `$ref` cycles that consume no input.
+const MAX_DEPTH: usize = 64;
+
+/// Check `example` and `examples` values against their schemas: on
+/// parameters, headers, and media types of each operation, and on schemas.
+/// `pattern` and `format` are not checked.
+pub fn check(doc: &Value) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for (route, path_item) in entries(doc.get("paths")) {
+        let route_path = pointer("#/paths", route);
+        for (method, operation) in operations(path_item) {
+            let label = format!("{} {route}", method.to_uppercase());
+            let op_path = pointer(&route_path, method);
+
+            let parameters = [
+                (path_item, route_path.as_str()),
+                (operation, op_path.as_str()),
+            ];
+            for (holder, base) in parameters {
+                let params_path = pointer(base, "parameters");
+                for (idx, parameter) in holder
+                    .get("parameters")
+                    .and_then(Value::as_sequence)
+                    .into_iter()
+                    .flatten()
+                    .enumerate()
+                {
+                    let parameter = follow(doc, parameter);
+                    let name = parameter.get("name").and_then(Value::as_str).unwrap_or("?");
+                    check_holder(
+                        doc,
+                        parameter,
+                        &pointer(&params_path, &idx.to_string()),
+                        &format!("{label} parameter {name}"),
+                        &mut findings,
+                    );
+                }
+            }
+
+            if let Some(body) = operation.get("requestBody") {
+                check_content(
+                    doc,
+                    follow(doc, body),
+                    &pointer(&op_path, "requestBody"),
+                    &format!("{label} request"),
+                    &mut findings,
+                );
+            }
+            for (status, response) in responses(operation) {
+                let response = follow(doc, response);
+                let response_path = pointer(&pointer(&op_path, "responses"), &status);
+                let response_label = format!("{label} response {status}");
+                check_content(
+                    doc,
+                    response,
+                    &response_path,
+                    &response_label,
+                    &mut findings,
+                );
+                for (name, header) in entries(response.get("headers")) {
+                    check_holder(
+                        doc,
+                        follow(doc, header),
+                        &pointer(&pointer(&response_path, "headers"), name),
+                        &format!("{response_label} header {name}"),
+                        &mut findings,
+                    );
+                }
+            }
+        }
+    }
+
+    for (name, schema) in entries(doc.get("components").and_then(|c| c.get("schemas"))) {
+        check_schema(
+            doc,
+            schema,
+            &pointer("#/components/schemas", name),
+            &format!("schema {name}"),
+            &mut findings,
+        );
+    }
+
+    findings
+}
+
+fn check_content(
+    doc: &Value,
+    holder: &Value,
+    base: &str,
+    label: &str,
+    findings: &mut Vec<Finding>,
+) {
+    let content_path = pointer(base, "content");
+    for (content_type, media) in entries(holder.get("content")) {
+        check_holder(
+            doc,
+            media,
+            &pointer(&content_path, content_type),
+            &format!("{label} {content_type}"),
+            findings,
+        );
+    }
+}
+
+/// Check the examples of a parameter, header, or media type against its
+/// `schema`, then the examples inside an inline schema.
+fn check_holder(doc: &Value, holder: &Value, path: &str, label: &str, findings: &mut Vec<Finding>) {
+    let Some(schema) = holder.get("schema") else {
+        check_content(doc, holder, path, label, findings);
+        return;
+    };
+    if let Some(example) = holder.get("example") {
+        report(
+            doc,
+            schema,
+            example,
+            &pointer(path, "example"),
+            label,
+            findings,
+        );
+    }
+    for (name, example) in entries(holder.get("examples")) {
+        let example = follow(doc, example);
+        if let Some(value) = example.get("value") {
+            report(
+                doc,
+                schema,
+                value,
+                &pointer(&pointer(path, "examples"), name),
+                &format!("{label} example {name}"),
+                findings,
+            );
+        }
+    }
+    if schema.get("$ref").is_none() {
+        check_schema(doc, schema, &pointer(path, "schema"), label, findings);
+    }
+}
+
+/// Check a schema's own `example` (and 3.1 `examples` list), then those of
+/// its inline subschemas. Referenced schemas are checked under components.
+fn check_schema(doc: &Value, schema: &Value, path: &str, label: &str, findings: &mut Vec<Finding>) {
+    if let Some(example) = schema.get("example") {
+        report(
+            doc,
+            schema,
+            example,
+            &pointer(path, "example"),
+            label,
+            findings,
+        );
+    }
+    for (idx, example) in schema
+        .get("examples")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let example_path = pointer(&pointer(path, "examples"), &idx.to_string());
+        report(doc, schema, example, &example_path, label, findings);
+    }
+
+    let mut children: Vec<(String, &Value)> = entries(schema.get("properties"))
+        .map(|(name, child)| (pointer(&pointer(path, "properties"), name), child))
+        .collect();
+    for keyword in ["items", "additionalProperties", "not"] {
+        if let Some(child) = schema.get(keyword).filter(|child| child.is_mapping()) {
+            children.push((pointer(path, keyword), child));
+        }
+    }
+    for keyword in ["allOf", "oneOf", "anyOf"] {
+        for (idx, child) in schema
+            .get(keyword)
+            .and_then(Value::as_sequence)
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            children.push((pointer(&pointer(path, keyword), &idx.to_string()), child));
+        }
+    }
+    for (child_path, child) in children {
+        if child.get("$ref").is_none() {
+            check_schema(doc, child, &child_path, label, findings);
+        }
+    }
+}
+
+fn report(
+    doc: &Value,
+    schema: &Value,
+    example: &Value,
+    path: &str,
+    label: &str,
+    findings: &mut Vec<Finding>,
+) {
+    let mut errors = Vec::new();
+    validate(doc, schema, example, "", 0, &mut errors);
+    for error in errors {
+        findings.push(Finding::error(
+            "example-schema",
+            path.to_string(),
+            format!("{label}: example does not match its schema: {error}"),
+        ));
+    }
+}
+
+/// Collect the ways `value` breaks `schema`, each prefixed with the JSON
+/// pointer of the offending part of the example (`/` for the whole of it).
+fn validate(
+    doc: &Value,
+    schema: &Value,
+    value: &Value,
+    at: &str,
+    depth: usize,
+    errors: &mut Vec<String>,
+) {
+    if depth > MAX_DEPTH || !schema.is_mapping() {
+        return;
+    }
+    let location = if at.is_empty() { "/" } else { at };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        // External references are left to the linter.
+        if let Some(target) = resolve_ref(doc, reference) {
+            validate(doc, target, value, at, depth + 1, errors);
+        }
+        return;
+    }
+
+    if value.is_null() && schema.get("nullable").and_then(Value::as_bool) == Some(true) {
+        return;
+    }
+    if let Some(types) = schema_types(schema)
+        && !types.iter().any(|kind| is_type(value, kind))
+    {
+        errors.push(format!(
+            "{location}: expected {}, got {}",
+            types.join(" or "),
+            type_name(value)
+        ));
+        return;
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_sequence)
+        && !allowed.iter().any(|candidate| same(candidate, value))
+    {
+        errors.push(format!(
+            "{location}: {} is not one of the enum values",
+            render(value)
+        ));
+    }
+    if let Some(expected) = schema.get("const")
+        && !same(expected, value)
+    {
+        errors.push(format!("{location}: expected {}", render(expected)));
+    }
+
+    for part in schema
+        .get("allOf")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+    {
+        validate(doc, part, value, at, depth + 1, errors);
+    }
+    // Without a discriminator, overlapping branches can legitimately match
+    // more than once, so `oneOf` is checked like `anyOf`.
+    for keyword in ["oneOf", "anyOf"] {
+        if let Some(branches) = schema.get(keyword).and_then(Value::as_sequence)
+            && !branches.iter().any(|branch| {
+                let mut branch_errors = Vec::new();
+                validate(doc, branch, value, at, depth + 1, &mut branch_errors);
+                branch_errors.is_empty()
+            })
+        {
+            errors.push(format!("{location}: matches none of the {keyword} schemas"));
+        }
+    }
+
+    match value {
+        Value::Mapping(_) => validate_object(doc, schema, value, at, depth, errors),
+        Value::Sequence(items) => {
+            check_bound(schema, "minItems", items.len(), location, "items", errors);
+            check_bound(schema, "maxItems", items.len(), location, "items", errors);
+            if let Some(item_schema) = schema.get("items") {
+                for (idx, item) in items.iter().enumerate() {
+                    let item_at = pointer(at, &idx.to_string());
+                    validate(doc, item_schema, item, &item_at, depth + 1, errors);
+                }
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count();
+            check_bound(schema, "minLength", length, location, "characters", errors);
+            check_bound(schema, "maxLength", length, location, "characters", errors);
+        }
+        Value::Number(number) => {
+            if let Some(number) = number.as_f64() {
+                check_range(schema, number, location, errors);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn validate_object(
+    doc: &Value,
+    schema: &Value,
+    value: &Value,
+    at: &str,
+    depth: usize,
+    errors: &mut Vec<String>,
+) {
+    let location = if at.is_empty() { "/" } else { at };
+    let properties = schema.get("properties");
+    for name in schema
+        .get("required")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+    {
+        // Read-only properties are absent from requests and write-only ones
+        // from responses, so neither is required in an example.
+        let property = properties.and_then(|properties| properties.get(name));
+        let one_way = property.is_some_and(|property| {
+            let property = follow(doc, property);
+            ["readOnly", "writeOnly"]
+                .iter()
+                .any(|flag| property.get(*flag).and_then(Value::as_bool) == Some(true))
+        });
+        if value.get(name).is_none() && !one_way {
+            errors.push(format!("{location}: missing required property '{name}'"));
+        }
+    }
+    for (name, child) in entries(Some(value)) {
+        let child_at = pointer(at, name);
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(property) => validate(doc, property, child, &child_at, depth + 1, errors),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    errors.push(format!("{location}: unexpected property '{name}'"));
+                }
+                Some(additional) if additional.is_mapping() => {
+                    validate(doc, additional, child, &child_at, depth + 1, errors);
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+/// The types a schema allows: `type` as a string or (3.1) a list, with
+/// `nullable: true` adding `null`. `None` when unconstrained.
+fn schema_types(schema: &Value) -> Option<Vec<String>> {
+    let mut types: Vec<String> = match schema.get("type")? {
+        Value::String(kind) => vec![kind.clone()],
+        Value::Sequence(kinds) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => return None,
+    };
+    if schema.get("nullable").and_then(Value::as_bool) == Some(true) {
+        types.push("null".to_string());
+    }
+    Some(types)
+}
+
+fn is_type(value: &Value, kind: &str) -> bool {
+    match kind {
+        "string" => value.is_string(),
+        "boolean" => value.is_bool(),
+        "object" => value.is_mapping(),
+        "array" => value.is_sequence(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => match value {
+            Value::Number(number) => {
+                number.is_i64()
+                    || number.is_u64()
+                    || number.as_f64().is_some_and(|number| number.fract() == 0.0)
+            }
+            _ => false,
+        },
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Sequence(_) => "array",
+        Value::Mapping(_) => "object",
+        Value::Tagged(_) => "tagged value",
+    }
+}
+
+/// Equality that treats `1` and `1.0` as the same number.
+fn same(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64() == right.as_f64(),
+        _ => left == right,
+    }
+}
+
+fn render(value: &Value) -> String {
+    serde_yaml::to_string(value)
+        .map(|text| text.trim_end().to_string())
+        .unwrap_or_else(|_| type_name(value).to_string())
+}
+
+fn check_bound(
+    schema: &Value,
+    keyword: &str,
+    actual: usize,
+    location: &str,
+    unit: &str,
+    errors: &mut Vec<String>,
+) {
+    let Some(limit) = schema.get(keyword).and_then(Value::as_u64) else {
+        return;
+    };
+    let actual = actual as u64;
+    let broken = if keyword.starts_with("min") {
+        actual < limit
+    } else {
+        actual > limit
+    };
+    if broken {
+        errors.push(format!(
+            "{location}: has {actual} {unit}, {keyword} is {limit}"
+        ));
+    }
+}
+
+/// `minimum`/`maximum`, with 3.0 boolean and 3.1 numeric `exclusive*`.
+fn check_range(schema: &Value, number: f64, location: &str, errors: &mut Vec<String>) {
+    let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+    let exclusive = |keyword: &str| schema.get(keyword).and_then(Value::as_bool) == Some(true);
+
+    if let Some(minimum) = bound("minimum") {
+        if exclusive("exclusiveMinimum") && number <= minimum {
+            errors.push(format!("{location}: {number} is not above {minimum}"));
+        } else if number < minimum {
+            errors.push(format!(
+                "{location}: {number} is below the minimum {minimum}"
+            ));
+        }
+    }
+    if let Some(minimum) = bound("exclusiveMinimum")
+        && number <= minimum
+    {
+        errors.push(format!("{location}: {number} is not above {minimum}"));
+    }
+    if let Some(maximum) = bound("maximum") {
+        if exclusive("exclusiveMaximum") && number >= maximum {
+            errors.push(format!("{location}: {number} is not below {maximum}"));
+        } else if number > maximum {
+            errors.push(format!(
+                "{location}: {number} is above the maximum {maximum}"
+            ));
+        }
+    }
+    if let Some(maximum) = bound("exclusiveMaximum")
+        && number >= maximum
+    {
+        errors.push(format!("{location}: {number} is not below {maximum}"));
+    }
+}
@@ -0,0 +1,147 @@
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeSet;
+
+use super::{HTTP_METHODS, follow, pointer};
+
+/// Apply the fixes that can't change what the API means: a missing leading
+/// `/` on a path, `required: true` on path parameters, and a string path
+/// parameter for each `{name}` in a route that declares none. Returns one
+/// line per fix.
+pub fn apply(doc: &mut Value) -> Vec<String> {
+    let mut fixes = Vec::new();
+    let snapshot = doc.clone();
+    let Some(paths) = doc.get_mut("paths").and_then(Value::as_mapping_mut) else {
+        return fixes;
+    };
+
+    let renames: Vec<(Value, String)> = paths
+        .keys()
+        .filter_map(|key| {
+            let route = key.as_str()?;
+            (!route.starts_with('/')).then(|| (key.clone(), format!("/{route}")))
+        })
+        .filter(|(_, fixed)| !paths.contains_key(fixed.as_str()))
+        .collect();
+    for (key, fixed) in renames {
+        let route = key.as_str().unwrap_or_default().to_string();
+        // Rebuild the mapping to keep the path in its place.
+        *paths = std::mem::take(paths)
+            .into_iter()
+            .map(|(name, item)| {
+                if name == key {
+                    (Value::String(fixed.clone()), item)
+                } else {
+                    (name, item)
+                }
+            })
+            .collect();
+        fixes.push(format!(
+            "path-key {}: renamed to {fixed}",
+            pointer("#/paths", &route)
+        ));
+    }
+
+    for (route, item) in paths.iter_mut() {
+        let Some(route) = route.as_str() else {
+            continue;
+        };
+        let route_path = pointer("#/paths", route);
+        let Some(item) = item.as_mapping_mut() else {
+            continue;
+        };
+        if item.contains_key("$ref") {
+            continue;
+        }
+
+        let mut declared = BTreeSet::new();
+        require_path_parameters(item, &snapshot, &route_path, &mut declared, &mut fixes);
+        for method in HTTP_METHODS {
+            if let Some(operation) = item.get_mut(method).and_then(Value::as_mapping_mut) {
+                let mut in_operation = BTreeSet::new();
+                require_path_parameters(
+                    operation,
+                    &snapshot,
+                    &pointer(&route_path, method),
+                    &mut in_operation,
+                    &mut fixes,
+                );
+                declared.extend(in_operation);
+            }
+        }
+
+        let missing: Vec<&str> = route
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .filter(|name| !declared.contains(*name))
+            .collect();
+        if missing.is_empty() {
+            continue;
+        }
+        let parameters = item
+            .entry("parameters".into())
+            .or_insert_with(|| Value::Sequence(Vec::new()));
+        let Some(parameters) = parameters.as_sequence_mut() else {
+            continue;
+        };
+        for name in missing {
+            parameters.push(path_parameter(name));
+            fixes.push(format!(
+                "path-parameters {route_path}: added string path parameter {name}"
+            ));
+        }
+    }
+    fixes
+}
+
+/// Set `required: true` on the inline path parameters of `holder`, adding
+/// every path parameter's name to `declared`.
+fn require_path_parameters(
+    holder: &mut Mapping,
+    doc: &Value,
+    holder_path: &str,
+    declared: &mut BTreeSet<String>,
+    fixes: &mut Vec<String>,
+) {
+    let Some(parameters) = holder
+        .get_mut("parameters")
+        .and_then(Value::as_sequence_mut)
+    else {
+        return;
+    };
+    for (idx, parameter) in parameters.iter_mut().enumerate() {
+        let target = follow(doc, parameter);
+        if target.get("in").and_then(Value::as_str) != Some("path") {
+            continue;
+        }
+        let Some(name) = target
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        declared.insert(name.clone());
+        if let Some(parameter) = parameter.as_mapping_mut()
+            && !parameter.contains_key("$ref")
+            && parameter.get("required").and_then(Value::as_bool) != Some(true)
+        {
+            parameter.insert("required".into(), true.into());
+            fixes.push(format!(
+                "parameter {}: path parameter {name} set to required: true",
+                pointer(&pointer(holder_path, "parameters"), &idx.to_string())
+            ));
+        }
+    }
+}
+
+fn path_parameter(name: &str) -> Value {
+    let mut schema = Mapping::new();
+    schema.insert("type".into(), "string".into());
+    let mut parameter = Mapping::new();
+    parameter.insert("name".into(), name.into());
+    parameter.insert("in".into(), "path".into());
+    parameter.insert("required".into(), true.into());
+    parameter.insert("schema".into(), Value::Mapping(schema));
+    Value::Mapping(parameter)
+}
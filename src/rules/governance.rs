@@ -0,0 +1,109 @@
+use serde_yaml::Value;
+use std::collections::HashMap;
+
+use super::structure::duplicate_operation_ids;
+use super::{Finding, Severity, entries, follow, operations, pointer, responses};
+
+/// API design rules enabled in `.oavc` (`rules.<rule>: warn|error`), checked
+/// per operation. `pagination_params` are the query parameters that count as
+/// paging a list endpoint.
+pub fn check(
+    doc: &Value,
+    levels: &HashMap<&str, Severity>,
+    pagination_params: &[String],
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut push = |rule: &str, path: String, message: String| {
+        if let Some(severity) = levels.get(rule) {
+            findings.push(Finding {
+                rule: rule.to_string(),
+                severity: *severity,
+                path,
+                message,
+            });
+        }
+    };
+
+    for (path, message) in duplicate_operation_ids(doc) {
+        push("operation-id-unique", path, message);
+    }
+
+    for (route, path_item) in entries(doc.get("paths")) {
+        let route_path = pointer("#/paths", route);
+        let path_item = follow(doc, path_item);
+        for (method, operation) in operations(path_item) {
+            let label = format!("{} {route}", method.to_uppercase());
+            let op_path = pointer(&route_path, method);
+
+            let tagged = operation
+                .get("tags")
+                .and_then(Value::as_sequence)
+                .is_some_and(|tags| !tags.is_empty());
+            if !tagged {
+                push(
+                    "operation-tags",
+                    op_path.clone(),
+                    format!("{label} has no tags"),
+                );
+            }
+
+            let described = operation
+                .get("description")
+                .and_then(Value::as_str)
+                .is_some_and(|description| !description.trim().is_empty());
+            if !described {
+                push(
+                    "operation-description",
+                    op_path.clone(),
+                    format!("{label} has no description"),
+                );
+            }
+
+            if !responses(operation)
+                .any(|(status, _)| status.starts_with('4') || status == "default")
+            {
+                push(
+                    "error-responses",
+                    pointer(&op_path, "responses"),
+                    format!("{label} declares no 4xx (or default) response"),
+                );
+            }
+
+            if method == "get" && returns_list(doc, operation) {
+                let paged = path_item
+                    .get("parameters")
+                    .into_iter()
+                    .chain(operation.get("parameters"))
+                    .filter_map(Value::as_sequence)
+                    .flatten()
+                    .map(|parameter| follow(doc, parameter))
+                    .filter(|parameter| {
+                        parameter.get("in").and_then(Value::as_str) == Some("query")
+                    })
+                    .filter_map(|parameter| parameter.get("name").and_then(Value::as_str))
+                    .any(|name| pagination_params.iter().any(|param| param == name));
+                if !paged {
+                    push(
+                        "pagination",
+                        op_path.clone(),
+                        format!(
+                            "{label} returns a list but takes none of the pagination parameters {}",
+                            pagination_params.join(", ")
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    findings
+}
+
+/// Whether the operation's success response is a JSON array.
+fn returns_list(doc: &Value, operation: &Value) -> bool {
+    responses(operation)
+        .filter(|(status, _)| status.starts_with('2'))
+        .flat_map(|(_, response)| entries(follow(doc, response).get("content")))
+        .filter_map(|(_, media)| media.get("schema"))
+        .any(|schema| follow(doc, schema).get("type").and_then(Value::as_str) == Some("array"))
+}
@@ -0,0 +1,95 @@
+use super::{is_block_indicator, pointer, split_key};
+
+/// Where a node of a YAML document starts.
+#[derive(Debug, Clone)]
+pub struct NodeStart {
+    /// 1-based line.
+    pub line: usize,
+    /// 1-based column of the key, or of the `-` of a sequence item.
+    pub column: usize,
+    pub pointer: String,
+}
+
+/// The start of every mapping key and block sequence item in YAML `content`,
+/// in file order, found from indentation alone. Flow collections are one node.
+pub fn node_starts(content: &str) -> Vec<NodeStart> {
+    let mut starts = Vec::new();
+    // Open nodes as (column, segment, whether it is a sequence item).
+    let mut nodes: Vec<(usize, String, bool)> = Vec::new();
+    let mut block_indent: Option<usize> = None;
+    for (idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if trimmed.is_empty() {
+            continue;
+        }
+        match block_indent {
+            Some(block) if indent > block => continue,
+            _ => block_indent = None,
+        }
+        if trimmed.starts_with('#') {
+            continue;
+        }
+        if trimmed.starts_with("---") || trimmed.starts_with("...") {
+            nodes.clear();
+            continue;
+        }
+
+        let mut column = indent;
+        let mut content = trimmed;
+        while content == "-" || content.starts_with("- ") {
+            nodes.retain(|(open, _, _)| *open <= column);
+            let index = match nodes.last() {
+                Some((open, index, true)) if *open == column => {
+                    index.parse::<usize>().unwrap_or(0) + 1
+                }
+                _ => 0,
+            };
+            nodes.retain(|(open, _, item)| !(*open == column && *item));
+            nodes.push((column, index.to_string(), true));
+            starts.push(NodeStart {
+                line: idx + 1,
+                column: column + 1,
+                pointer: node_pointer(&nodes),
+            });
+            let rest = content[1..].trim_start();
+            column += content.len() - rest.len();
+            content = rest;
+        }
+        let value = match split_key(content) {
+            Some((key, value)) => {
+                nodes.retain(|(open, _, _)| *open < column);
+                nodes.push((column, key, false));
+                starts.push(NodeStart {
+                    line: idx + 1,
+                    column: column + 1,
+                    pointer: node_pointer(&nodes),
+                });
+                value
+            }
+            None => content,
+        };
+        if is_block_indicator(value) {
+            block_indent = Some(indent);
+        }
+    }
+    starts
+}
+
+/// `(line, column)` of the node at `pointer`, or of its closest ancestor
+/// in `starts`.
+pub fn locate(starts: &[NodeStart], pointer: &str) -> Option<(usize, usize)> {
+    let mut pointer = pointer;
+    loop {
+        if let Some(start) = starts.iter().find(|start| start.pointer == pointer) {
+            return Some((start.line, start.column));
+        }
+        pointer = &pointer[..pointer.rfind('/')?];
+    }
+}
+
+fn node_pointer(nodes: &[(usize, String, bool)]) -> String {
+    nodes.iter().fold("#".to_string(), |base, (_, segment, _)| {
+        pointer(&base, segment)
+    })
+}
@@ -0,0 +1,191 @@
+mod callbacks;
+mod content_types;
+mod discriminator;
+mod examples;
+mod fix;
+mod governance;
+mod locate;
+mod naming;
+mod security;
+mod structure;
+mod yaml_style;
+
+pub use callbacks::{check as check_callbacks, event_operations};
+pub use content_types::{
+    check as check_content_types, is_allowed as is_content_type_allowed,
+    matrix as content_type_matrix, unsupported_by,
+};
+pub use discriminator::check as check_discriminators;
+pub use examples::check as check_examples;
+pub use fix::apply as fix_document;
+pub use governance::check as check_governance;
+pub use locate::{locate, node_starts};
+pub use naming::check as check_naming;
+pub use security::check as check_security;
+pub use structure::check as check_structure;
+pub use yaml_style::check as check_yaml_style;
+use yaml_style::{is_block_indicator, split_key};
+
+use anyhow::{Context, Result};
+use serde_yaml::Value;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+pub const HTTP_METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Rules of other engines, mostly Redocly, that check the same thing as a
+/// native rule, mapped to that rule.
+const EQUIVALENT_RULES: [(&str, &str); 11] = [
+    ("operation-operationId-unique", "operation-id-unique"),
+    ("operation-4xx-response", "error-responses"),
+    ("operation-2xx-response", "operation-responses"),
+    ("path-parameters-defined", "path-parameters"),
+    ("no-unresolved-refs", "ref-resolve"),
+    ("no-invalid-media-type-examples", "example-schema"),
+    ("no-invalid-schema-examples", "example-schema"),
+    ("no-invalid-parameter-examples", "example-schema"),
+    ("spec-components-invalid-map-name", "component-name"),
+    ("security-defined", "owasp-auth"),
+    ("paths-kebab-case", "naming-paths"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub rule: String,
+    pub severity: Severity,
+    /// JSON pointer into the spec, e.g. `#/paths/~1pets/post`.
+    pub path: String,
+    pub message: String,
+}
+
+impl Finding {
+    pub fn error(rule: &str, path: String, message: impl Into<String>) -> Self {
+        Self {
+            rule: rule.to_string(),
+            severity: Severity::Error,
+            path,
+            message: message.into(),
+        }
+    }
+
+    pub fn warning(rule: &str, path: String, message: impl Into<String>) -> Self {
+        Self {
+            rule: rule.to_string(),
+            severity: Severity::Warning,
+            path,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}] {}: {}",
+            self.severity.as_str(),
+            self.rule,
+            self.path,
+            self.message
+        )
+    }
+}
+
+pub fn load_document(spec_path: &Path) -> Result<Value> {
+    let content = fs::read_to_string(spec_path)
+        .with_context(|| format!("Failed to read spec {}", spec_path.display()))?;
+    serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse spec {}", spec_path.display()))
+}
+
+/// The native rule `rule` checks the same thing as, or `rule` itself.
+pub fn canonical_rule(rule: &str) -> &str {
+    EQUIVALENT_RULES
+        .iter()
+        .find(|(other, _)| *other == rule)
+        .map_or(rule, |(_, native)| native)
+}
+
+pub fn has_errors(findings: &[Finding]) -> bool {
+    findings.iter().any(|f| f.severity == Severity::Error)
+}
+
+/// Returns the major/minor OpenAPI version declared by the document, if any.
+pub fn openapi_version(doc: &Value) -> Option<(u32, u32)> {
+    let raw = doc.get("openapi")?.as_str()?;
+    let mut parts = raw.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Iterate over the operations of a path item as `(method, operation)` pairs.
+pub fn operations(path_item: &Value) -> impl Iterator<Item = (&'static str, &Value)> {
+    HTTP_METHODS
+        .iter()
+        .filter_map(move |method| path_item.get(*method).map(|op| (*method, op)))
+}
+
+/// Iterate over `(key, value)` pairs of a mapping, skipping non-string keys.
+pub fn entries(value: Option<&Value>) -> impl Iterator<Item = (&str, &Value)> {
+    value
+        .and_then(Value::as_mapping)
+        .into_iter()
+        .flat_map(|mapping| mapping.iter())
+        .filter_map(|(key, value)| key.as_str().map(|key| (key, value)))
+}
+
+/// Iterate over an operation's responses as `(status, response)` pairs,
+/// including status codes YAML reads as numbers (`200:` rather than `'200':`).
+pub fn responses(operation: &Value) -> impl Iterator<Item = (String, &Value)> {
+    operation
+        .get("responses")
+        .and_then(Value::as_mapping)
+        .into_iter()
+        .flat_map(|mapping| mapping.iter())
+        .filter_map(|(key, value)| match key {
+            Value::String(status) => Some((status.clone(), value)),
+            Value::Number(status) => Some((status.to_string(), value)),
+            _ => None,
+        })
+}
+
+/// Append a segment to a JSON pointer, escaping `~` and `/`.
+pub fn pointer(base: &str, segment: &str) -> String {
+    format!("{base}/{}", segment.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Resolve a local reference such as `#/components/schemas/Pet`.
+pub fn resolve_ref<'a>(doc: &'a Value, reference: &str) -> Option<&'a Value> {
+    let pointer = reference.strip_prefix('#')?;
+    pointer.split('/').skip(1).try_fold(doc, |value, segment| {
+        value.get(segment.replace("~1", "/").replace("~0", "~"))
+    })
+}
+
+/// The target of `value` if it is a local reference, else `value` itself.
+pub fn follow<'a>(doc: &'a Value, value: &'a Value) -> &'a Value {
+    value
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| resolve_ref(doc, reference))
+        .unwrap_or(value)
+}
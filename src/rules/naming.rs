@@ -0,0 +1,227 @@
+use serde_yaml::Value;
+
+use super::{Finding, entries, follow, operations, pointer};
+use crate::config::{NameCase, Naming};
+
+/// Keys whose values are example data, not schemas.
+const EXAMPLE_KEYS: [&str; 2] = ["example", "examples"];
+
+/// Names that break the casing `naming` sets for their kind: static path
+/// segments, parameters, schema properties, schema names, and string enum
+/// values.
+pub fn check(doc: &Value, naming: &Naming) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for (route, path_item) in entries(doc.get("paths")) {
+        let route_path = pointer("#/paths", route);
+        if let Some(case) = naming.paths {
+            for segment in route.split('/') {
+                if segment.is_empty() || segment.contains('{') || matches(case, segment) {
+                    continue;
+                }
+                findings.push(Finding::error(
+                    "naming-paths",
+                    route_path.clone(),
+                    format!(
+                        "Path segment {segment} in {route} is not {} case",
+                        case.as_str()
+                    ),
+                ));
+            }
+        }
+        check_parameters(path_item, &route_path, naming, &mut findings);
+        for (method, operation) in operations(path_item) {
+            check_parameters(
+                operation,
+                &pointer(&route_path, method),
+                naming,
+                &mut findings,
+            );
+        }
+    }
+
+    let components = doc.get("components");
+    for (name, parameter) in entries(components.and_then(|c| c.get("parameters"))) {
+        let parameter_path = pointer("#/components/parameters", name);
+        check_parameter(
+            follow(doc, parameter),
+            &parameter_path,
+            naming,
+            &mut findings,
+        );
+    }
+    for (name, schema) in entries(components.and_then(|c| c.get("schemas"))) {
+        let schema_path = pointer("#/components/schemas", name);
+        if let Some(case) = naming.schemas
+            && !matches(case, name)
+        {
+            findings.push(Finding::error(
+                "naming-schemas",
+                schema_path.clone(),
+                format!("Schema name {name} is not {} case", case.as_str()),
+            ));
+        }
+        check_schema(schema, &schema_path, naming, &mut findings);
+    }
+
+    // Inline schemas of parameters, headers, and media types anywhere else.
+    for (key, value) in entries(Some(doc)) {
+        if key != "components" {
+            find_schemas(value, &pointer("#", key), naming, &mut findings);
+        }
+    }
+    for (key, value) in entries(components) {
+        if key != "schemas" {
+            find_schemas(value, &pointer("#/components", key), naming, &mut findings);
+        }
+    }
+
+    findings
+}
+
+/// Check the inline parameters of a path item or operation. Referenced ones
+/// are checked once, under `components`.
+fn check_parameters(
+    holder: &Value,
+    holder_path: &str,
+    naming: &Naming,
+    findings: &mut Vec<Finding>,
+) {
+    let Some(parameters) = holder.get("parameters").and_then(Value::as_sequence) else {
+        return;
+    };
+    let parameters_path = pointer(holder_path, "parameters");
+    for (idx, parameter) in parameters.iter().enumerate() {
+        if parameter.get("$ref").is_none() {
+            check_parameter(
+                parameter,
+                &pointer(&parameters_path, &idx.to_string()),
+                naming,
+                findings,
+            );
+        }
+    }
+}
+
+/// Check a parameter's name. Header names are left alone: they're
+/// case-insensitive and conventionally `Train-Case`.
+fn check_parameter(parameter: &Value, path: &str, naming: &Naming, findings: &mut Vec<Finding>) {
+    let Some(case) = naming.parameters else {
+        return;
+    };
+    let location = parameter.get("in").and_then(Value::as_str);
+    let Some(name) = parameter.get("name").and_then(Value::as_str) else {
+        return;
+    };
+    if location != Some("header") && !matches(case, name) {
+        findings.push(Finding::error(
+            "naming-parameters",
+            path.to_string(),
+            format!("Parameter {name} is not {} case", case.as_str()),
+        ));
+    }
+}
+
+/// Walk `value` for `schema` entries outside `components.schemas`.
+fn find_schemas(value: &Value, path: &str, naming: &Naming, findings: &mut Vec<Finding>) {
+    match value {
+        Value::Mapping(_) => {
+            for (key, child) in entries(Some(value)) {
+                if EXAMPLE_KEYS.contains(&key) {
+                    continue;
+                }
+                let child_path = pointer(path, key);
+                if key == "schema" {
+                    check_schema(child, &child_path, naming, findings);
+                } else {
+                    find_schemas(child, &child_path, naming, findings);
+                }
+            }
+        }
+        Value::Sequence(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                find_schemas(item, &pointer(path, &idx.to_string()), naming, findings);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Check a schema's property names and enum values, and those of the
+/// schemas inside it.
+fn check_schema(schema: &Value, path: &str, naming: &Naming, findings: &mut Vec<Finding>) {
+    if schema.get("$ref").is_some() {
+        return;
+    }
+    for (name, property) in entries(schema.get("properties")) {
+        let property_path = pointer(&pointer(path, "properties"), name);
+        if let Some(case) = naming.properties
+            && !matches(case, name)
+        {
+            findings.push(Finding::error(
+                "naming-properties",
+                property_path.clone(),
+                format!("Property {name} is not {} case", case.as_str()),
+            ));
+        }
+        check_schema(property, &property_path, naming, findings);
+    }
+    if let Some(case) = naming.enums
+        && let Some(values) = schema.get("enum").and_then(Value::as_sequence)
+    {
+        for (idx, value) in values.iter().enumerate() {
+            if let Some(value) = value.as_str()
+                && !matches(case, value)
+            {
+                findings.push(Finding::error(
+                    "naming-enums",
+                    pointer(&pointer(path, "enum"), &idx.to_string()),
+                    format!("Enum value {value} is not {} case", case.as_str()),
+                ));
+            }
+        }
+    }
+    for key in ["items", "additionalProperties", "not"] {
+        if let Some(child) = schema.get(key).filter(|child| child.is_mapping()) {
+            check_schema(child, &pointer(path, key), naming, findings);
+        }
+    }
+    for key in ["allOf", "oneOf", "anyOf", "prefixItems"] {
+        if let Some(children) = schema.get(key).and_then(Value::as_sequence) {
+            let list_path = pointer(path, key);
+            for (idx, child) in children.iter().enumerate() {
+                check_schema(
+                    child,
+                    &pointer(&list_path, &idx.to_string()),
+                    naming,
+                    findings,
+                );
+            }
+        }
+    }
+}
+
+/// Whether `name` is written in `case`. Digits count as either case.
+fn matches(case: NameCase, name: &str) -> bool {
+    let words = |separator: char, upper: bool| {
+        name.split(separator).all(|word| {
+            !word.is_empty()
+                && word.chars().all(|c| {
+                    c.is_ascii_digit()
+                        || if upper {
+                            c.is_ascii_uppercase()
+                        } else {
+                            c.is_ascii_lowercase()
+                        }
+                })
+        })
+    };
+    let alphanumeric = name.chars().all(|c| c.is_ascii_alphanumeric());
+    match case {
+        NameCase::Camel => alphanumeric && name.starts_with(|c: char| c.is_ascii_lowercase()),
+        NameCase::Pascal => alphanumeric && name.starts_with(|c: char| c.is_ascii_uppercase()),
+        NameCase::Snake => words('_', false),
+        NameCase::Kebab => words('-', false),
+        NameCase::UpperSnake => words('_', true),
+    }
+}
@@ -0,0 +1,198 @@
+use serde_yaml::Value;
+use std::collections::HashMap;
+
+use super::{Finding, Severity, entries, follow, operations, pointer, responses};
+
+/// Response headers that document a rate limit, lowercased.
+const RATE_LIMIT_HEADERS: [&str; 6] = [
+    "ratelimit",
+    "ratelimit-limit",
+    "ratelimit-policy",
+    "x-ratelimit-limit",
+    "x-rate-limit-limit",
+    "retry-after",
+];
+
+/// OWASP API Security Top 10 rules enabled in `.oavc` (`rules.<rule>:
+/// warn|error`): servers, security schemes, and each operation's
+/// authentication, rate limiting, and path IDs.
+pub fn check(doc: &Value, levels: &HashMap<&str, Severity>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut push = |rule: &str, path: String, message: String| {
+        if let Some(severity) = levels.get(rule) {
+            findings.push(Finding {
+                rule: rule.to_string(),
+                severity: *severity,
+                path,
+                message,
+            });
+        }
+    };
+
+    if let Some(servers) = doc.get("servers").and_then(Value::as_sequence) {
+        for (idx, server) in servers.iter().enumerate() {
+            let Some(url) = server.get("url").and_then(Value::as_str) else {
+                continue;
+            };
+            if is_plain_http(url) {
+                push(
+                    "owasp-https",
+                    pointer(&pointer("#/servers", &idx.to_string()), "url"),
+                    format!("Server {url} uses plain HTTP (API8:2023 Security Misconfiguration)"),
+                );
+            }
+        }
+    }
+
+    let schemes = doc
+        .get("components")
+        .and_then(|components| components.get("securitySchemes"));
+    for (name, scheme) in entries(schemes) {
+        let scheme_path = pointer("#/components/securitySchemes", name);
+        let scheme = follow(doc, scheme);
+        let field = |key: &str| scheme.get(key).and_then(Value::as_str);
+        if field("type") == Some("http")
+            && field("scheme").is_some_and(|scheme| scheme.eq_ignore_ascii_case("basic"))
+        {
+            push(
+                "owasp-auth-schemes",
+                scheme_path,
+                format!(
+                    "Security scheme {name} uses HTTP Basic, which sends the password with every request (API2:2023 Broken Authentication)"
+                ),
+            );
+        } else if field("type") == Some("apiKey") && field("in") == Some("query") {
+            push(
+                "owasp-auth-schemes",
+                scheme_path,
+                format!(
+                    "Security scheme {name} takes the API key in the query string, where proxies and logs keep it (API2:2023 Broken Authentication)"
+                ),
+            );
+        }
+    }
+
+    for (route, path_item) in entries(doc.get("paths")) {
+        let route_path = pointer("#/paths", route);
+        let path_item = follow(doc, path_item);
+        for (method, operation) in operations(path_item) {
+            let label = format!("{} {route}", method.to_uppercase());
+            let op_path = pointer(&route_path, method);
+
+            let (security, security_path) = match operation.get("security") {
+                Some(security) => (Some(security), pointer(&op_path, "security")),
+                None => (doc.get("security"), "#/security".to_string()),
+            };
+            let requirements = security
+                .and_then(Value::as_sequence)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            if requirements.is_empty() {
+                push(
+                    "owasp-auth",
+                    op_path.clone(),
+                    format!(
+                        "{label} has no security requirement (API2:2023 Broken Authentication)"
+                    ),
+                );
+            } else if requirements
+                .iter()
+                .any(|requirement| requirement.as_mapping().is_some_and(|m| m.is_empty()))
+            {
+                push(
+                    "owasp-auth",
+                    security_path.clone(),
+                    format!(
+                        "{label} allows anonymous access with an empty security requirement (API2:2023 Broken Authentication)"
+                    ),
+                );
+            }
+            for (idx, requirement) in requirements.iter().enumerate() {
+                for (name, _) in entries(Some(requirement)) {
+                    if schemes.and_then(|schemes| schemes.get(name)).is_none() {
+                        push(
+                            "owasp-auth",
+                            pointer(&security_path, &idx.to_string()),
+                            format!("{label} requires undefined security scheme {name}"),
+                        );
+                    }
+                }
+            }
+
+            let responses_path = pointer(&op_path, "responses");
+            if !responses(operation).any(|(status, _)| status == "429") {
+                push(
+                    "owasp-rate-limit",
+                    responses_path,
+                    format!(
+                        "{label} declares no 429 response (API4:2023 Unrestricted Resource Consumption)"
+                    ),
+                );
+            } else if !responses(operation).any(|(_, response)| {
+                entries(follow(doc, response).get("headers")).any(|(header, _)| {
+                    RATE_LIMIT_HEADERS.contains(&header.to_ascii_lowercase().as_str())
+                })
+            }) {
+                push(
+                    "owasp-rate-limit",
+                    responses_path,
+                    format!(
+                        "{label} documents no rate-limit response headers, such as RateLimit or Retry-After (API4:2023 Unrestricted Resource Consumption)"
+                    ),
+                );
+            }
+
+            for parameter in path_item
+                .get("parameters")
+                .into_iter()
+                .chain(operation.get("parameters"))
+                .filter_map(Value::as_sequence)
+                .flatten()
+                .map(|parameter| follow(doc, parameter))
+                .filter(|parameter| parameter.get("in").and_then(Value::as_str) == Some("path"))
+            {
+                let Some(name) = parameter.get("name").and_then(Value::as_str) else {
+                    continue;
+                };
+                let numeric = parameter
+                    .get("schema")
+                    .map(|schema| follow(doc, schema))
+                    .is_some_and(is_numeric);
+                if numeric {
+                    push(
+                        "owasp-numeric-ids",
+                        op_path.clone(),
+                        format!(
+                            "{label}: path parameter {name} is numeric, so IDs are easy to enumerate (API1:2023 Broken Object Level Authorization)"
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    findings
+}
+
+/// Whether `url` is `http://` to anything but the local machine.
+fn is_plain_http(url: &str) -> bool {
+    let Some(rest) = url.strip_prefix("http://") else {
+        return false;
+    };
+    let authority = rest.split('/').next().unwrap_or_default();
+    let host = authority
+        .rsplit_once(':')
+        .filter(|(_, port)| port.chars().all(|c| c.is_ascii_digit()))
+        .map_or(authority, |(host, _)| host);
+    !matches!(host, "localhost" | "127.0.0.1" | "[::1]" | "0.0.0.0")
+}
+
+/// Whether a schema's `type` is `integer` or `number`, alone or with `null`.
+fn is_numeric(schema: &Value) -> bool {
+    let numeric = |value: &Value| matches!(value.as_str(), Some("integer" | "number"));
+    match schema.get("type") {
+        Some(Value::Sequence(types)) => types.iter().any(numeric),
+        Some(value) => numeric(value),
+        None => false,
+    }
+}
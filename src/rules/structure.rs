@@ -0,0 +1,426 @@
+use serde_yaml::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use super::{
+    Finding, entries, follow, openapi_version, operations, pointer, resolve_ref, responses,
+};
+
+const PARAMETER_LOCATIONS: [&str; 4] = ["query", "header", "path", "cookie"];
+const SCHEMA_TYPES: [&str; 7] = [
+    "string", "number", "integer", "boolean", "array", "object", "null",
+];
+const COMPONENT_KINDS: [&str; 10] = [
+    "schemas",
+    "responses",
+    "parameters",
+    "examples",
+    "requestBodies",
+    "headers",
+    "securitySchemes",
+    "links",
+    "callbacks",
+    "pathItems",
+];
+
+/// Structural checks standing in for Redocly when no container engine is
+/// available: the OpenAPI version, required objects and fields, path
+/// parameters, `$ref` targets (files relative to `base_dir`), and unique
+/// `operationId`s.
+pub fn check(doc: &Value, base_dir: &Path) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    let Some(version) = openapi_version(doc) else {
+        findings.push(Finding::error(
+            "openapi-version",
+            "#/openapi".to_string(),
+            "missing or invalid openapi version (expected 3.0.x or 3.1.x)",
+        ));
+        return findings;
+    };
+    if version.0 != 3 || version.1 > 1 {
+        findings.push(Finding::error(
+            "openapi-version",
+            "#/openapi".to_string(),
+            format!(
+                "OpenAPI {}.{} is not supported (expected 3.0.x or 3.1.x)",
+                version.0, version.1
+            ),
+        ));
+        return findings;
+    }
+    let v31 = version >= (3, 1);
+
+    check_info(doc, &mut findings);
+    if v31 {
+        if ["paths", "components", "webhooks"]
+            .iter()
+            .all(|key| doc.get(*key).is_none())
+        {
+            findings.push(Finding::error(
+                "root-fields",
+                "#".to_string(),
+                "an OpenAPI 3.1 document needs paths, components, or webhooks",
+            ));
+        }
+    } else if doc.get("paths").is_none() {
+        findings.push(Finding::error(
+            "root-fields",
+            "#".to_string(),
+            "missing required field paths",
+        ));
+    }
+
+    for (route, path_item) in entries(doc.get("paths")) {
+        let route_path = pointer("#/paths", route);
+        if !route.starts_with('/') {
+            findings.push(Finding::error(
+                "path-key",
+                route_path.clone(),
+                format!("path {route} must start with /"),
+            ));
+        }
+        let path_item = follow(doc, path_item);
+        for (method, operation) in operations(path_item) {
+            let op_path = pointer(&route_path, method);
+            check_operation(doc, operation, &op_path, v31, &mut findings);
+            check_path_parameters(doc, route, path_item, operation, &op_path, &mut findings);
+        }
+    }
+    for (name, path_item) in entries(doc.get("webhooks")) {
+        let hook_path = pointer("#/webhooks", name);
+        for (method, operation) in operations(follow(doc, path_item)) {
+            let op_path = pointer(&hook_path, method);
+            check_operation(doc, operation, &op_path, v31, &mut findings);
+        }
+    }
+    for (path, message) in duplicate_operation_ids(doc) {
+        findings.push(Finding::error("operation-id-unique", path, message));
+    }
+
+    if let Some(components) = doc.get("components") {
+        for kind in COMPONENT_KINDS {
+            for (name, _) in entries(components.get(kind)) {
+                if !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+                {
+                    findings.push(Finding::error(
+                        "component-name",
+                        pointer(&pointer("#/components", kind), name),
+                        format!("component name {name} may only contain letters, digits, '.', '-', and '_'"),
+                    ));
+                }
+            }
+        }
+    }
+
+    walk(doc, doc, "#", base_dir, v31, &mut findings);
+    findings
+}
+
+/// Operations under `paths` and `webhooks` whose `operationId` an earlier
+/// operation already uses, as `(path, message)`.
+pub(super) fn duplicate_operation_ids(doc: &Value) -> Vec<(String, String)> {
+    let mut operation_ids: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (root, key) in [("#/paths", "paths"), ("#/webhooks", "webhooks")] {
+        for (name, path_item) in entries(doc.get(key)) {
+            let item_path = pointer(root, name);
+            for (method, operation) in operations(follow(doc, path_item)) {
+                if let Some(id) = operation.get("operationId").and_then(Value::as_str) {
+                    operation_ids
+                        .entry(id)
+                        .or_default()
+                        .push(pointer(&item_path, method));
+                }
+            }
+        }
+    }
+    operation_ids
+        .into_iter()
+        .flat_map(|(id, paths)| {
+            let first = paths[0].clone();
+            paths
+                .into_iter()
+                .skip(1)
+                .map(move |path| (path, format!("operationId {id} is also used by {first}")))
+        })
+        .collect()
+}
+
+fn check_info(doc: &Value, findings: &mut Vec<Finding>) {
+    let Some(info) = doc.get("info").filter(|info| info.is_mapping()) else {
+        findings.push(Finding::error(
+            "info",
+            "#".to_string(),
+            "missing required object info",
+        ));
+        return;
+    };
+    for field in ["title", "version"] {
+        if info.get(field).and_then(Value::as_str).is_none() {
+            findings.push(Finding::error(
+                "info",
+                pointer("#/info", field),
+                format!("info.{field} is required and must be a string"),
+            ));
+        }
+    }
+}
+
+fn check_operation(
+    doc: &Value,
+    operation: &Value,
+    op_path: &str,
+    v31: bool,
+    findings: &mut Vec<Finding>,
+) {
+    match operation.get("responses") {
+        None if !v31 => findings.push(Finding::error(
+            "operation-responses",
+            op_path.to_string(),
+            "operation has no responses",
+        )),
+        None => {}
+        Some(_) => {
+            let responses_path = pointer(op_path, "responses");
+            if responses(operation).next().is_none() {
+                findings.push(Finding::error(
+                    "operation-responses",
+                    responses_path.clone(),
+                    "responses must declare at least one response",
+                ));
+            }
+            for (status, _) in responses(operation) {
+                if !is_status_key(&status) {
+                    findings.push(Finding::error(
+                        "response-status",
+                        pointer(&responses_path, &status),
+                        format!(
+                            "{status} is not a status code (expected default, a code, or 1XX-5XX)"
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    let params_path = pointer(op_path, "parameters");
+    let mut seen = BTreeSet::new();
+    for (idx, parameter) in parameters(operation).enumerate() {
+        let param_path = pointer(&params_path, &idx.to_string());
+        let parameter = follow(doc, parameter);
+        let name = parameter.get("name").and_then(Value::as_str);
+        let location = parameter.get("in").and_then(Value::as_str);
+        let (Some(name), Some(location)) = (name, location) else {
+            findings.push(Finding::error(
+                "parameter",
+                param_path,
+                "parameter needs a name and an in",
+            ));
+            continue;
+        };
+        if !PARAMETER_LOCATIONS.contains(&location) {
+            findings.push(Finding::error(
+                "parameter",
+                pointer(&param_path, "in"),
+                format!("in: {location} is invalid (expected query, header, path, or cookie)"),
+            ));
+        }
+        if location == "path" && parameter.get("required").and_then(Value::as_bool) != Some(true) {
+            findings.push(Finding::error(
+                "parameter",
+                param_path.clone(),
+                format!("path parameter {name} must be required: true"),
+            ));
+        }
+        if parameter.get("schema").is_some() == parameter.get("content").is_some() {
+            findings.push(Finding::error(
+                "parameter",
+                param_path.clone(),
+                format!("parameter {name} needs exactly one of schema and content"),
+            ));
+        }
+        if !seen.insert((name, location)) {
+            findings.push(Finding::error(
+                "parameter",
+                param_path,
+                format!("{location} parameter {name} is declared twice"),
+            ));
+        }
+    }
+}
+
+/// Every `{name}` in the route is a path parameter of the operation or
+/// path item, and every path parameter appears in the route.
+fn check_path_parameters(
+    doc: &Value,
+    route: &str,
+    path_item: &Value,
+    operation: &Value,
+    op_path: &str,
+    findings: &mut Vec<Finding>,
+) {
+    let templated: BTreeSet<&str> = route
+        .split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+        .collect();
+    let declared: BTreeSet<&str> = parameters(path_item)
+        .chain(parameters(operation))
+        .map(|parameter| follow(doc, parameter))
+        .filter(|parameter| parameter.get("in").and_then(Value::as_str) == Some("path"))
+        .filter_map(|parameter| parameter.get("name").and_then(Value::as_str))
+        .collect();
+    for name in templated.difference(&declared) {
+        findings.push(Finding::error(
+            "path-parameters",
+            op_path.to_string(),
+            format!("{{{name}}} in {route} has no path parameter"),
+        ));
+    }
+    for name in declared.difference(&templated) {
+        findings.push(Finding::error(
+            "path-parameters",
+            op_path.to_string(),
+            format!("path parameter {name} does not appear in {route}"),
+        ));
+    }
+}
+
+fn parameters(holder: &Value) -> impl Iterator<Item = &Value> {
+    holder
+        .get("parameters")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+}
+
+/// Check every `$ref` and `type` in the document. Example and default values
+/// are data and skipped.
+fn walk(
+    doc: &Value,
+    value: &Value,
+    path: &str,
+    base_dir: &Path,
+    v31: bool,
+    findings: &mut Vec<Finding>,
+) {
+    match value {
+        Value::Mapping(_) => {
+            if let Some(reference) = value.get("$ref") {
+                check_ref(doc, reference, &pointer(path, "$ref"), base_dir, findings);
+            }
+            // Security schemes have a `type` of their own.
+            if let Some(kind) = value.get("type")
+                && !path.starts_with("#/components/securitySchemes")
+            {
+                check_type(kind, &pointer(path, "type"), v31, findings);
+            }
+            for (key, child) in entries(Some(value)) {
+                if matches!(key, "example" | "examples" | "enum" | "const" | "default") {
+                    continue;
+                }
+                // `properties` maps names to schemas, so a property may be
+                // called `type` or `$ref`.
+                if key == "properties" {
+                    for (name, schema) in entries(Some(child)) {
+                        let property_path = pointer(&pointer(path, key), name);
+                        walk(doc, schema, &property_path, base_dir, v31, findings);
+                    }
+                    continue;
+                }
+                walk(doc, child, &pointer(path, key), base_dir, v31, findings);
+            }
+        }
+        Value::Sequence(items) => {
+            for (idx, child) in items.iter().enumerate() {
+                walk(
+                    doc,
+                    child,
+                    &pointer(path, &idx.to_string()),
+                    base_dir,
+                    v31,
+                    findings,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_ref(
+    doc: &Value,
+    reference: &Value,
+    path: &str,
+    base_dir: &Path,
+    findings: &mut Vec<Finding>,
+) {
+    let Some(reference) = reference.as_str() else {
+        findings.push(Finding::error(
+            "ref-resolve",
+            path.to_string(),
+            "$ref must be a string",
+        ));
+        return;
+    };
+    if reference.starts_with('#') {
+        if resolve_ref(doc, reference).is_none() {
+            findings.push(Finding::error(
+                "ref-resolve",
+                path.to_string(),
+                format!("{reference} does not exist"),
+            ));
+        }
+        return;
+    }
+    if reference.contains("://") {
+        return;
+    }
+    let file = reference.split('#').next().unwrap_or(reference);
+    if !base_dir.join(file).is_file() {
+        findings.push(Finding::error(
+            "ref-resolve",
+            path.to_string(),
+            format!("referenced file {file} does not exist"),
+        ));
+    }
+}
+
+fn check_type(kind: &Value, path: &str, v31: bool, findings: &mut Vec<Finding>) {
+    let kinds: Vec<&Value> = match kind {
+        Value::Sequence(kinds) if v31 => kinds.iter().collect(),
+        Value::Sequence(_) => {
+            findings.push(Finding::error(
+                "schema-type",
+                path.to_string(),
+                "type lists need OpenAPI 3.1; use a single type with nullable in 3.0",
+            ));
+            return;
+        }
+        kind => vec![kind],
+    };
+    for kind in kinds {
+        let valid = kind
+            .as_str()
+            .is_some_and(|kind| SCHEMA_TYPES.contains(&kind) && (v31 || kind != "null"));
+        if !valid {
+            findings.push(Finding::error(
+                "schema-type",
+                path.to_string(),
+                format!(
+                    "{} is not a schema type",
+                    serde_yaml::to_string(kind).unwrap_or_default().trim_end()
+                ),
+            ));
+        }
+    }
+}
+
+fn is_status_key(status: &str) -> bool {
+    let bytes = status.as_bytes();
+    status == "default"
+        || (bytes.len() == 3
+            && (b'1'..=b'5').contains(&bytes[0])
+            && (bytes[1..].iter().all(u8::is_ascii_digit) || &bytes[1..] == b"XX"))
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use super::Finding;
+
+/// Source-level checks of one spec file, before it is parsed: invalid
+/// UTF-8 and, for YAML, tab indentation, duplicate keys, merge keys, and
+/// anchors and aliases, which bundling expands into copies. Findings are
+/// located as `label:line:column`.
+pub fn check(label: &str, bytes: &[u8], yaml: bool) -> Vec<Finding> {
+    let content = match std::str::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(err) => {
+            let valid = &bytes[..err.valid_up_to()];
+            let line = valid.iter().filter(|b| **b == b'\n').count() + 1;
+            let column =
+                valid.len() - valid.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1) + 1;
+            return vec![Finding::error(
+                "utf-8",
+                format!("{label}:{line}:{column}"),
+                "the file is not valid UTF-8",
+            )];
+        }
+    };
+    if !yaml {
+        return Vec::new();
+    }
+
+    let mut findings = Vec::new();
+    // Open mappings as (column, key -> line of its first definition).
+    let mut mappings: Vec<(usize, HashMap<String, usize>)> = Vec::new();
+    let mut block_indent: Option<usize> = None;
+    for (idx, line) in content.lines().enumerate() {
+        let number = idx + 1;
+        let at = |column: usize| format!("{label}:{number}:{}", column + 1);
+        if line.starts_with("---") || line.starts_with("...") {
+            mappings.clear();
+            block_indent = None;
+            continue;
+        }
+        let trimmed = line.trim_start_matches([' ', '\t']);
+        let indent = line.len() - trimmed.len();
+        if trimmed.is_empty() {
+            continue;
+        }
+        match block_indent {
+            Some(block) if indent > block => continue,
+            _ => block_indent = None,
+        }
+        if let Some(tab) = line[..indent].find('\t') {
+            findings.push(Finding::error(
+                "tab-indentation",
+                at(tab),
+                "indentation contains a tab; YAML only allows spaces",
+            ));
+        }
+        if trimmed.starts_with('#') {
+            continue;
+        }
+
+        let mut column = indent;
+        let mut content = trimmed;
+        while content == "-" || content.starts_with("- ") {
+            let rest = content[1..].trim_start();
+            column += content.len() - rest.len();
+            content = rest;
+            mappings.retain(|(open, _)| *open < column);
+        }
+
+        let value = match split_key(content) {
+            Some((key, value)) => {
+                mappings.retain(|(open, _)| *open <= column);
+                match mappings.last_mut() {
+                    Some((open, keys)) if *open == column => {
+                        if let Some(first) = keys.get(&key) {
+                            findings.push(Finding::error(
+                                "duplicate-key",
+                                at(column),
+                                format!("{key} is already defined on line {first}; parsers keep only one"),
+                            ));
+                        } else {
+                            keys.insert(key.clone(), number);
+                        }
+                    }
+                    _ => mappings.push((column, HashMap::from([(key.clone(), number)]))),
+                }
+                if key == "<<" {
+                    findings.push(Finding::error(
+                        "merge-key",
+                        at(column),
+                        "merge keys (<<) are YAML 1.1 only and are lost when the spec is bundled",
+                    ));
+                }
+                value
+            }
+            None => content,
+        };
+
+        let value_column = line.len() - value.len();
+        if let Some(name) = token(value, '&') {
+            findings.push(Finding::warning(
+                "anchor",
+                at(value_column),
+                format!(
+                    "anchor &{name} doesn't survive bundling, which expands its aliases into copies"
+                ),
+            ));
+        } else if let Some(name) = token(value, '*') {
+            findings.push(Finding::warning(
+                "anchor",
+                at(value_column),
+                format!("alias *{name} is expanded into a copy when the spec is bundled; use $ref"),
+            ));
+        }
+
+        let scalar = match token(value, '&') {
+            Some(name) => value[name.len() + 1..].trim_start(),
+            None => value,
+        };
+        if is_block_indicator(scalar) {
+            block_indent = Some(if value.len() == content.len() {
+                indent
+            } else {
+                column
+            });
+        }
+    }
+    findings
+}
+
+/// `key` and the rest of a `key: value` line, for plain and quoted keys.
+pub(crate) fn split_key(content: &str) -> Option<(String, &str)> {
+    if content.starts_with(['{', '[', '&', '*', '!', '|', '>', '?', '%', '@', '`', '#']) {
+        return None;
+    }
+    let (key, rest) = match content.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let end = content[1..].find(quote)? + 1;
+            (&content[1..end], content[end + 1..].strip_prefix(':')?)
+        }
+        _ => match content.find(": ") {
+            Some(end) => (&content[..end], &content[end + 1..]),
+            None => (content.strip_suffix(':')?, ""),
+        },
+    };
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((key.trim_end().to_string(), rest.trim_start()))
+}
+
+/// The name after a leading `&` or `*`.
+fn token(value: &str, sigil: char) -> Option<&str> {
+    let name = value.strip_prefix(sigil)?;
+    let end = name.find(char::is_whitespace).unwrap_or(name.len());
+    (end > 0).then(|| &name[..end])
+}
+
+pub(crate) fn is_block_indicator(value: &str) -> bool {
+    let value = value.split(" #").next().unwrap_or(value).trim_end();
+    value.starts_with(['|', '>'])
+        && value[1..]
+            .chars()
+            .all(|c| matches!(c, '+' | '-') || c.is_ascii_digit())
+}
@@ -0,0 +1,219 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::net::{IpAddr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::daemon::{Response, read_request, write_response};
+use crate::output::Output;
+use crate::steps::html_escape;
+use crate::util::OAV_DIR;
+
+/// Path the injected script polls for the reports' version.
+const VERSION_PATH: &str = "/_oav/version";
+
+/// Reloads the page once the reports' version changes, which the server
+/// substitutes for `{version}`.
+const RELOAD_SCRIPT: &str = r#"  <script>
+    (function () {
+      let version = "{version}";
+      setInterval(function () {
+        fetch("/_oav/version", { cache: "no-store" })
+          .then(function (response) { return response.text(); })
+          .then(function (latest) {
+            if (latest !== version) {
+              version = latest;
+              location.reload();
+            }
+          })
+          .catch(function () {});
+      }, 1000);
+    })();
+  </script>
+"#;
+
+/// Serve `.oav/reports` on `host`:`port` until interrupted, one request at a
+/// time. HTML pages reload themselves when the dashboard is rewritten.
+pub fn serve(root: &Path, host: IpAddr, port: u16, output: &Output) -> Result<()> {
+    let reports_dir = root.join(OAV_DIR).join("reports");
+    fs::create_dir_all(&reports_dir).context("Failed to create reports directory")?;
+    let listener = TcpListener::bind((host, port))
+        .with_context(|| format!("Failed to listen on {host}:{port}"))?;
+    let shown = if host.is_unspecified() {
+        "localhost".to_string()
+    } else {
+        host.to_string()
+    };
+    output.println_always(&format!(
+        "Serving .oav/reports on http://{shown}:{port} (Ctrl-C to stop)"
+    ));
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        let response = match read_request(&mut stream) {
+            Ok(request) => {
+                let response = if request.method == "GET" {
+                    handle(&reports_dir, &request.path)
+                } else {
+                    text(405, &format!("{} is not allowed here", request.method))
+                };
+                output.println(&format!(
+                    "{} {} {}",
+                    request.method, request.path, response.status
+                ));
+                response
+            }
+            Err(err) => text(400, &format!("{err:#}")),
+        };
+        let _ = write_response(&mut stream, &response);
+    }
+    Ok(())
+}
+
+fn handle(reports_dir: &Path, path: &str) -> Response {
+    if path == VERSION_PATH {
+        return text(200, &version(reports_dir));
+    }
+    let Some(file) = resolve(reports_dir, path) else {
+        return text(404, &format!("Nothing at {path}"));
+    };
+    if file.is_dir() {
+        return match fs::read_dir(&file) {
+            Ok(_) if !path.ends_with('/') => Response {
+                status: 301,
+                content_type: "text/plain; charset=utf-8",
+                body: Vec::new(),
+                location: Some(format!("{path}/")),
+            },
+            Ok(entries) => html(reports_dir, &listing(path, entries)),
+            Err(_) => text(404, &format!("Nothing at {path}")),
+        };
+    }
+    let Ok(body) = fs::read(&file) else {
+        return text(404, &format!("Nothing at {path}"));
+    };
+    let content_type = content_type(&file);
+    if content_type.starts_with("text/html") {
+        return html(reports_dir, &String::from_utf8_lossy(&body));
+    }
+    Response {
+        status: 200,
+        content_type,
+        body,
+        location: None,
+    }
+}
+
+/// The file `path` names under `reports_dir`, with `/` naming the dashboard.
+/// Paths leaving the directory name nothing.
+fn resolve(reports_dir: &Path, path: &str) -> Option<PathBuf> {
+    if path == "/" {
+        return Some(reports_dir.join("dashboard.html"));
+    }
+    let mut file = reports_dir.to_path_buf();
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        let segment = percent_decode(segment)?;
+        if segment == "." || segment == ".." || segment.contains(['/', '\\']) {
+            return None;
+        }
+        file.push(segment);
+    }
+    Some(file)
+}
+
+fn percent_decode(segment: &str) -> Option<String> {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = segment.get(index + 1..index + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// When the dashboard was last written, which changes once a run or
+/// `oav report` regenerates the reports.
+fn version(reports_dir: &Path) -> String {
+    fs::metadata(reports_dir.join("dashboard.html"))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|since| since.as_millis().to_string())
+        .unwrap_or_default()
+}
+
+/// `page` with the reload script before `</body>`.
+fn html(reports_dir: &Path, page: &str) -> Response {
+    let script = RELOAD_SCRIPT.replace("{version}", &version(reports_dir));
+    let body = match page.rfind("</body>") {
+        Some(end) => format!("{}{script}{}", &page[..end], &page[end..]),
+        None => format!("{page}{script}"),
+    };
+    Response {
+        status: 200,
+        content_type: "text/html; charset=utf-8",
+        body: body.into_bytes(),
+        location: None,
+    }
+}
+
+/// An index of a reports subdirectory, such as a generator's logs.
+fn listing(path: &str, entries: fs::ReadDir) -> String {
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if entry.path().is_dir() {
+                format!("{name}/")
+            } else {
+                name
+            }
+        })
+        .collect();
+    names.sort();
+    let mut page = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head><meta charset=\"UTF-8\"><title>{0}</title></head>\n<body>\n  <h1>{0}</h1>\n  <ul>\n    <li><a href=\"../\">../</a></li>\n",
+        html_escape(path)
+    );
+    for name in names {
+        let name = html_escape(&name);
+        page.push_str(&format!("    <li><a href=\"{name}\">{name}</a></li>\n"));
+    }
+    page.push_str("  </ul>\n</body>\n</html>\n");
+    page
+}
+
+fn content_type(file: &Path) -> &'static str {
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some("html" | "htm") => "text/html; charset=utf-8",
+        Some("md") => "text/markdown; charset=utf-8",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("pdf") => "application/pdf",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("log" | "txt" | "tsv" | "prom" | "sarif" | "yaml" | "yml") => {
+            "text/plain; charset=utf-8"
+        }
+        _ => "application/octet-stream",
+    }
+}
+
+fn text(status: u16, message: &str) -> Response {
+    Response {
+        status,
+        content_type: "text/plain; charset=utf-8",
+        body: format!("{message}\n").into_bytes(),
+        location: None,
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use super::report::html_escape;
+use crate::config::{BadgeStyle, Config};
+use crate::results::RunResults;
+use crate::util::{OAV_DIR, write_atomic};
+
+const LABEL_COLOR: &str = "#555";
+const PASSING_COLOR: &str = "#4c1";
+const FAILING_COLOR: &str = "#e05d44";
+
+/// Dimensions of a badge style.
+struct Look {
+    height: f64,
+    radius: f64,
+    font_size: f64,
+    /// Space each side of a text.
+    padding: f64,
+    letter_spacing: f64,
+    uppercase: bool,
+    /// Gradient and text shadow.
+    shaded: bool,
+    bold: bool,
+}
+
+impl Look {
+    fn of(style: BadgeStyle) -> Self {
+        let flat = Self {
+            height: 20.0,
+            radius: 3.0,
+            font_size: 11.0,
+            padding: 6.0,
+            letter_spacing: 0.0,
+            uppercase: false,
+            shaded: true,
+            bold: false,
+        };
+        match style {
+            BadgeStyle::Flat => flat,
+            BadgeStyle::FlatSquare => Self {
+                radius: 0.0,
+                shaded: false,
+                ..flat
+            },
+            BadgeStyle::ForTheBadge => Self {
+                height: 28.0,
+                radius: 0.0,
+                font_size: 10.0,
+                padding: 12.0,
+                letter_spacing: 1.0,
+                uppercase: true,
+                shaded: false,
+                bold: true,
+            },
+        }
+    }
+
+    /// Width `text` takes up, from rough Verdana glyph widths.
+    fn text_width(&self, text: &str) -> f64 {
+        let width: f64 = text
+            .chars()
+            .map(|c| match c {
+                'i' | 'l' | 'j' | 'I' | '!' | '.' | ',' | ':' | ';' | '\'' | '|' => 3.4,
+                'f' | 't' | 'r' | '(' | ')' | ' ' | '/' | '-' | '·' => 4.6,
+                'm' | 'w' | 'M' | 'W' => 10.5,
+                'A'..='Z' => 7.5,
+                _ => 6.9,
+            })
+            .sum();
+        let bold = if self.bold { 1.1 } else { 1.0 };
+        width * self.font_size / 11.0 * bold + self.letter_spacing * text.chars().count() as f64
+    }
+}
+
+/// Write `.oav/reports/badge.svg` with whether `run` passed and its counts.
+pub fn write(root: &Path, config: &Config, run: &RunResults) -> Result<()> {
+    let (passed, failed) = (run.passed(), run.failed());
+    let (message, color) = if failed > 0 {
+        (
+            format!("failing · {failed} failed, {passed} passed"),
+            FAILING_COLOR,
+        )
+    } else {
+        (format!("passing · {passed} passed"), PASSING_COLOR)
+    };
+    let reports_dir = root.join(OAV_DIR).join("reports");
+    fs::create_dir_all(&reports_dir).context("Failed to create reports directory")?;
+    let path = reports_dir.join("badge.svg");
+    write_atomic(
+        &path,
+        &render(&config.badge_label, &message, color, config.badge_style),
+    )
+    .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// A shields.io-like badge of `label` on grey and `message` on `color`. An
+/// empty label leaves only the message.
+fn render(label: &str, message: &str, color: &str, style: BadgeStyle) -> String {
+    let look = Look::of(style);
+    let case = |text: &str| {
+        if look.uppercase {
+            text.to_uppercase()
+        } else {
+            text.to_string()
+        }
+    };
+    let (label, message) = (case(label), case(message));
+    let section = |text: &str| {
+        if text.is_empty() {
+            0.0
+        } else {
+            (look.text_width(text) + 2.0 * look.padding).round()
+        }
+    };
+    let label_width = section(&label);
+    let message_width = section(&message);
+    let width = label_width + message_width;
+    let height = look.height;
+    let baseline = (height / 2.0 + look.font_size * 0.35).round();
+
+    let mut texts = String::new();
+    for (text, x) in [
+        (&label, label_width / 2.0),
+        (&message, label_width + message_width / 2.0),
+    ] {
+        if text.is_empty() {
+            continue;
+        }
+        let text = html_escape(text);
+        if look.shaded {
+            texts.push_str(&format!(
+                r##"    <text x="{x:.1}" y="{}" fill="#010101" fill-opacity=".3">{text}</text>
+"##,
+                baseline + 1.0
+            ));
+        }
+        texts.push_str(&format!(
+            r#"    <text x="{x:.1}" y="{baseline}">{text}</text>
+"#
+        ));
+    }
+    let gradient = if look.shaded {
+        r##"  <linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>
+"##
+    } else {
+        ""
+    };
+    let shade = if look.shaded {
+        format!(
+            r#"    <rect width="{width}" height="{height}" fill="url(#s)"/>
+"#
+        )
+    } else {
+        String::new()
+    };
+    let weight = if look.bold {
+        r#" font-weight="bold""#
+    } else {
+        ""
+    };
+    let spacing = if look.letter_spacing > 0.0 {
+        format!(r#" letter-spacing="{}""#, look.letter_spacing)
+    } else {
+        String::new()
+    };
+    let title = html_escape(
+        &[label.as_str(), message.as_str()]
+            .iter()
+            .filter(|text| !text.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join(": "),
+    );
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" role="img" aria-label="{title}">
+  <title>{title}</title>
+{gradient}  <clipPath id="r"><rect width="{width}" height="{height}" rx="{}" fill="#fff"/></clipPath>
+  <g clip-path="url(#r)">
+    <rect width="{label_width}" height="{height}" fill="{LABEL_COLOR}"/>
+    <rect x="{label_width}" width="{message_width}" height="{height}" fill="{color}"/>
+{shade}  </g>
+  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="{}"{weight}{spacing}>
+{texts}  </g>
+</svg>
+"##,
+        look.radius, look.font_size
+    )
+}
@@ -0,0 +1,134 @@
+use anyhow::{Context, Result};
+use serde_yaml::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::util::{OAV_DIR, write_atomic};
+
+/// Fingerprints of the inputs of the last successful generation per target,
+/// stored in `.oav/cache/generate.json`.
+pub struct GenerateCache {
+    path: PathBuf,
+    entries: BTreeMap<String, String>,
+}
+
+impl GenerateCache {
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(OAV_DIR).join("cache").join("generate.json");
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self { path, entries }
+    }
+
+    pub fn is_fresh(&self, key: &str, fingerprint: &str) -> bool {
+        self.entries
+            .get(key)
+            .is_some_and(|stored| stored == fingerprint)
+    }
+
+    pub fn update(&mut self, key: &str, fingerprint: Option<String>) -> Result<()> {
+        match fingerprint {
+            Some(fingerprint) => self.entries.insert(key.to_string(), fingerprint),
+            None => self.entries.remove(key),
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("Failed to create cache directory")?;
+        }
+        write_atomic(&self.path, &serde_json::to_string_pretty(&self.entries)?)
+    }
+}
+
+/// Hash everything that influences generated output: the spec (including
+/// files it references), the generator config, custom templates, and the
+/// generator image.
+pub fn fingerprint(
+    root: &Path,
+    spec_path: &Path,
+    config_path: &Path,
+    template_dir: Option<&Path>,
+    image: &str,
+) -> Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(image.as_bytes());
+    hasher.update(
+        &fs::read(config_path)
+            .with_context(|| format!("Failed to read {}", config_path.display()))?,
+    );
+
+    let mut templates = BTreeSet::new();
+    if let Some(dir) = template_dir {
+        files_under(dir, &mut templates)?;
+    }
+    for path in spec_files(root, spec_path).into_iter().chain(templates) {
+        let content =
+            fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        hasher.update(path.to_string_lossy().as_bytes());
+        hasher.update(&content);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn files_under(dir: &Path, files: &mut BTreeSet<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files_under(&path, files)?;
+        } else {
+            files.insert(path);
+        }
+    }
+    Ok(())
+}
+
+/// Canonical paths of the spec and every local file it references through
+/// `$ref`, recursively, in a stable order.
+pub fn spec_files(root: &Path, spec_path: &Path) -> BTreeSet<PathBuf> {
+    let mut visited = BTreeSet::new();
+    let mut pending = vec![root.join(spec_path)];
+    while let Some(path) = pending.pop() {
+        let Ok(canonical) = path.canonicalize() else {
+            continue;
+        };
+        if !visited.insert(canonical.clone()) {
+            continue;
+        }
+        let Ok(content) = fs::read(&canonical) else {
+            continue;
+        };
+        if let Ok(doc) = serde_yaml::from_slice::<Value>(&content) {
+            let base = canonical.parent().unwrap_or(root);
+            let mut refs = Vec::new();
+            external_refs(&doc, &mut refs);
+            pending.extend(refs.into_iter().map(|reference| base.join(reference)));
+        }
+    }
+    visited
+}
+
+/// Collect relative file references (`other.yaml#/Foo`) from `$ref` values.
+fn external_refs(value: &Value, refs: &mut Vec<String>) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, child) in mapping {
+                if key.as_str() == Some("$ref")
+                    && let Some(reference) = child.as_str()
+                {
+                    let file = reference.split('#').next().unwrap_or_default();
+                    if !file.is_empty() && !file.contains("://") {
+                        refs.push(file.to_string());
+                    }
+                } else {
+                    external_refs(child, refs);
+                }
+            }
+        }
+        Value::Sequence(items) => items.iter().for_each(|item| external_refs(item, refs)),
+        _ => {}
+    }
+}
@@ -0,0 +1,315 @@
+use anyhow::{Context, Result};
+use serde_yaml::Value;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::{active_generators, spec_files};
+use crate::baseline;
+use crate::config::{Config, GOVERNANCE_RULES, Naming, SECURITY_RULES};
+use crate::plugins;
+use crate::results::{LintFindings, ResultsStore};
+use crate::rules::{self, Finding, Severity};
+use crate::suppressions;
+use crate::util::{OAV_DIR, to_posix_path, write_log_header};
+
+/// Run the native (container-free) spec checks, recording one status entry per check.
+pub fn run(root: &Path, spec_path: &Path, config: &Config, results: &ResultsStore) -> Result<bool> {
+    let reports_dir = root.join(OAV_DIR).join("reports").join("lint");
+    fs::create_dir_all(&reports_dir).context("Failed to create lint reports directory")?;
+    let doc = rules::load_document(&root.join(spec_path))?;
+    let generators: Vec<String> = active_generators(config)
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect();
+
+    write_content_type_matrix(root, &doc, &config.allowed_content_types, &generators)?;
+
+    let mut failures = 0;
+    for (name, findings) in native(&doc, config) {
+        if !record(
+            &reports_dir,
+            config,
+            results,
+            "lint",
+            "native",
+            name,
+            &findings,
+        )? {
+            failures += 1;
+        }
+    }
+
+    if let Some(dir) = &config.plugin_dir {
+        let plugins_dir = reports_dir.join("plugins");
+        fs::create_dir_all(&plugins_dir)?;
+        for plugin in plugins::load(&root.join(dir))? {
+            // A broken plugin fails its own check rather than the run.
+            let findings = plugin.run(&doc).unwrap_or_else(|err| {
+                vec![Finding::error(
+                    "plugin",
+                    "#".to_string(),
+                    format!("{err:#}"),
+                )]
+            });
+            if !record(
+                &plugins_dir,
+                config,
+                results,
+                "lint",
+                "plugin",
+                &plugin.name,
+                &findings,
+            )? {
+                failures += 1;
+            }
+        }
+    }
+
+    Ok(failures == 0)
+}
+
+/// The findings of each native check of `doc` that `config` enables, by
+/// check name. Plugins aren't included.
+pub fn native(doc: &Value, config: &Config) -> Vec<(&'static str, Vec<Finding>)> {
+    let generators: Vec<String> = active_generators(config)
+        .into_iter()
+        .map(|(_, name)| name)
+        .collect();
+    let mut checks = vec![
+        ("callbacks", rules::check_callbacks(doc)),
+        ("discriminator", rules::check_discriminators(doc)),
+        ("examples", rules::check_examples(doc)),
+        (
+            "governance",
+            rules::check_governance(
+                doc,
+                &rule_levels(config, &GOVERNANCE_RULES),
+                &config.pagination_params,
+            ),
+        ),
+        (
+            "content-types",
+            rules::check_content_types(doc, &config.allowed_content_types, &generators),
+        ),
+    ];
+    if config.naming != Naming::default() {
+        checks.push(("naming", rules::check_naming(doc, &config.naming)));
+    }
+    checks
+}
+
+/// Severity of each OWASP rule that isn't `off`.
+pub fn security_levels(config: &Config) -> HashMap<&'static str, Severity> {
+    rule_levels(config, &SECURITY_RULES)
+}
+
+/// Check the document's structure natively, in place of Redocly when no
+/// container engine is available.
+pub fn offline(
+    root: &Path,
+    spec_path: &Path,
+    config: &Config,
+    results: &ResultsStore,
+) -> Result<bool> {
+    let reports_dir = root.join(OAV_DIR).join("reports").join("lint");
+    fs::create_dir_all(&reports_dir).context("Failed to create lint reports directory")?;
+    results.start("lint", "offline", "structure");
+    let spec = root.join(spec_path);
+    let doc = rules::load_document(&spec)?;
+    let base_dir = spec.parent().unwrap_or(root);
+    let findings = rules::check_structure(&doc, base_dir);
+    record(
+        &reports_dir,
+        config,
+        results,
+        "lint",
+        "offline",
+        "structure",
+        &findings,
+    )
+}
+
+/// Check the spec's files as text (encoding, indentation, duplicate keys,
+/// anchors) before anything parses them. Under `spec_template` the original
+/// spec is checked, not the substituted copy.
+pub fn yaml_style(
+    root: &Path,
+    spec_path: &Path,
+    config: &Config,
+    results: &ResultsStore,
+) -> Result<bool> {
+    let reports_dir = root.join(OAV_DIR).join("reports").join("lint");
+    fs::create_dir_all(&reports_dir).context("Failed to create lint reports directory")?;
+    let spec = match &config.spec {
+        Some(spec) if config.spec_template => PathBuf::from(spec),
+        _ => spec_path.to_path_buf(),
+    };
+    results.start("lint", "native", "yaml-style");
+    let canonical_root = root.canonicalize().context("Failed to resolve root path")?;
+    let mut findings = Vec::new();
+    for file in spec_files(root, &spec) {
+        let bytes =
+            fs::read(&file).with_context(|| format!("Failed to read {}", file.display()))?;
+        let label = file.strip_prefix(&canonical_root).unwrap_or(&file);
+        let yaml = file
+            .extension()
+            .is_some_and(|ext| ext == "yaml" || ext == "yml");
+        findings.extend(rules::check_yaml_style(&to_posix_path(label), &bytes, yaml));
+    }
+    record(
+        &reports_dir,
+        config,
+        results,
+        "lint",
+        "native",
+        "yaml-style",
+        &findings,
+    )
+}
+
+/// Audit the document against the OWASP API Security rules, reported under
+/// its own `security` stage rather than with style linting.
+pub fn security(
+    root: &Path,
+    spec_path: &Path,
+    config: &Config,
+    results: &ResultsStore,
+) -> Result<bool> {
+    let reports_dir = root.join(OAV_DIR).join("reports").join("security");
+    fs::create_dir_all(&reports_dir).context("Failed to create security reports directory")?;
+    results.start("security", "spec", "owasp");
+    let doc = rules::load_document(&root.join(spec_path))?;
+    let findings = rules::check_security(&doc, &security_levels(config));
+    record(
+        &reports_dir,
+        config,
+        results,
+        "security",
+        "spec",
+        "owasp",
+        &findings,
+    )
+}
+
+/// Severity of each of `rules` that isn't `off`.
+fn rule_levels(config: &Config, rules: &[(&'static str, &str)]) -> HashMap<&'static str, Severity> {
+    rules
+        .iter()
+        .filter_map(|(rule, _)| match config.rule_level(rule) {
+            "error" => Some((*rule, Severity::Error)),
+            "warn" => Some((*rule, Severity::Warning)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Log the findings of one check and record its status entry. Findings in
+/// the baseline are logged but don't fail the check; suppressed ones are
+/// only counted.
+pub(super) fn record(
+    reports_dir: &Path,
+    config: &Config,
+    results: &ResultsStore,
+    stage: &str,
+    scope: &str,
+    name: &str,
+    findings: &[Finding],
+) -> Result<bool> {
+    let log_path = reports_dir.join(format!("{name}.log"));
+    write_log_header(&log_path, &format!("$ oav check {name}"))?;
+    let LintFindings {
+        findings,
+        accepted,
+        suppressed,
+    } = results.filter_findings(name, findings.to_vec())?;
+    write_findings(&log_path, &findings, &accepted, suppressed)?;
+
+    // `strict` fails on warnings too.
+    let success = if config.strict {
+        findings.is_empty()
+    } else {
+        !rules::has_errors(&findings)
+    };
+    results.record(
+        stage,
+        scope,
+        name,
+        if success { "ok" } else { "fail" },
+        &log_path,
+    )?;
+    if !accepted.is_empty() {
+        results.record_warnings(stage, scope, name, vec![baseline_note(accepted.len())])?;
+    }
+    Ok(success)
+}
+
+/// Log line for findings `.oavignore` or `# oav-ignore` comments left out.
+pub(super) fn suppressed_note(suppressed: usize) -> String {
+    format!(
+        "{suppressed} finding(s) suppressed by {} or oav-ignore comments",
+        suppressions::FILE_NAME
+    )
+}
+
+/// Warning on a check whose findings are partly in the baseline.
+pub(super) fn baseline_note(accepted: usize) -> String {
+    format!("{accepted} finding(s) accepted in {}", baseline::FILE_NAME)
+}
+
+fn write_findings(
+    log_path: &Path,
+    findings: &[Finding],
+    accepted: &[Finding],
+    suppressed: usize,
+) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(log_path)
+        .context("Failed to open log file")?;
+    if findings.is_empty() {
+        writeln!(file, "No findings.")?;
+    }
+    for finding in findings {
+        writeln!(file, "{finding}")?;
+    }
+    if !accepted.is_empty() {
+        writeln!(file, "\n{}:", baseline_note(accepted.len()))?;
+    }
+    for finding in accepted {
+        writeln!(file, "{finding}")?;
+    }
+    if suppressed > 0 {
+        writeln!(file, "\n{}", suppressed_note(suppressed))?;
+    }
+    Ok(())
+}
+
+/// Write `.oav/reports/content-types.tsv`, rendered as the content-type matrix in the dashboard.
+fn write_content_type_matrix(
+    root: &Path,
+    doc: &Value,
+    allowed: &[String],
+    generators: &[String],
+) -> Result<()> {
+    let path = root.join(OAV_DIR).join("reports").join("content-types.tsv");
+    let mut content = String::new();
+    for entry in rules::content_type_matrix(doc) {
+        let status = if !rules::is_content_type_allowed(&entry.content_type, allowed) {
+            "fail"
+        } else if !rules::unsupported_by(&entry.content_type, generators).is_empty() {
+            "warn"
+        } else {
+            "ok"
+        };
+        content.push_str(&format!(
+            "{}\t{}\t{}\t{status}\n",
+            entry.operation,
+            entry.direction.as_str(),
+            entry.content_type
+        ));
+    }
+    fs::write(&path, content).context("Failed to write content type matrix")?;
+    Ok(())
+}
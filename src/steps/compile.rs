@@ -1,115 +1,571 @@
 use anyhow::{Context, Result, bail};
+use serde_yaml::{Mapping, Value};
+use sha2::{Digest, Sha256};
 use std::fs;
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
 
+use super::generate::output_dir;
+use super::warm;
 use crate::cli::Mode;
 use crate::config::Config;
-use crate::docker;
+use crate::docker::{self, RunStatus};
 use crate::output::Output;
-use crate::util::{OAV_DIR, append_status, write_log_header};
+use crate::remote;
+use crate::results::ResultsStore;
+use crate::util::{OAV_DIR, to_posix_path, write_atomic, write_log_header};
 
-const SUPPORTED_SERVER_GENERATORS: [&str; 6] = [
+const CACHE_OVERRIDE_FILE: &str = "docker-compose.cache.yaml";
+/// Prefix compose gave volumes under the former fixed `oav` project.
+const VOLUME_PREFIX: &str = "oav_";
+
+pub const SUPPORTED_SERVER_GENERATORS: [&str; 10] = [
     "aspnetcore",
     "go-server",
     "kotlin-spring",
+    "nodejs-express-server",
+    "php-slim4",
     "python-fastapi",
+    "rust-axum",
+    "scala-play-server",
     "spring",
     "typescript-nestjs",
 ];
 
-const SUPPORTED_CLIENT_GENERATORS: [&str; 8] = [
+pub const SUPPORTED_CLIENT_GENERATORS: [&str; 12] = [
     "csharp",
+    "dart-dio",
     "go",
     "java",
     "kotlin",
     "python",
+    "ruby",
+    "rust",
+    "swift5",
     "typescript-axios",
     "typescript-fetch",
     "typescript-node",
 ];
 
+/// Stages that run one compose service per generated project: `build-*`
+/// services compile it, `test-*` services run its own test suite.
+#[derive(Clone, Copy, PartialEq)]
+pub(super) enum BuildStage {
+    Compile,
+    Test,
+}
+
+impl BuildStage {
+    fn as_str(self) -> &'static str {
+        match self {
+            BuildStage::Compile => "compile",
+            BuildStage::Test => "test",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            BuildStage::Compile => "Compile",
+            BuildStage::Test => "Test",
+        }
+    }
+
+    fn service_prefix(self, scope: &str) -> &'static str {
+        match (self, scope == "server") {
+            (BuildStage::Compile, true) => "build-",
+            (BuildStage::Compile, false) => "build-client-",
+            (BuildStage::Test, true) => "test-",
+            (BuildStage::Test, false) => "test-client-",
+        }
+    }
+}
+
 struct Task {
     scope: String,
     service: String,
     name: String,
 }
 
-pub fn run(root: &Path, config: &Config, output: &Output) -> Result<bool> {
-    let reports_root = root.join(OAV_DIR).join("reports").join("compile");
-    fs::create_dir_all(&reports_root).context("Failed to create compile reports directory")?;
-
-    let mut tasks = Vec::new();
+pub fn run(root: &Path, config: &Config, results: &ResultsStore, output: &Output) -> Result<bool> {
+    run_stage(root, config, results, output, BuildStage::Compile)
+}
 
-    if matches!(config.mode, Mode::Server | Mode::Both) {
-        tasks.extend(resolve_tasks(
-            "server",
-            &config.server_generators,
-            &SUPPORTED_SERVER_GENERATORS,
-            "build-",
-        )?);
-    }
+/// Run the `stage` service of every compiled generator. Generators without
+/// a service for the stage, such as those without tests, are skipped.
+pub(super) fn run_stage(
+    root: &Path,
+    config: &Config,
+    results: &ResultsStore,
+    output: &Output,
+    stage: BuildStage,
+) -> Result<bool> {
+    let reports_root = root.join(OAV_DIR).join("reports").join(stage.as_str());
+    fs::create_dir_all(&reports_root)
+        .with_context(|| format!("Failed to create {} reports directory", stage.as_str()))?;
 
-    if matches!(config.mode, Mode::Client | Mode::Both) {
-        tasks.extend(resolve_tasks(
-            "client",
-            &config.client_generators,
-            &SUPPORTED_CLIENT_GENERATORS,
-            "build-client-",
-        )?);
+    let (compose, compose_args) = compose_project(root, config, results)?;
+    // Compile fails for a missing service; tests are optional.
+    let (tasks, missing): (Vec<Task>, Vec<Task>) =
+        tasks(config, stage)?.into_iter().partition(|task| {
+            stage == BuildStage::Compile
+                || compose
+                    .get("services")
+                    .and_then(|services| services.get(&task.service))
+                    .is_some()
+        });
+    if !missing.is_empty() {
+        output.println(&format!(
+            "No tests to run for {}",
+            missing
+                .iter()
+                .map(|task| format!("{} {}", task.scope, task.name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
     }
+    let mut builders = if config.warm_builders && stage == BuildStage::Compile {
+        // Arguments for every compile task start the builders; a task with
+        // its own needs a container of its own.
+        let services: Vec<(&str, &str)> = tasks
+            .iter()
+            .filter(|task| {
+                config
+                    .docker_args
+                    .get(&format!("compile.{}", task.name))
+                    .is_none_or(Vec::is_empty)
+            })
+            .map(|task| (task.scope.as_str(), task.service.as_str()))
+            .collect();
+        Some(warm::Builders::start(
+            root,
+            config,
+            &compose,
+            compose_args.clone(),
+            &services,
+            &reports_root,
+            output,
+        )?)
+    } else {
+        None
+    };
 
+    let timeout = config.timeout(stage.as_str())?;
     let mut failures = 0;
     for task in tasks {
+        if results.should_stop() {
+            break;
+        }
         let report_dir = reports_root.join(&task.scope);
         fs::create_dir_all(&report_dir)?;
         let log_path = report_dir.join(format!("{}.log", task.service));
-        let project_dir = root.join(OAV_DIR);
-        let compose_path = project_dir.join("docker-compose.yaml");
-        let command_line = format!(
-            "$ docker compose -f {compose} --project-directory {project} run --rm {service}",
-            compose = compose_path.display(),
-            project = project_dir.display(),
-            service = task.service
-        );
-        write_log_header(&log_path, &command_line)?;
-
-        output.substep_start(&format!("Compile {} {}", task.scope, task.name));
-        let mut command = Command::new("docker");
-        command
-            .arg("compose")
-            .arg("-f")
-            .arg(&compose_path)
-            .arg("--project-directory")
-            .arg(&project_dir)
-            .arg("run")
-            .arg("--rm")
-            .arg(&task.service);
-
-        let success = docker::run_with_logging(&mut command, &log_path, output)?;
-        append_status(
-            root,
-            "compile",
+        // `exec` can't take run arguments such as mounts, so tasks with
+        // their own docker_args have no builder and get their own container.
+        let extra_args = config.docker_args(stage.as_str(), Some(&task.name));
+        let args = match builders
+            .as_ref()
+            .and_then(|builders| builders.exec_args(&task.service))
+        {
+            Some(args) => args,
+            None => {
+                let mut args = builders
+                    .as_ref()
+                    .map(|builders| builders.compose_args().to_vec())
+                    .unwrap_or_else(|| compose_args.clone());
+                args.extend([
+                    "run".to_string(),
+                    "--name".to_string(),
+                    docker::container_name(&task.service),
+                ]);
+                args.extend(extra_args);
+                args.push(task.service.clone());
+                args
+            }
+        };
+        results.start(stage.as_str(), &task.scope, &task.name);
+        write_log_header(
+            &log_path,
+            &format!("$ {} {}", docker::engine().binary(), args.join(" ")),
+        )?;
+
+        let label = format!("{} {} {}", stage.label(), task.scope, task.name);
+        output.substep_start(&label);
+        let mut command = docker::engine().command();
+        command.args(&args);
+
+        // `exec`'d commands have no container of their own to kill, so a
+        // timed out one takes its builder down with it.
+        let container = args
+            .iter()
+            .position(|arg| arg == "--name")
+            .map(|index| args[index + 1].as_str());
+        let (status, metrics) =
+            docker::run_with_logging(&mut command, &log_path, output, container, timeout)?;
+        if status == RunStatus::TimedOut
+            && container.is_none()
+            && let Some(builders) = builders.as_mut()
+        {
+            builders.stop(&task.service);
+        }
+        let success = status.success();
+        results.record_with_metrics(
+            stage.as_str(),
             &task.scope,
             &task.name,
-            if success { "ok" } else { "fail" },
+            status.as_str(),
             &log_path,
+            metrics,
         )?;
-        output.substep_finish(&format!("Compile {} {}", task.scope, task.name), success);
+        let label = status.label(&label);
+        if !success && config.warn_only(stage.as_str()) {
+            output.substep_warn(&label);
+        } else {
+            output.substep_finish(&label, success);
+        }
         if !success {
             failures += 1;
         }
     }
 
+    // Remove warm builders before the project's network.
+    drop(builders);
+    let _ = docker::engine()
+        .command()
+        .args(&compose_args)
+        .args(["down", "--remove-orphans"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+
     Ok(failures == 0)
 }
 
+/// The compose file with custom generator services added, and the `docker
+/// compose` arguments selecting it, its override file, and this run's project.
+pub(super) fn compose_project(
+    root: &Path,
+    config: &Config,
+    results: &ResultsStore,
+) -> Result<(Value, Vec<String>)> {
+    let project_dir = root.join(OAV_DIR);
+    let compose_path = project_dir.join("docker-compose.yaml");
+    let mut compose = load_compose(&compose_path)?;
+    let custom = add_custom_services(root, config, &mut compose)?;
+    let override_path = write_cache_override(root, config, &compose, &custom)?;
+    let compose_args = vec![
+        "compose".to_string(),
+        "-f".to_string(),
+        compose_path.display().to_string(),
+        "-f".to_string(),
+        override_path.display().to_string(),
+        "--project-directory".to_string(),
+        project_dir.display().to_string(),
+        "-p".to_string(),
+        project_name(root, results),
+    ];
+    Ok((compose, compose_args))
+}
+
+/// Compose project for this run. The project otherwise defaults to the
+/// `.oav` directory name, which concurrent runs and other checkouts share.
+pub(super) fn project_name(root: &Path, results: &ResultsStore) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(root.to_string_lossy().as_bytes());
+    hasher.update(results.run_id().as_bytes());
+    format!("oav-{}", &format!("{:x}", hasher.finalize())[..12])
+}
+
+pub(super) fn load_compose(path: &Path) -> Result<Value> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_yaml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Build services of custom generators with an `image` and `command`, added
+/// to `compose` unless it defines a service of that name already. Returns
+/// the names added, which the override file has to define in full.
+fn add_custom_services(root: &Path, config: &Config, compose: &mut Value) -> Result<Vec<String>> {
+    let mut added = Vec::new();
+    let Some(services) = compose
+        .as_mapping_mut()
+        .and_then(|compose| compose.get_mut("services"))
+        .and_then(Value::as_mapping_mut)
+    else {
+        return Ok(added);
+    };
+    for (name, generator) in &config.custom_generators {
+        let (Some(image), Some(command)) = (&generator.image, &generator.command) else {
+            continue;
+        };
+        let service = format!(
+            "{}{name}",
+            BuildStage::Compile.service_prefix(&generator.scope)
+        );
+        if services.contains_key(service.as_str()) {
+            continue;
+        }
+        let project = match output_dir(root, &root.join(&generator.config))
+            .ok()
+            .flatten()
+        {
+            Some(dir) => to_posix_path(dir.strip_prefix(root.join(OAV_DIR)).unwrap_or(&dir)),
+            None => format!("generated/{}/{name}", generator.scope),
+        };
+        let mut definition = Mapping::new();
+        definition.insert("image".into(), image.as_str().into());
+        definition.insert(
+            "volumes".into(),
+            Value::Sequence(vec![format!("./{project}:/src:rw").into()]),
+        );
+        definition.insert("working_dir".into(), "/src".into());
+        // Compose interpolates `$`, which the command means literally.
+        definition.insert(
+            "command".into(),
+            Value::Sequence(vec![
+                "sh".into(),
+                "-c".into(),
+                command.replace('$', "$$").into(),
+            ]),
+        );
+        services.insert(service.as_str().into(), Value::Mapping(definition));
+        added.push(service);
+    }
+    Ok(added)
+}
+
+/// Named volumes declared at the top level of the compose file.
+pub(super) fn named_volumes(compose: &Value) -> Vec<&str> {
+    compose
+        .get("volumes")
+        .and_then(Value::as_mapping)
+        .map(|volumes| volumes.keys().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Dependency caches are the named volumes declared in the compose file
+/// (`m2-cache`, `npm-cache`, ...), shared across runs by default. With
+/// `compile_cache: false` they are replaced by anonymous volumes that are
+/// discarded with the container; with `compile_cache_dir` they are
+/// bind-mounted from `<dir>/<name>` (`m2`, `npm`, ...). Returns `None` when
+/// the named volume should be used as declared.
+pub(super) fn cache_mount(
+    root: &Path,
+    config: &Config,
+    volume: &str,
+    target: &str,
+) -> Result<Option<String>> {
+    if !config.compile_cache {
+        return Ok(Some(target.to_string()));
+    }
+    // Host directories can't be mounted into a remote daemon.
+    let Some(dir) = config
+        .compile_cache_dir
+        .as_ref()
+        .filter(|_| remote::active().is_none())
+    else {
+        return Ok(None);
+    };
+    let host = root
+        .join(dir)
+        .join(volume.strip_suffix("-cache").unwrap_or(volume));
+    fs::create_dir_all(&host).with_context(|| format!("Failed to create {}", host.display()))?;
+    Ok(Some(format!("{}:{target}", to_posix_path(&host))))
+}
+
+/// Compose merges service volumes by target path, so an override file is
+/// enough to swap the cache mounts of every build service, and on a remote
+/// engine to move project mounts onto the workspace volume. The override also
+/// pins cache volume names to what the former fixed `oav` project used, so
+/// caches survive per-run project names, and sets the configured `platform`,
+/// which `compose run` has no flag for, `container_user`, resource limits,
+/// the container environment, and SELinux labels on bind mounts. `custom`
+/// services aren't in the compose file and are written in full.
+fn write_cache_override(
+    root: &Path,
+    config: &Config,
+    compose: &Value,
+    custom: &[String],
+) -> Result<PathBuf> {
+    let override_path = root.join(OAV_DIR).join(CACHE_OVERRIDE_FILE);
+    let named_volumes = named_volumes(compose);
+
+    let mut volumes = Mapping::new();
+    for volume in &named_volumes {
+        let declared_name = compose
+            .get("volumes")
+            .and_then(|volumes| volumes.get(*volume))
+            .and_then(|definition| definition.get("name"));
+        if declared_name.is_none() {
+            let mut definition = Mapping::new();
+            definition.insert("name".into(), format!("{VOLUME_PREFIX}{volume}").into());
+            volumes.insert((*volume).into(), Value::Mapping(definition));
+        }
+    }
+
+    remote::declare_compose_volume(&mut volumes);
+
+    let mut services = Mapping::new();
+    let customized = !config.compile_cache || config.compile_cache_dir.is_some();
+    let environment = docker::compose_env(config);
+    for (service, definition) in compose
+        .get("services")
+        .and_then(Value::as_mapping)
+        .into_iter()
+        .flatten()
+    {
+        let mut mounts = Vec::new();
+        for volume in definition
+            .get("volumes")
+            .and_then(Value::as_sequence)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            let Some((source, target)) = volume.split_once(':') else {
+                continue;
+            };
+            if source.starts_with("./") && remote::active().is_some() {
+                mounts.push(remote::compose_volume(volume));
+                continue;
+            }
+            if !named_volumes.contains(&source) {
+                let labeled = docker::label_mount(config, volume);
+                if labeled != volume {
+                    mounts.push(Value::String(labeled));
+                }
+                continue;
+            }
+            let target = target.split(':').next().unwrap_or(target);
+            if customized && let Some(mount) = cache_mount(root, config, source, target)? {
+                mounts.push(Value::String(docker::label_mount(config, &mount)));
+            }
+        }
+        let mut overrides = match definition.as_mapping() {
+            Some(definition) if custom.iter().any(|name| service.as_str() == Some(name)) => {
+                definition.clone()
+            }
+            _ => Mapping::new(),
+        };
+        if !mounts.is_empty() {
+            overrides.insert("volumes".into(), Value::Sequence(mounts));
+        }
+        if let Some(platform) = &config.platform {
+            overrides.insert("platform".into(), platform.clone().into());
+        }
+        if let Some(user) = docker::explicit_user(config) {
+            overrides.insert("user".into(), user.into());
+        }
+        docker::compose_resources(config, &mut overrides);
+        if !environment.is_empty() {
+            overrides.insert("environment".into(), environment_value(&environment));
+        }
+        if !overrides.is_empty() {
+            services.insert(service.clone(), Value::Mapping(overrides));
+        }
+    }
+
+    let mut document = Mapping::new();
+    if !services.is_empty() {
+        document.insert("services".into(), Value::Mapping(services));
+    }
+    if !volumes.is_empty() {
+        document.insert("volumes".into(), Value::Mapping(volumes));
+    }
+    let yaml = serde_yaml::to_string(&document).context("Failed to serialize cache override")?;
+    write_atomic(
+        &override_path,
+        &format!("# Generated by oav from compile_cache settings; do not edit.\n{yaml}"),
+    )?;
+    Ok(override_path)
+}
+
+pub(super) fn environment_value(environment: &[String]) -> Value {
+    Value::Sequence(environment.iter().cloned().map(Value::String).collect())
+}
+
+fn tasks(config: &Config, stage: BuildStage) -> Result<Vec<Task>> {
+    let mut tasks = Vec::new();
+
+    if matches!(config.mode, Mode::Server | Mode::Both) {
+        tasks.extend(resolve_tasks(
+            "server",
+            &compiled(
+                "compile_server_generators",
+                config.compile_server_generators.as_deref(),
+                &config.server_generators,
+            )?,
+            &SUPPORTED_SERVER_GENERATORS,
+            config,
+            stage,
+        )?);
+    }
+
+    if matches!(config.mode, Mode::Client | Mode::Both) {
+        tasks.extend(resolve_tasks(
+            "client",
+            &compiled(
+                "compile_client_generators",
+                config.compile_client_generators.as_deref(),
+                &config.client_generators,
+            )?,
+            &SUPPORTED_CLIENT_GENERATORS,
+            config,
+            stage,
+        )?);
+    }
+    Ok(tasks)
+}
+
+/// Generators to compile: the `compile_*_generators` list if set, else the
+/// generated ones. Each must also be generated, or there is nothing to build.
+fn compiled(key: &str, compile: Option<&[String]>, generated: &[String]) -> Result<Vec<String>> {
+    let Some(compile) = compile else {
+        return Ok(generated.to_vec());
+    };
+    if !generated.is_empty()
+        && let Some(name) = compile
+            .iter()
+            .map(|name| name.trim())
+            .find(|name| !generated.iter().any(|generated| generated.trim() == *name))
+    {
+        bail!("{key} lists {name}, which is not generated");
+    }
+    Ok(compile.to_vec())
+}
+
+/// Images of the services `stage` will run. Services built from a
+/// Dockerfile have no image to pull and are skipped.
+pub(super) fn service_images(
+    root: &Path,
+    config: &Config,
+    stage: BuildStage,
+) -> Result<Vec<String>> {
+    let mut compose = load_compose(&root.join(OAV_DIR).join("docker-compose.yaml"))?;
+    add_custom_services(root, config, &mut compose)?;
+    Ok(tasks(config, stage)?
+        .iter()
+        .filter_map(|task| {
+            compose
+                .get("services")?
+                .get(&task.service)?
+                .get("image")?
+                .as_str()
+                .map(str::to_string)
+        })
+        .collect())
+}
+
+/// `stage` tasks for `requested`, or every supported generator of `scope`.
+/// Custom generators compile in their `service`, or the one oav adds for
+/// their `command`; without either they have nothing to compile. They have
+/// no tests.
 fn resolve_tasks(
     scope: &str,
     requested: &[String],
     supported: &[&str],
-    prefix: &str,
+    config: &Config,
+    stage: BuildStage,
 ) -> Result<Vec<Task>> {
+    let prefix = stage.service_prefix(scope);
     let names: Vec<String> = if !requested.is_empty() {
         let filtered: Vec<String> = requested
             .iter()
@@ -126,12 +582,24 @@ fn resolve_tasks(
 
     let mut tasks = Vec::new();
     for name in names {
-        if !supported.contains(&name.as_str()) {
-            bail!("Unsupported {scope} generator for compile: {name}");
-        }
+        let service = if supported.contains(&name.as_str()) {
+            format!("{prefix}{name}")
+        } else {
+            match config.custom_generators.get(&name) {
+                Some(custom) if custom.scope == scope && stage == BuildStage::Test => continue,
+                Some(custom) if custom.scope == scope => match &custom.service {
+                    Some(service) => service.clone(),
+                    None if custom.command.is_some() => format!("{prefix}{name}"),
+                    None => continue,
+                },
+                _ => bail!(
+                    "Unsupported {scope} generator for compile: {name} (declare it under custom_generators)"
+                ),
+            }
+        };
         tasks.push(Task {
             scope: scope.to_string(),
-            service: format!("{prefix}{name}"),
+            service,
             name,
         });
     }
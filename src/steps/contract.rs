@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use serde_yaml::Value;
+use std::fs;
+use std::path::Path;
+
+use super::compile;
+use super::smoke::{self, Server};
+use crate::config::Config;
+use crate::docker::{self, RunStatus};
+use crate::output::Output;
+use crate::remote;
+use crate::results::ResultsStore;
+use crate::rules;
+use crate::util::{OAV_DIR, append_error, to_posix_path, write_log_header};
+
+/// Run Schemathesis with the spec against `contract_url`, or, without one,
+/// against each generated server booted in its smoke service.
+pub fn run(
+    root: &Path,
+    spec_path: &Path,
+    config: &Config,
+    results: &ResultsStore,
+    output: &Output,
+) -> Result<bool> {
+    let reports_dir = root.join(OAV_DIR).join("reports").join("contract");
+    fs::create_dir_all(&reports_dir).context("Failed to create contract reports directory")?;
+
+    if let Some(url) = &config.contract_url {
+        let log_path = reports_dir.join("schemathesis.log");
+        let label = format!("Contract {url}");
+        output.substep_start(&label);
+        results.start("contract", "spec", "schemathesis");
+        let status = schemathesis(root, spec_path, config, None, url, None, &log_path, output)?;
+        return finish(
+            config,
+            results,
+            output,
+            "spec",
+            "schemathesis",
+            &label,
+            status,
+            &log_path,
+        );
+    }
+
+    let report_dir = reports_dir.join("server");
+    fs::create_dir_all(&report_dir)?;
+    let (compose, compose_args) = compile::compose_project(root, config, results)?;
+    let (servers, missing) = smoke::servers(config, &compose);
+    if !missing.is_empty() {
+        output.println(&format!("No contract test for {}", missing.join(", ")));
+    }
+    let network = format!("{}_default", compile::project_name(root, results));
+    let base_path = base_path(&rules::load_document(&root.join(spec_path))?);
+    let ready_timeout = smoke::ready_timeout(config)?;
+
+    let mut success = true;
+    for name in servers {
+        if results.should_stop() {
+            break;
+        }
+        let log_path = report_dir.join(format!("{name}.log"));
+        let label = format!("Contract server {name}");
+        output.substep_start(&label);
+        results.start("contract", "server", &name);
+        let status = match Server::start(&compose, &compose_args, config, &name, &log_path, output)?
+        {
+            Some(server) => {
+                let mut status = server.wait_ready(
+                    &compose_args,
+                    &config.smoke_path,
+                    ready_timeout,
+                    &log_path,
+                )?;
+                if status.success() {
+                    let url = format!("{}{base_path}", server.origin);
+                    status = schemathesis(
+                        root,
+                        spec_path,
+                        config,
+                        Some(&name),
+                        &url,
+                        Some(&network),
+                        &log_path,
+                        output,
+                    )?;
+                } else {
+                    append_error(&log_path, "Server not ready; Schemathesis not run")?;
+                }
+                server.stop(&log_path, output)?;
+                status
+            }
+            None => RunStatus::Failed,
+        };
+        success &= finish(
+            config, results, output, "server", &name, &label, status, &log_path,
+        )?;
+    }
+    smoke::down(&compose_args);
+    Ok(success)
+}
+
+/// Record and print the result of one contract run.
+#[allow(clippy::too_many_arguments)]
+fn finish(
+    config: &Config,
+    results: &ResultsStore,
+    output: &Output,
+    scope: &str,
+    target: &str,
+    label: &str,
+    status: RunStatus,
+    log_path: &Path,
+) -> Result<bool> {
+    let success = status.success();
+    results.record("contract", scope, target, status.as_str(), log_path)?;
+    let label = status.label(label);
+    if !success && config.warn_only("contract") {
+        output.substep_warn(&label);
+    } else {
+        output.substep_finish(&label, success);
+    }
+    Ok(success)
+}
+
+/// Run `schemathesis run` for the spec against `url`, on `network` if given.
+#[allow(clippy::too_many_arguments)]
+fn schemathesis(
+    root: &Path,
+    spec_path: &Path,
+    config: &Config,
+    generator: Option<&str>,
+    url: &str,
+    network: Option<&str>,
+    log_path: &Path,
+    output: &Output,
+) -> Result<RunStatus> {
+    let container = docker::container_name("contract-schemathesis");
+    let mut args: Vec<String> = vec!["run".to_string(), "--name".to_string(), container.clone()];
+    args.extend(docker::user_args(config));
+    args.extend(docker::platform_args(config.platform.as_deref()));
+    args.extend(docker::env_args(config));
+    args.extend(docker::resource_args(config));
+    if let Some(network) = network {
+        args.extend(["--network".to_string(), network.to_string()]);
+    }
+    args.extend(config.docker_args("contract", generator));
+    args.extend([
+        "-v".to_string(),
+        remote::work_mount(root, config),
+        "-w".to_string(),
+        format!("/work/{OAV_DIR}"),
+        config.schemathesis_image.clone(),
+        "run".to_string(),
+        format!("/work/{}", to_posix_path(spec_path)),
+        "--base-url".to_string(),
+        url.to_string(),
+    ]);
+    args.extend(config.contract_args.iter().cloned());
+
+    let command_line = format!("$ {} {}", docker::engine().binary(), args.join(" "));
+    if network.is_some() {
+        append_error(log_path, &format!("\n{command_line}"))?;
+    } else {
+        write_log_header(log_path, &command_line)?;
+    }
+    let mut command = docker::engine().command();
+    command.args(&args);
+    let (status, _) = docker::run_with_logging(
+        &mut command,
+        log_path,
+        output,
+        Some(&container),
+        config.timeout("contract")?,
+    )?;
+    Ok(status)
+}
+
+/// Path of the spec's first server URL (`/v1` for `https://api.example.com/v1`),
+/// which generated servers serve under.
+fn base_path(doc: &Value) -> String {
+    let Some(url) = doc
+        .get("servers")
+        .and_then(Value::as_sequence)
+        .and_then(|servers| servers.first())
+        .and_then(|server| server.get("url"))
+        .and_then(Value::as_str)
+    else {
+        return String::new();
+    };
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("", |index| &rest[index..]),
+        None => url,
+    };
+    path.trim_end_matches('/').to_string()
+}
@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use super::generate::output_dir;
+use crate::results::ResultsStore;
+use crate::util::write_log_header;
+
+const MAX_SCANNED_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Record whether a generator emitted code for the spec's webhook and callback
+/// operations. Several generators silently drop these sections, so a target
+/// that generated successfully is flagged with a `warn` status when any are missing.
+pub fn record(
+    root: &Path,
+    scope: &str,
+    name: &str,
+    config_path: &Path,
+    operations: &[String],
+    report_dir: &Path,
+    results: &ResultsStore,
+) -> Result<()> {
+    if operations.is_empty() {
+        return Ok(());
+    }
+    let log_path = report_dir.join(format!("{name}.coverage.log"));
+    write_log_header(
+        &log_path,
+        &format!("$ oav coverage {scope} {name} (webhooks and callbacks)"),
+    )?;
+
+    let generated = generated_text(root, config_path)?;
+    let (covered, missing): (Vec<&String>, Vec<&String>) = operations
+        .iter()
+        .partition(|operation| generated.contains(&normalize(operation)));
+
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(&log_path)
+        .context("Failed to open coverage log")?;
+    for operation in &covered {
+        writeln!(file, "covered: {operation}")?;
+    }
+    for operation in &missing {
+        writeln!(file, "missing: {operation}")?;
+    }
+
+    results.record(
+        "coverage",
+        scope,
+        name,
+        if missing.is_empty() { "ok" } else { "warn" },
+        &log_path,
+    )
+}
+
+/// Concatenate the normalized text of every generated file for a generator config.
+fn generated_text(root: &Path, config_path: &Path) -> Result<String> {
+    let Some(output_dir) = output_dir(root, config_path)? else {
+        return Ok(String::new());
+    };
+
+    let mut text = String::new();
+    let walker = walkdir::WalkDir::new(output_dir).follow_links(false);
+    for entry in walker.into_iter().filter_map(Result::ok) {
+        if !entry.file_type().is_file()
+            || entry
+                .metadata()
+                .map(|meta| meta.len() > MAX_SCANNED_FILE_SIZE)
+                .unwrap_or(true)
+        {
+            continue;
+        }
+        if let Ok(bytes) = fs::read(entry.path()) {
+            text.push_str(&normalize(&String::from_utf8_lossy(&bytes)));
+            text.push('\n');
+        }
+    }
+    Ok(text)
+}
+
+/// Lowercase and strip separators so `onPetCreated`, `on_pet_created` and
+/// `OnPetCreated` all match.
+fn normalize(input: &str) -> String {
+    input
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
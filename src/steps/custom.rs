@@ -0,0 +1,98 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::config::{Config, CustomStep};
+use crate::docker::{self, RunStatus};
+use crate::output::Output;
+use crate::remote;
+use crate::results::ResultsStore;
+use crate::util::{OAV_DIR, append_error, to_posix_path, write_log_header};
+
+/// Run the `custom_steps` that go after the `after` stage, in name order,
+/// recording each under the `custom` stage.
+pub fn run(
+    root: &Path,
+    spec_path: &Path,
+    config: &Config,
+    after: &str,
+    results: &ResultsStore,
+    output: &Output,
+) -> Result<bool> {
+    let reports_dir = root.join(OAV_DIR).join("reports").join("custom");
+    fs::create_dir_all(&reports_dir).context("Failed to create custom reports directory")?;
+
+    let mut success = true;
+    for (name, step) in config
+        .custom_steps
+        .iter()
+        .filter(|(_, step)| step.after == after)
+    {
+        if results.should_stop() {
+            break;
+        }
+        let log_path = reports_dir.join(format!("{name}.log"));
+        let label = format!("Step {name}");
+        output.substep_start(&label);
+        results.start("custom", "step", name);
+        let status = run_step(root, spec_path, config, name, step, &log_path, output)?;
+        results.record("custom", "step", name, status.as_str(), &log_path)?;
+        output.substep_finish(&status.label(&label), status.success());
+        success &= status.success();
+    }
+    Ok(success)
+}
+
+/// Whether any custom step goes after the `after` stage.
+pub fn any_after(config: &Config, after: &str) -> bool {
+    config.custom_steps.values().any(|step| step.after == after)
+}
+
+fn run_step(
+    root: &Path,
+    spec_path: &Path,
+    config: &Config,
+    name: &str,
+    step: &CustomStep,
+    log_path: &Path,
+    output: &Output,
+) -> Result<RunStatus> {
+    // A remote engine only sees the uploaded workspace volume.
+    if remote::active().is_some() && !step.mounts.is_empty() {
+        write_log_header(log_path, &format!("custom step {name}"))?;
+        append_error(
+            log_path,
+            "mounts are not supported with a remote container engine",
+        )?;
+        return Ok(RunStatus::Failed);
+    }
+
+    let container = docker::container_name(&format!("custom-{name}"));
+    let mut args: Vec<String> = vec!["run".to_string(), "--name".to_string(), container.clone()];
+    args.extend(docker::user_args(config));
+    args.extend(docker::platform_args(config.platform.as_deref()));
+    args.extend(docker::env_args(config));
+    args.extend(docker::resource_args(config));
+    args.extend(["-v".to_string(), remote::work_mount(root, config)]);
+    for mount in &step.mounts {
+        let (host, target) = mount.split_once(':').unwrap_or((mount, ""));
+        let host = root.join(host);
+        args.extend([
+            "-v".to_string(),
+            docker::label_mount(config, &format!("{}:{target}", host.display())),
+        ]);
+    }
+    args.extend(["-w".to_string(), "/work".to_string(), step.image.clone()]);
+    let spec = to_posix_path(spec_path);
+    args.extend(step.command.iter().map(|arg| arg.replace("{spec}", &spec)));
+
+    write_log_header(
+        log_path,
+        &format!("$ {} {}", docker::engine().binary(), args.join(" ")),
+    )?;
+    let mut command = docker::engine().command();
+    command.args(&args);
+    let (status, _) =
+        docker::run_with_logging(&mut command, log_path, output, Some(&container), None)?;
+    Ok(status)
+}
@@ -0,0 +1,183 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::output::Output;
+use crate::results::{MergedFinding, RunResults, StatusEntry};
+
+/// What changed from one run to another.
+struct Diff<'a> {
+    /// Tasks failing in the later run that didn't fail, or didn't run, before.
+    regressed: Vec<(Option<&'a StatusEntry>, &'a StatusEntry)>,
+    /// Tasks failing in the earlier run that ran without failing later.
+    fixed: Vec<(&'a StatusEntry, &'a StatusEntry)>,
+    new_findings: Vec<&'a MergedFinding>,
+    resolved_findings: Vec<&'a MergedFinding>,
+}
+
+/// Compare `before` with `after`, print what regressed and what was fixed,
+/// and write it as Markdown to `out`.
+pub fn run(before: &RunResults, after: &RunResults, out: &Path, output: &Output) -> Result<()> {
+    let diff = compare(before, after);
+    print(before, after, &diff);
+    if let Some(parent) = out.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(out, markdown(before, after, &diff))
+        .with_context(|| format!("Failed to write {}", out.display()))?;
+    output.println_always(&format!("Diff: {}", out.display()));
+    Ok(())
+}
+
+fn compare<'a>(before: &'a RunResults, after: &'a RunResults) -> Diff<'a> {
+    let earlier = |entry: &StatusEntry| {
+        before.entries.iter().rev().find(|other| {
+            other.stage == entry.stage && other.scope == entry.scope && other.target == entry.target
+        })
+    };
+    let mut regressed = Vec::new();
+    let mut fixed = Vec::new();
+    for entry in &after.entries {
+        match earlier(entry) {
+            Some(old) if old.failed() && !entry.failed() => fixed.push((old, entry)),
+            Some(old) if !old.failed() && entry.failed() => regressed.push((Some(old), entry)),
+            None if entry.failed() => regressed.push((None, entry)),
+            _ => {}
+        }
+    }
+    let same = |a: &MergedFinding, b: &MergedFinding| a.rule == b.rule && a.path == b.path;
+    Diff {
+        regressed,
+        fixed,
+        new_findings: after
+            .findings
+            .iter()
+            .filter(|finding| !before.findings.iter().any(|old| same(old, finding)))
+            .collect(),
+        resolved_findings: before
+            .findings
+            .iter()
+            .filter(|finding| !after.findings.iter().any(|new| same(new, finding)))
+            .collect(),
+    }
+}
+
+fn headline(diff: &Diff) -> String {
+    format!(
+        "{} regressed, {} fixed, {} new finding(s), {} resolved finding(s)",
+        diff.regressed.len(),
+        diff.fixed.len(),
+        diff.new_findings.len(),
+        diff.resolved_findings.len()
+    )
+}
+
+fn task(entry: &StatusEntry) -> String {
+    format!("{} {} {}", entry.stage, entry.scope, entry.target)
+}
+
+fn finding(finding: &MergedFinding) -> String {
+    format!(
+        "{}[{}] {}: {}",
+        finding.severity, finding.rule, finding.path, finding.message
+    )
+}
+
+fn print(before: &RunResults, after: &RunResults, diff: &Diff) {
+    println!("{} -> {}", before.run_id, after.run_id);
+    println!("{}", headline(diff));
+    if !diff.regressed.is_empty() {
+        println!("\nRegressed:");
+        for (old, new) in &diff.regressed {
+            let was = old.map_or("not run".to_string(), |old| old.status_label());
+            println!("  {}: {was} -> {}", task(new), new.status_label());
+        }
+    }
+    if !diff.fixed.is_empty() {
+        println!("\nFixed:");
+        for (old, new) in &diff.fixed {
+            println!(
+                "  {}: {} -> {}",
+                task(new),
+                old.status_label(),
+                new.status_label()
+            );
+        }
+    }
+    for (title, findings) in [
+        ("New findings", &diff.new_findings),
+        ("Resolved findings", &diff.resolved_findings),
+    ] {
+        if !findings.is_empty() {
+            println!("\n{title}:");
+            for item in findings.iter() {
+                println!("  {}", finding(item));
+            }
+        }
+    }
+}
+
+/// The diff as Markdown, to post on a pull request.
+fn markdown(before: &RunResults, after: &RunResults, diff: &Diff) -> String {
+    let mut md = format!(
+        "## OpenAPI validation changes\n\n`{}` → `{}`: {}\n",
+        before.run_id,
+        after.run_id,
+        headline(diff)
+    );
+    if !diff.regressed.is_empty() {
+        md.push_str("\n### Regressed\n\n| Stage | Scope | Target | Before | After |\n|---|---|---|---|---|\n");
+        for (old, new) in &diff.regressed {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                cell(&new.stage),
+                cell(&new.scope),
+                cell(&new.target),
+                old.map_or("not run".to_string(), |old| cell(&old.status_label())),
+                cell(&new.status_label())
+            ));
+        }
+    }
+    if !diff.fixed.is_empty() {
+        md.push_str(
+            "\n### Fixed\n\n| Stage | Scope | Target | Before | After |\n|---|---|---|---|---|\n",
+        );
+        for (old, new) in &diff.fixed {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                cell(&new.stage),
+                cell(&new.scope),
+                cell(&new.target),
+                cell(&old.status_label()),
+                cell(&new.status_label())
+            ));
+        }
+    }
+    for (title, findings) in [
+        ("New findings", &diff.new_findings),
+        ("Resolved findings", &diff.resolved_findings),
+    ] {
+        if findings.is_empty() {
+            continue;
+        }
+        md.push_str(&format!(
+            "\n### {title}\n\n| Severity | Rule | Path | Message |\n|---|---|---|---|\n"
+        ));
+        for finding in findings.iter() {
+            md.push_str(&format!(
+                "| {} | {} | `{}` | {} |\n",
+                cell(&finding.severity),
+                cell(&finding.rule),
+                cell(&finding.path),
+                cell(&finding.message)
+            ));
+        }
+    }
+    md
+}
+
+/// `text` fit for a Markdown table cell.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
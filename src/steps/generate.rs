@@ -1,111 +1,387 @@
 use anyhow::{Context, Result, bail};
+use serde_yaml::{Mapping, Value};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 
+use super::cache::{self, GenerateCache};
+use super::coverage;
 use crate::cli::Mode;
 use crate::config::Config;
 use crate::docker;
+use crate::downconvert::{self, Conversion};
 use crate::output::Output;
-use crate::util::{OAV_DIR, append_error, append_status, to_posix_path, write_log_header};
+use crate::remote;
+use crate::results::ResultsStore;
+use crate::rules;
+use crate::util::{OAV_DIR, append_error, copy_dir, to_posix_path, write_atomic, write_log_header};
 
-pub fn run(root: &Path, spec_path: &Path, config: &Config, output: &Output) -> Result<bool> {
-    let reports_root = root.join(OAV_DIR).join("reports").join("generate");
-    let server_dir = root.join(OAV_DIR).join("generators").join("server");
-    let client_dir = root.join(OAV_DIR).join("generators").join("client");
+/// Directory under `.oav` for generator configs with `generator_properties` applied.
+const EFFECTIVE_DIR: &str = "effective";
 
-    let mut failures = 0;
+struct Generation<'a> {
+    root: &'a Path,
+    spec_path: &'a Path,
+    conversion: Option<&'a Conversion>,
+    config: &'a Config,
+    event_operations: Vec<String>,
+    reports_root: PathBuf,
+    cache: Option<GenerateCache>,
+    /// Label of a `generator_matrix` run, appended to target names.
+    variant: Option<String>,
+    results: &'a ResultsStore,
+    output: &'a Output,
+}
+
+pub fn run(
+    root: &Path,
+    spec_path: &Path,
+    conversion: Option<&Conversion>,
+    config: &Config,
+    use_cache: bool,
+    results: &ResultsStore,
+    output: &Output,
+) -> Result<bool> {
+    let event_operations = rules::load_document(&root.join(spec_path))
+        .map(|doc| rules::event_operations(&doc))
+        .unwrap_or_default();
 
-    if matches!(config.mode, Mode::Server | Mode::Both) {
-        if !run_for_scope(
+    // Other generator versions only generate; compile builds the output of
+    // `generator_image`.
+    let mut runs = vec![(None, config.clone())];
+    runs.extend(config.matrix_images().into_iter().map(|(label, image)| {
+        let variant = Config {
+            generator_image: image,
+            ..config.clone()
+        };
+        (Some(label), variant)
+    }));
+
+    let mut failures = 0;
+    for (variant, config) in &runs {
+        if results.should_stop() {
+            break;
+        }
+        let mut generation = Generation {
             root,
             spec_path,
-            &config.generator_image,
-            "server",
-            &server_dir,
-            &config.server_generators,
-            &config.generator_overrides,
-            &reports_root,
+            conversion,
+            config,
+            event_operations: event_operations.clone(),
+            reports_root: root.join(OAV_DIR).join("reports").join("generate"),
+            cache: use_cache.then(|| GenerateCache::load(root)),
+            variant: variant.clone(),
+            results,
             output,
-        )? {
+        };
+        if matches!(config.mode, Mode::Server | Mode::Both)
+            && !generation.run_scope("server", &config.server_generators)?
+        {
+            failures += 1;
+        }
+        if matches!(config.mode, Mode::Client | Mode::Both)
+            && !results.should_stop()
+            && !generation.run_scope("client", &config.client_generators)?
+        {
             failures += 1;
         }
     }
 
-    if matches!(config.mode, Mode::Client | Mode::Both) {
-        if !run_for_scope(
-            root,
-            spec_path,
-            &config.generator_image,
+    Ok(failures == 0)
+}
+
+/// Directory a generator config writes to, from its `outputDir` (relative to `.oav`).
+pub fn output_dir(root: &Path, config_path: &Path) -> Result<Option<PathBuf>> {
+    let content = fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read {}", config_path.display()))?;
+    let config: serde_yaml::Value = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", config_path.display()))?;
+    Ok(config
+        .get("outputDir")
+        .and_then(|dir| dir.as_str())
+        .map(|dir| root.join(OAV_DIR).join(dir)))
+}
+
+/// File openapi-generator writes once it has finished generating into a
+/// config's `outputDir`: the `.openapi-generator/FILES` list of generated
+/// files.
+fn generated_marker(root: &Path, config_path: &Path) -> Result<Option<PathBuf>> {
+    Ok(output_dir(root, config_path)?.map(|dir| dir.join(".openapi-generator").join("FILES")))
+}
+
+/// Scope (`server` or `client`) that generates `name`: the scope whose
+/// generator list names it, else the scope of a custom generator of that
+/// name, else the one with a generator config of that name.
+pub fn scope_of(root: &Path, config: &Config, name: &str) -> Option<&'static str> {
+    let scopes = [
+        ("server", &config.server_generators),
+        ("client", &config.client_generators),
+    ];
+    scopes
+        .iter()
+        .find(|(_, listed)| listed.iter().any(|listed| listed.trim() == name))
+        .or_else(|| {
+            let custom = config.custom_generators.get(name)?;
+            scopes.iter().find(|(scope, _)| *scope == custom.scope)
+        })
+        .or_else(|| {
+            scopes.iter().find(|(scope, _)| {
+                let file = format!("{name}.yaml");
+                root.join(OAV_DIR)
+                    .join("generators")
+                    .join(scope)
+                    .join(&file)
+                    .is_file()
+                    || config
+                        .generator_config_dir(scope)
+                        .is_some_and(|dir| root.join(dir).join(&file).is_file())
+            })
+        })
+        .map(|(scope, _)| *scope)
+}
+
+/// Copy the output of every generator that passed in this run to
+/// `<out>/<scope>/<name>`, replacing earlier copies. Returns the directories
+/// written.
+pub fn export(
+    root: &Path,
+    config: &Config,
+    results: &ResultsStore,
+    out: &Path,
+) -> Result<Vec<PathBuf>> {
+    let run = results.snapshot();
+    let mut exported = Vec::new();
+    for (scope, enabled, requested) in [
+        (
+            "server",
+            matches!(config.mode, Mode::Server | Mode::Both),
+            &config.server_generators,
+        ),
+        (
             "client",
-            &client_dir,
+            matches!(config.mode, Mode::Client | Mode::Both),
             &config.client_generators,
-            &config.generator_overrides,
-            &reports_root,
-            output,
-        )? {
-            failures += 1;
+        ),
+    ] {
+        if !enabled {
+            continue;
+        }
+        let config_dir = root.join(OAV_DIR).join("generators").join(scope);
+        let Ok(configs) = resolve_configs(
+            root,
+            &config_dir,
+            requested,
+            &config.generator_config_paths(),
+            config.generator_config_dir(scope).as_deref(),
+        ) else {
+            continue;
+        };
+        for (name, config_path) in configs {
+            let passed = run.entries.iter().any(|entry| {
+                entry.stage == "generate"
+                    && entry.scope == scope
+                    && entry.target == name
+                    && entry.passed()
+            });
+            if !passed {
+                continue;
+            }
+            let Some(source) = output_dir(root, &config_path)?.filter(|dir| dir.is_dir()) else {
+                continue;
+            };
+            let destination = out.join(scope).join(&name);
+            if destination.exists() {
+                fs::remove_dir_all(&destination)
+                    .with_context(|| format!("Failed to remove {}", destination.display()))?;
+            }
+            copy_dir(&source, &destination)?;
+            exported.push(destination);
         }
     }
+    Ok(exported)
+}
 
-    Ok(failures == 0)
+struct Target {
+    name: String,
+    /// `name`, or `name@<label>` in a matrix run; names logs and results.
+    id: String,
+    config_path: PathBuf,
+    fingerprint: Option<String>,
 }
 
-fn run_for_scope(
-    root: &Path,
-    spec_path: &Path,
-    generator_image: &str,
-    scope: &str,
-    config_dir: &Path,
-    requested: &[String],
-    overrides: &HashMap<String, String>,
-    reports_root: &Path,
-    output: &Output,
-) -> Result<bool> {
-    let report_dir = reports_root.join(scope);
-    fs::create_dir_all(&report_dir).context("Failed to create generate report directory")?;
-    let error_log = report_dir.join("_errors.log");
-
-    let configs = match resolve_configs(root, config_dir, requested, overrides) {
-        Ok(configs) => configs,
-        Err(err) => {
-            append_error(&error_log, &err.to_string())?;
-            append_status(root, "generate", scope, "_config_", "fail", &error_log)?;
-            return Ok(false);
+fn container_path(root: &Path, path: &Path) -> Result<String> {
+    let relative = path
+        .strip_prefix(root)
+        .context("Generator config path is outside repository")?;
+    Ok(format!("/work/{}", to_posix_path(relative)))
+}
+
+impl Generation<'_> {
+    /// Target names are suffixed with `@<label>` in matrix runs.
+    fn target_id(&self, name: &str) -> String {
+        match &self.variant {
+            Some(label) => format!("{name}@{label}"),
+            None => name.to_string(),
         }
-    };
+    }
 
-    let mut failures = 0;
-    for (name, config_path) in configs {
-        let name = name.as_str();
-        let log_path = report_dir.join(format!("{name}.log"));
-        let config_rel = config_path
-            .strip_prefix(root)
-            .context("Generator config path is outside repository")?;
-        let container_config = format!("/work/{}", to_posix_path(config_rel));
-        let container_spec = format!("/work/{}", to_posix_path(spec_path));
+    /// The spec `generator` reads: the 3.0 conversion if `downconvert` names it.
+    fn spec_for(&self, generator: &str) -> &Path {
+        downconvert::spec_for(
+            self.conversion,
+            self.spec_path,
+            self.config,
+            "generate",
+            Some(generator),
+        )
+    }
+
+    fn run_scope(&mut self, scope: &str, requested: &[String]) -> Result<bool> {
+        let root = self.root;
+        let config_dir = root.join(OAV_DIR).join("generators").join(scope);
+        let output = self.output;
+        let report_dir = self.reports_root.join(scope);
+        fs::create_dir_all(&report_dir).context("Failed to create generate report directory")?;
+        let error_log = report_dir.join("_errors.log");
+
+        let configs = match resolve_configs(
+            root,
+            &config_dir,
+            requested,
+            &self.config.generator_config_paths(),
+            self.config.generator_config_dir(scope).as_deref(),
+        )
+        .and_then(|configs| {
+            effective_configs(root, self.config, self.variant.as_deref(), scope, configs)
+        }) {
+            Ok(configs) => configs,
+            Err(err) => {
+                append_error(&error_log, &err.to_string())?;
+                self.results
+                    .record("generate", scope, "_config_", "fail", &error_log)?;
+                return Ok(false);
+            }
+        };
+
+        let mut pending = Vec::new();
+        for (name, config_path) in configs {
+            let id = self.target_id(&name);
+            let log_path = report_dir.join(format!("{id}.log"));
+            let fingerprint = match &self.cache {
+                Some(_) => Some(cache::fingerprint(
+                    root,
+                    self.spec_for(&name),
+                    &config_path,
+                    self.config
+                        .templates
+                        .get(&name)
+                        .map(|dir| root.join(dir))
+                        .as_deref(),
+                    &self.config.generator_image,
+                )?),
+                None => None,
+            };
+            if let (Some(cache), Some(fingerprint)) = (&self.cache, &fingerprint)
+                && cache.is_fresh(&format!("{scope}/{id}"), fingerprint)
+                && log_path.is_file()
+                && output_dir(root, &config_path)?.is_some_and(|dir| dir.is_dir())
+                // Rerun under `strict` so warnings fail the target.
+                && (!self.config.strict || log_warnings(&log_path).is_empty())
+            {
+                let label = format!("Generate {scope} {id}");
+                self.results
+                    .record("generate", scope, &id, "cached", &log_path)?;
+                self.record_warnings(scope, &id, &log_path)?;
+                output.substep_start(&label);
+                output.substep_finish(&format!("{label} (cached)"), true);
+                self.record_coverage(scope, &id, &config_path, &report_dir)?;
+                continue;
+            }
+            pending.push(Target {
+                name,
+                id,
+                config_path,
+                fingerprint,
+            });
+        }
+
+        // Generators with their own docker_args need their own container.
+        let (batchable, mut individual): (Vec<Target>, Vec<Target>) =
+            pending.into_iter().partition(|target| {
+                !self
+                    .config
+                    .docker_args
+                    .contains_key(&format!("generate.{}", target.name))
+            });
+        // A batch's warnings can't be told apart per target, which `strict` needs.
+        let unfinished = if self.config.generate_batch && !self.config.strict && batchable.len() > 1
+        {
+            self.run_batch(scope, batchable, &report_dir)?
+        } else {
+            batchable
+        };
+        individual.extend(unfinished);
+
+        let mut failures = 0;
+        for target in &individual {
+            if self.results.should_stop() {
+                break;
+            }
+            if !self.run_target(scope, target, &report_dir)? {
+                failures += 1;
+            }
+        }
+        Ok(failures == 0)
+    }
+
+    fn run_target(&mut self, scope: &str, target: &Target, report_dir: &Path) -> Result<bool> {
+        let root = self.root;
+        let output = self.output;
+        let generator_image = &self.config.generator_image;
+        let name = target.name.as_str();
+        let log_path = report_dir.join(format!("{}.log", target.id));
+        let label = format!("Generate {scope} {}", target.id);
+
+        let container_config = container_path(root, &target.config_path)?;
+        let container_spec = format!("/work/{}", to_posix_path(self.spec_for(name)));
+        let extra_args = self.config.docker_args("generate", Some(name));
 
         let command_line = format!(
-            "$ docker run --rm {user} -v {root}:/work -w /work/{oav} {image} generate -i {spec} -c {config}",
-            user = docker::user_flag(),
-            root = root.display(),
+            "$ {engine} run {user} -v {mount} -w /work/{oav} {image} generate -i {spec} -c {config}",
+            engine = docker::engine().binary(),
+            user = docker::user_args(self.config)
+                .into_iter()
+                .chain(docker::platform_args(self.config.platform.as_deref()))
+                .chain(docker::resource_args(self.config))
+                .chain(docker::java_opts_args(self.config))
+                .chain(extra_args.iter().cloned())
+                .collect::<Vec<_>>()
+                .join(" "),
+            mount = remote::work_mount(root, self.config),
             oav = OAV_DIR,
             image = generator_image,
             spec = container_spec,
             config = container_config
         )
         .replace("  ", " ");
+        self.results.start("generate", scope, &target.id);
         write_log_header(&log_path, &command_line)?;
 
-        output.substep_start(&format!("Generate {scope} {name}"));
-        let mut command = Command::new("docker");
+        output.substep_start(&label);
+        let container = docker::container_name(&format!("generate-{scope}-{}", target.id));
+        let mut command = docker::engine().command();
         command
             .arg("run")
-            .arg("--rm")
-            .args(docker::user_args())
+            .arg("--name")
+            .arg(&container)
+            .args(docker::user_args(self.config))
+            .args(docker::platform_args(self.config.platform.as_deref()))
+            .args(docker::env_args(self.config))
+            .args(docker::java_opts_args(self.config))
+            .args(docker::resource_args(self.config))
+            .args(&extra_args)
             .arg("-v")
-            .arg(format!("{}:/work", root.display()))
+            .arg(remote::work_mount(root, self.config))
             .arg("-w")
             .arg(format!("/work/{OAV_DIR}"))
             .arg(generator_image)
@@ -115,29 +391,386 @@ fn run_for_scope(
             .arg("-c")
             .arg(container_config);
 
-        let success = docker::run_with_logging(&mut command, &log_path, output)?;
-        append_status(
-            root,
+        let (mut status, metrics) = docker::run_with_logging(
+            &mut command,
+            &log_path,
+            output,
+            Some(&container),
+            self.config.timeout("generate")?,
+        )?;
+        let warnings = log_warnings(&log_path);
+        if self.config.strict && status.success() && !warnings.is_empty() {
+            append_error(
+                &log_path,
+                &format!("strict: the generator logged {} warning(s)", warnings.len()),
+            )?;
+            status = docker::RunStatus::Failed;
+        }
+        let success = status.success();
+        self.results.record_with_metrics(
             "generate",
             scope,
-            name,
-            if success { "ok" } else { "fail" },
+            &target.id,
+            status.as_str(),
             &log_path,
+            metrics,
         )?;
-        output.substep_finish(&format!("Generate {scope} {name}"), success);
-        if !success {
-            failures += 1;
+        self.results
+            .record_warnings("generate", scope, &target.id, warnings)?;
+        if !success && self.config.warn_only("generate") {
+            output.substep_warn(&status.label(&label));
+        } else {
+            output.substep_finish(&status.label(&label), success);
         }
+        self.finish_target(scope, target, success, report_dir)?;
+        Ok(success)
     }
 
-    Ok(failures == 0)
+    /// Run every pending config through one `batch` invocation to pay the
+    /// generator's JVM startup once. Batch configs are the generator configs
+    /// with `inputSpec` added. Returns the targets the batch didn't generate,
+    /// unrecorded, so they are rerun individually and failures land in the
+    /// right log.
+    fn run_batch(
+        &mut self,
+        scope: &str,
+        targets: Vec<Target>,
+        report_dir: &Path,
+    ) -> Result<Vec<Target>> {
+        let root = self.root;
+        let output = self.output;
+        let generator_image = &self.config.generator_image;
+        let batch_dir = root.join(OAV_DIR).join("batch").join(scope);
+        if batch_dir.exists() {
+            fs::remove_dir_all(&batch_dir).context("Failed to clear batch directory")?;
+        }
+        fs::create_dir_all(&batch_dir).context("Failed to create batch directory")?;
+
+        let mut batch_configs = Vec::new();
+        for target in &targets {
+            let content = fs::read_to_string(&target.config_path)
+                .with_context(|| format!("Failed to read {}", target.config_path.display()))?;
+            let mut config: serde_yaml::Value = serde_yaml::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", target.config_path.display()))?;
+            let Some(mapping) = config.as_mapping_mut() else {
+                return Ok(targets);
+            };
+            mapping.insert(
+                "inputSpec".into(),
+                format!("/work/{}", to_posix_path(self.spec_for(&target.name))).into(),
+            );
+            let path = batch_dir.join(format!("{}.yaml", target.name));
+            fs::write(
+                &path,
+                serde_yaml::to_string(&config).context("Failed to serialize batch config")?,
+            )
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+            batch_configs.push(container_path(root, &path)?);
+            // Left over from an earlier run, it would pass the target off as generated.
+            if let Some(marker) = generated_marker(root, &target.config_path)?
+                && marker.exists()
+            {
+                fs::remove_file(&marker)
+                    .with_context(|| format!("Failed to remove {}", marker.display()))?;
+            }
+        }
+
+        if let Some(remote) = remote::active() {
+            remote.push(root, &[Path::new(OAV_DIR).join("batch").join(scope)])?;
+        }
+
+        let log_path = report_dir.join("_batch.log");
+        let extra_args = self.config.docker_args("generate", None);
+        let command_line = format!(
+            "$ {engine} run {user} -v {mount} -w /work/{oav} {image} batch --root-dir /work/{oav} {configs}",
+            engine = docker::engine().binary(),
+            user = docker::user_args(self.config)
+                .into_iter()
+                .chain(docker::platform_args(self.config.platform.as_deref()))
+                .chain(docker::resource_args(self.config))
+                .chain(docker::java_opts_args(self.config))
+                .chain(extra_args.iter().cloned())
+                .collect::<Vec<_>>()
+                .join(" "),
+            mount = remote::work_mount(root, self.config),
+            oav = OAV_DIR,
+            image = generator_image,
+            configs = batch_configs.join(" ")
+        )
+        .replace("  ", " ");
+        write_log_header(&log_path, &command_line)?;
+
+        let label = format!("Generate {scope} (batch of {})", targets.len());
+        output.substep_start(&label);
+        let container = docker::container_name(&format!("generate-{scope}-batch"));
+        let mut command = docker::engine().command();
+        command
+            .arg("run")
+            .arg("--name")
+            .arg(&container)
+            .args(docker::user_args(self.config))
+            .args(docker::platform_args(self.config.platform.as_deref()))
+            .args(docker::env_args(self.config))
+            .args(docker::java_opts_args(self.config))
+            .args(docker::resource_args(self.config))
+            .args(&extra_args)
+            .arg("-v")
+            .arg(remote::work_mount(root, self.config))
+            .arg("-w")
+            .arg(format!("/work/{OAV_DIR}"))
+            .arg(generator_image)
+            .arg("batch")
+            .arg("--root-dir")
+            .arg(format!("/work/{OAV_DIR}"))
+            .args(&batch_configs);
+
+        // The per-task limit applies to the batch as a whole, scaled by its size.
+        let timeout = self
+            .config
+            .timeout("generate")?
+            .map(|after| after * targets.len() as u32);
+        // Resource use is shared by every target in the batch, so none is recorded.
+        let (status, _) =
+            docker::run_with_logging(&mut command, &log_path, output, Some(&container), timeout)?;
+        let success = status.success();
+        output.substep_finish(&label, success);
+        let (generated, unfinished) = if success {
+            (targets, Vec::new())
+        } else {
+            let mut generated = Vec::new();
+            let mut unfinished = Vec::new();
+            for target in targets {
+                if self.batch_generated(&target)? {
+                    generated.push(target);
+                } else {
+                    unfinished.push(target);
+                }
+            }
+            output.println(&format!(
+                "Batch generate failed for {scope}; retrying {} individually",
+                unfinished
+                    .iter()
+                    .map(|target| target.id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+            (generated, unfinished)
+        };
+
+        for target in &generated {
+            let target_log = report_dir.join(format!("{}.log", target.id));
+            write_log_header(&target_log, &command_line)?;
+            append_error(
+                &target_log,
+                "Generated in a batch; see _batch.log for output.",
+            )?;
+            self.results
+                .record("generate", scope, &target.id, "ok", &target_log)?;
+            self.finish_target(scope, target, true, report_dir)?;
+        }
+        Ok(unfinished)
+    }
+
+    /// Whether a failed batch got as far as finishing `target`.
+    fn batch_generated(&self, target: &Target) -> Result<bool> {
+        let Some(marker) = generated_marker(self.root, &target.config_path)? else {
+            return Ok(false);
+        };
+        if let Some(remote) = remote::active()
+            && let Some(dir) = output_dir(self.root, &target.config_path)?
+            && let Ok(relative) = dir.strip_prefix(self.root)
+            // Fails if the batch never got to the target.
+            && remote.pull(self.root, relative).is_err()
+        {
+            return Ok(false);
+        }
+        Ok(marker.is_file())
+    }
+
+    fn finish_target(
+        &mut self,
+        scope: &str,
+        target: &Target,
+        success: bool,
+        report_dir: &Path,
+    ) -> Result<()> {
+        if let Some(cache) = &mut self.cache {
+            cache.update(
+                &format!("{scope}/{}", target.id),
+                target.fingerprint.clone().filter(|_| success),
+            )?;
+        }
+        if success {
+            if let Some(remote) = remote::active()
+                && let Some(dir) = output_dir(self.root, &target.config_path)?
+                && let Ok(relative) = dir.strip_prefix(self.root)
+            {
+                remote.pull(self.root, relative)?;
+            }
+            self.record_coverage(scope, &target.id, &target.config_path, report_dir)?;
+        }
+        Ok(())
+    }
+
+    fn record_warnings(&self, scope: &str, id: &str, log_path: &Path) -> Result<()> {
+        self.results
+            .record_warnings("generate", scope, id, log_warnings(log_path))
+    }
+
+    fn record_coverage(
+        &self,
+        scope: &str,
+        name: &str,
+        config_path: &Path,
+        report_dir: &Path,
+    ) -> Result<()> {
+        coverage::record(
+            self.root,
+            scope,
+            name,
+            config_path,
+            &self.event_operations,
+            report_dir,
+            self.results,
+        )
+    }
+}
+
+/// `parse_warnings` of the log at `log_path`.
+fn log_warnings(log_path: &Path) -> Vec<String> {
+    parse_warnings(&fs::read_to_string(log_path).unwrap_or_default())
+}
+
+/// Messages of the `WARN` lines openapi-generator logs, such as unsupported
+/// features and schema mapping fallbacks, without duplicates.
+fn parse_warnings(log: &str) -> Vec<String> {
+    let mut warnings: Vec<String> = Vec::new();
+    for line in log.lines() {
+        let Some((_, rest)) = line.split_once(" WARN ") else {
+            continue;
+        };
+        let message = rest
+            .split_once(" - ")
+            .map_or(rest, |(_, message)| message)
+            .trim();
+        if !message.is_empty() && !warnings.iter().any(|warning| warning == message) {
+            warnings.push(message.to_string());
+        }
+    }
+    warnings
+}
+
+/// Swap the config of each generator with `generator_properties` or
+/// `templates` for a copy under `.oav/effective` whose `additionalProperties`
+/// include the properties and whose `templateDir` points at the templates,
+/// so caching, batching, and the log all see the config that actually ran.
+/// In a matrix run every config is copied, with `outputDir` moved to
+/// `generated/matrix/<label>` so versions don't overwrite each other.
+fn effective_configs(
+    root: &Path,
+    config: &Config,
+    variant: Option<&str>,
+    scope: &str,
+    configs: Vec<(String, PathBuf)>,
+) -> Result<Vec<(String, PathBuf)>> {
+    let mut effective_dir = root.join(OAV_DIR).join(EFFECTIVE_DIR);
+    if let Some(label) = variant {
+        effective_dir = effective_dir.join("matrix").join(label);
+    }
+    let effective_dir = effective_dir.join(scope);
+    let mut written = false;
+    let mut resolved = Vec::new();
+    for (name, path) in configs {
+        let properties = config.generator_properties.get(&name);
+        let templates = config.templates.get(&name);
+        if properties.is_none() && templates.is_none() && variant.is_none() {
+            resolved.push((name, path));
+            continue;
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut generator: Value = serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let Some(mapping) = generator.as_mapping_mut() else {
+            bail!("Generator config {} is not a YAML map", path.display());
+        };
+        if let Some(label) = variant {
+            let output = mapping
+                .get("outputDir")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("generated/{scope}/{name}"));
+            mapping.insert(
+                "outputDir".into(),
+                format!(
+                    "generated/matrix/{label}/{}",
+                    output.trim_start_matches("generated/")
+                )
+                .into(),
+            );
+        }
+        if let Some(templates) = templates {
+            mapping.insert(
+                "templateDir".into(),
+                container_path(root, &template_dir(root, &name, templates)?)?.into(),
+            );
+        }
+        if let Some(properties) = properties {
+            let additional = mapping
+                .entry("additionalProperties".into())
+                .or_insert_with(|| Value::Mapping(Mapping::new()));
+            let Some(additional) = additional.as_mapping_mut() else {
+                bail!(
+                    "additionalProperties in {} is not a YAML map",
+                    path.display()
+                );
+            };
+            for (key, value) in properties {
+                additional.insert(key.clone(), value.clone());
+            }
+        }
+        fs::create_dir_all(&effective_dir)
+            .with_context(|| format!("Failed to create {}", effective_dir.display()))?;
+        let effective = effective_dir.join(format!("{name}.yaml"));
+        write_atomic(
+            &effective,
+            &serde_yaml::to_string(&generator).context("Failed to serialize generator config")?,
+        )?;
+        written = true;
+        resolved.push((name, effective));
+    }
+    if written && let Some(remote) = remote::active() {
+        let relative = effective_dir.strip_prefix(root).unwrap_or(&effective_dir);
+        remote.push(root, &[relative.to_path_buf()])?;
+    }
+    Ok(resolved)
 }
 
+/// `templates` entry of `name` resolved against `root`, which it must stay
+/// inside to be visible in the container.
+fn template_dir(root: &Path, name: &str, dir: &str) -> Result<PathBuf> {
+    let resolved = root.join(dir);
+    if !resolved.is_dir() {
+        bail!("Templates for '{name}' point to a missing directory: {dir}");
+    }
+    let canonical = resolved
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve templates for '{name}'"))?;
+    let canonical_root = root.canonicalize().context("Failed to resolve root path")?;
+    let Ok(relative) = canonical.strip_prefix(&canonical_root) else {
+        bail!("Templates for '{name}' resolve outside repository: {dir}");
+    };
+    Ok(root.join(relative))
+}
+
+/// Config path of each requested generator: its override, else
+/// `<user_dir>/<name>.yaml` if present, else `<config_dir>/<name>.yaml`.
 fn resolve_configs(
     root: &Path,
     config_dir: &Path,
     requested: &[String],
     overrides: &HashMap<String, String>,
+    user_dir: Option<&str>,
 ) -> Result<Vec<(String, PathBuf)>> {
     if !config_dir.is_dir() {
         bail!("Missing config directory: {}", config_dir.display());
@@ -158,10 +791,10 @@ fn resolve_configs(
         for entry in fs::read_dir(config_dir).context("Failed to read generator directory")? {
             let entry = entry?;
             let path = entry.path();
-            if path.extension().and_then(|ext| ext.to_str()) == Some("yaml") {
-                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                    names.push(stem.to_string());
-                }
+            if path.extension().and_then(|ext| ext.to_str()) == Some("yaml")
+                && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+            {
+                names.push(stem.to_string());
             }
         }
         names
@@ -169,12 +802,17 @@ fn resolve_configs(
 
     // Resolve each generator's config path
     for name in &generators {
-        let path = if let Some(override_path) = overrides.get(name) {
-            // User specified an override - resolve relative to root
-            let resolved = root.join(override_path);
+        let override_path = overrides.get(name).cloned().or_else(|| {
+            user_dir
+                .map(|dir| format!("{dir}/{name}.yaml"))
+                .filter(|path| root.join(path).is_file())
+        });
+        let path = if let Some(override_path) = override_path {
+            // Override or user config dir - resolve relative to root
+            let resolved = root.join(&override_path);
             if !resolved.is_file() {
                 bail!(
-                    "Generator override for '{}' points to invalid path: {}",
+                    "Generator config for '{}' points to invalid path: {}",
                     name,
                     override_path
                 );
@@ -185,7 +823,7 @@ fn resolve_configs(
             let canonical_root = root.canonicalize().context("Failed to resolve root path")?;
             if !canonical.starts_with(&canonical_root) {
                 bail!(
-                    "Generator override for '{}' resolves outside repository: {}",
+                    "Generator config for '{}' resolves outside repository: {}",
                     name,
                     override_path
                 );
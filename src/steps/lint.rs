@@ -1,45 +1,285 @@
 use anyhow::{Context, Result};
+use serde_yaml::Value;
 use std::fs;
-use std::path::Path;
-use std::process::Command;
+use std::path::{Path, PathBuf};
 
-use crate::docker;
+use super::check::{baseline_note, suppressed_note};
+use crate::baseline;
+use crate::config::{Config, CustomStep};
+use crate::docker::{self, RunStatus};
 use crate::output::Output;
-use crate::util::{OAV_DIR, append_status, to_posix_path, write_log_header};
+use crate::remote;
+use crate::results::{LintFindings, ResultsStore, TaskMetrics};
+use crate::rules::{self, Finding, Severity};
+use crate::suppressions;
+use crate::util::{OAV_DIR, append_error, to_posix_path, write_log_header};
 
-pub fn run(root: &Path, spec_path: &Path, redocly_image: &str, output: &Output) -> Result<bool> {
+/// Redocly configs `oav init` picks up, in order of preference.
+const REDOCLY_CONFIGS: [&str; 2] = ["redocly.yaml", ".redocly.yaml"];
+/// Spectral rulesets `oav init` picks up, in order of preference. Redocly
+/// can't read them, so they run in a `spectral` custom step.
+const SPECTRAL_CONFIGS: [&str; 3] = [".spectral.yml", ".spectral.yaml", ".spectral.json"];
+const SPECTRAL_IMAGE: &str = "stoplight/spectral:6";
+
+/// Linter configs in the repository root: the Redocly config and the
+/// Spectral ruleset to use.
+pub fn find_project_configs(root: &Path) -> (Option<&'static str>, Option<&'static str>) {
+    let find =
+        |names: &[&'static str]| names.iter().copied().find(|name| root.join(name).is_file());
+    (find(&REDOCLY_CONFIGS), find(&SPECTRAL_CONFIGS))
+}
+
+/// Custom step linting the spec with the Spectral ruleset `path` (relative
+/// to the root, which is the step's working directory) after Redocly.
+pub fn spectral_step(path: &str) -> CustomStep {
+    CustomStep {
+        image: SPECTRAL_IMAGE.to_string(),
+        command: vec![
+            "lint".to_string(),
+            "--ruleset".to_string(),
+            path.to_string(),
+            "{spec}".to_string(),
+        ],
+        after: "lint".to_string(),
+        mounts: Vec::new(),
+    }
+}
+
+/// The repository's Redocly config and the local plugins it lists, which
+/// Redocly reads when linting from the repository root.
+pub fn project_files(root: &Path) -> Vec<PathBuf> {
+    let Some(name) = find_project_configs(root).0 else {
+        return Vec::new();
+    };
+    let plugins = fs::read_to_string(root.join(name))
+        .ok()
+        .and_then(|content| serde_yaml::from_str::<Value>(&content).ok())
+        .and_then(|doc| doc.get("plugins").and_then(Value::as_sequence).cloned())
+        .unwrap_or_default();
+    let mut files = vec![PathBuf::from(name)];
+    files.extend(
+        plugins
+            .iter()
+            .filter_map(Value::as_str)
+            .map(PathBuf::from)
+            .filter(|path| root.join(path).is_file()),
+    );
+    files
+}
+
+pub fn run(
+    root: &Path,
+    spec_path: &Path,
+    config: &Config,
+    results: &ResultsStore,
+    output: &Output,
+) -> Result<bool> {
     let reports_dir = root.join(OAV_DIR).join("reports").join("lint");
     fs::create_dir_all(&reports_dir).context("Failed to create lint reports directory")?;
     let log_path = reports_dir.join("redocly.log");
+    results.start("lint", "spec", "redocly");
+    let Some(Redocly {
+        mut status,
+        metrics,
+        problems,
+    }) = redocly(root, spec_path, config, &log_path, output)?
+    else {
+        results.record("lint", "spec", "redocly", "fail", &log_path)?;
+        return Ok(false);
+    };
+    let reported_errors = rules::has_errors(&problems);
+    let LintFindings {
+        findings: problems,
+        accepted,
+        suppressed,
+    } = results.filter_findings("redocly", problems)?;
+    if suppressed > 0 {
+        append_error(&log_path, &suppressed_note(suppressed))?;
+    }
+    // Redocly exits non-zero on any error, including accepted and
+    // suppressed ones.
+    if status == RunStatus::Failed && reported_errors && !rules::has_errors(&problems) {
+        append_error(
+            &log_path,
+            &format!(
+                "No errors left after {} and {}",
+                baseline::FILE_NAME,
+                suppressions::FILE_NAME
+            ),
+        )?;
+        status = RunStatus::Ok;
+    }
+    if config.strict && status.success() {
+        let warnings = problems
+            .iter()
+            .filter(|problem| problem.severity == Severity::Warning)
+            .count();
+        if warnings > 0 {
+            append_error(
+                &log_path,
+                &format!("strict: Redocly reported {warnings} warning(s)"),
+            )?;
+            status = RunStatus::Failed;
+        }
+    }
+    results.record_with_metrics(
+        "lint",
+        "spec",
+        "redocly",
+        status.as_str(),
+        &log_path,
+        metrics,
+    )?;
+    if !accepted.is_empty() {
+        results.record_warnings(
+            "lint",
+            "spec",
+            "redocly",
+            vec![baseline_note(accepted.len())],
+        )?;
+    }
+    Ok(status.success())
+}
+
+/// One run of Redocly.
+pub struct Redocly {
+    pub status: RunStatus,
+    pub metrics: Option<TaskMetrics>,
+    pub problems: Vec<Finding>,
+}
 
-    let workspace = root.to_string_lossy().to_string();
-    let container_root = format!("/work/{OAV_DIR}");
+/// Run Redocly on `spec_path`, logging to `log_path`, or return `None` if
+/// `lint_config` is missing.
+pub fn redocly(
+    root: &Path,
+    spec_path: &Path,
+    config: &Config,
+    log_path: &Path,
+    output: &Output,
+) -> Result<Option<Redocly>> {
+    let redocly_image = &config.redocly_image;
+    let platform = config.platform.as_deref();
+    let user_args: Vec<String> = docker::explicit_user(config)
+        .map(|user| vec!["--user".to_string(), user])
+        .unwrap_or_default();
+    let extra_args = config.docker_args("lint", None);
+
+    let mount = remote::work_mount(root, config);
+    // From the repository root, Redocly finds the project's redocly.yaml,
+    // with its API aliases and plugins, by itself.
+    let container_root = match find_project_configs(root).0 {
+        Some(_) => "/work".to_string(),
+        None => format!("/work/{OAV_DIR}"),
+    };
     let spec = format!("/work/{}", to_posix_path(spec_path));
+    let config_args: Vec<String> = config
+        .lint_config
+        .iter()
+        .flat_map(|path| {
+            [
+                "--config".to_string(),
+                format!("/work/{}", to_posix_path(Path::new(path))),
+            ]
+        })
+        .collect();
     let command_line = format!(
-        "$ docker run --rm -v {workspace}:/work -w {container_root} {redocly_image} lint {spec}"
-    );
-    write_log_header(&log_path, &command_line)?;
+        "$ {engine} run {user} {platform} {extra} -v {mount} -w {container_root} {redocly_image} lint {config_args} {lint_args} {spec}",
+        engine = docker::engine().binary(),
+        user = user_args.join(" "),
+        platform = docker::platform_args(platform).join(" "),
+        extra = extra_args.join(" "),
+        config_args = config_args.join(" "),
+        lint_args = config.lint_args.join(" ")
+    )
+    .replace("  ", " ");
+    write_log_header(log_path, &command_line)?;
+
+    if let Some(path) = &config.lint_config
+        && !root.join(path).is_file()
+    {
+        append_error(log_path, &format!("lint_config not found: {path}"))?;
+        return Ok(None);
+    }
 
-    let mut command = Command::new("docker");
+    let container = docker::container_name("lint-redocly");
+    let mut command = docker::engine().command();
     command
         .arg("run")
-        .arg("--rm")
+        .arg("--name")
+        .arg(&container)
+        .args(&user_args)
+        .args(docker::platform_args(platform))
+        .args(docker::env_args(config))
+        .args(&extra_args)
         .arg("-v")
-        .arg(format!("{workspace}:/work"))
+        .arg(&mount)
         .arg("-w")
-        .arg(container_root)
+        .arg(&container_root)
         .arg(redocly_image)
         .arg("lint")
+        .args(&config_args)
+        .args(&config.lint_args)
         .arg(spec);
 
-    let success = docker::run_with_logging(&mut command, &log_path, output)?;
-    append_status(
-        root,
-        "lint",
-        "spec",
-        "redocly",
-        if success { "ok" } else { "fail" },
-        &log_path,
+    let (status, metrics) = docker::run_with_logging(
+        &mut command,
+        log_path,
+        output,
+        Some(&container),
+        config.timeout("lint")?,
     )?;
-    Ok(success)
+    let problems = parse_problems(&fs::read_to_string(log_path).unwrap_or_default());
+    Ok(Some(Redocly {
+        status,
+        metrics,
+        problems,
+    }))
+}
+
+/// Problems in Redocly's default output: `[n] file:line:col at #/pointer`,
+/// the message, and `Error was generated by the <rule> rule.` (or `Warning`).
+fn parse_problems(log: &str) -> Vec<Finding> {
+    let mut problems = Vec::new();
+    let mut current: Option<(String, Option<String>)> = None;
+    for line in log.lines() {
+        let line = line.trim();
+        if let Some((number, location)) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("] "))
+            && !number.is_empty()
+            && number.chars().all(|c| c.is_ascii_digit())
+        {
+            let path = location
+                .rsplit_once(" at ")
+                .map_or(location, |(_, path)| path);
+            current = Some((path.to_string(), None));
+            continue;
+        }
+        let Some((path, message)) = &mut current else {
+            continue;
+        };
+        let generated = |level: &str| {
+            line.strip_prefix(level)?
+                .strip_prefix(" was generated by the ")?
+                .strip_suffix(" rule.")
+        };
+        if let Some(rule) = generated("Error") {
+            problems.push(Finding::error(
+                rule,
+                path.clone(),
+                message.take().unwrap_or_default(),
+            ));
+            current = None;
+        } else if let Some(rule) = generated("Warning") {
+            problems.push(Finding::warning(
+                rule,
+                path.clone(),
+                message.take().unwrap_or_default(),
+            ));
+            current = None;
+        } else if message.is_none() && !line.is_empty() {
+            *message = Some(line.to_string());
+        }
+    }
+    problems
 }
@@ -0,0 +1,219 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::results::{RunResults, StatusEntry, StepTiming};
+use crate::util::{OAV_DIR, unix_now, write_atomic};
+
+/// Durations, results, and cache use of one run, as `metrics.json`.
+#[derive(Serialize)]
+struct Metrics<'a> {
+    run_id: &'a str,
+    started_at: &'a str,
+    passed: usize,
+    failed: usize,
+    stages: BTreeMap<&'a str, StageMetrics>,
+    steps: &'a [StepTiming],
+    tasks: Vec<TaskMetric<'a>>,
+    cache: CacheMetrics,
+}
+
+#[derive(Serialize, Default)]
+struct StageMetrics {
+    tasks: usize,
+    passed: usize,
+    failed: usize,
+    /// Sum of the stage's timed tasks, which may have run in parallel.
+    duration_ms: u64,
+}
+
+#[derive(Serialize)]
+struct TaskMetric<'a> {
+    stage: &'a str,
+    scope: &'a str,
+    target: &'a str,
+    status: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    peak_memory_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu_seconds: Option<f64>,
+}
+
+/// Generate targets reused from the cache (`cached`) and run anew.
+#[derive(Serialize)]
+struct CacheMetrics {
+    hits: usize,
+    misses: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hit_rate: Option<f64>,
+}
+
+/// Write `.oav/reports/metrics.json` for `run`, and the Prometheus textfile
+/// at `metrics_textfile` when set.
+pub fn write(root: &Path, config: &Config, run: &RunResults) -> Result<()> {
+    let reports_dir = root.join(OAV_DIR).join("reports");
+    fs::create_dir_all(&reports_dir).context("Failed to create reports directory")?;
+    let metrics = collect(run);
+    write_atomic(
+        &reports_dir.join("metrics.json"),
+        &serde_json::to_string_pretty(&metrics)?,
+    )?;
+    if let Some(path) = &config.metrics_textfile {
+        write_atomic(&root.join(path), &prometheus(&metrics))
+            .with_context(|| format!("Failed to write metrics_textfile {path}"))?;
+    }
+    Ok(())
+}
+
+fn collect(run: &RunResults) -> Metrics<'_> {
+    let mut stages: BTreeMap<&str, StageMetrics> = BTreeMap::new();
+    for entry in &run.entries {
+        let stage = stages.entry(&entry.stage).or_default();
+        stage.tasks += 1;
+        stage.passed += usize::from(entry.passed());
+        stage.failed += usize::from(entry.failed());
+        stage.duration_ms += entry.duration_ms.unwrap_or_default();
+    }
+    let cacheable: Vec<&StatusEntry> = run.entries.iter().filter(|e| cacheable(e)).collect();
+    let hits = cacheable.iter().filter(|e| e.status == "cached").count();
+    Metrics {
+        run_id: &run.run_id,
+        started_at: &run.started_at,
+        passed: run.passed(),
+        failed: run.failed(),
+        stages,
+        steps: &run.steps,
+        tasks: run
+            .entries
+            .iter()
+            .map(|entry| TaskMetric {
+                stage: &entry.stage,
+                scope: &entry.scope,
+                target: &entry.target,
+                status: &entry.status,
+                duration_ms: entry.duration_ms,
+                peak_memory_bytes: entry.metrics.map(|m| m.peak_memory_bytes),
+                cpu_seconds: entry.metrics.map(|m| m.cpu_seconds),
+            })
+            .collect(),
+        cache: CacheMetrics {
+            hits,
+            misses: cacheable.len() - hits,
+            hit_rate: (!cacheable.is_empty()).then(|| hits as f64 / cacheable.len() as f64),
+        },
+    }
+}
+
+/// A generator target, which the generate cache can reuse.
+fn cacheable(entry: &StatusEntry) -> bool {
+    entry.stage == "generate"
+        && matches!(entry.scope.as_str(), "server" | "client")
+        && entry.target != "_config_"
+}
+
+/// `metrics` in the Prometheus text exposition format, for node_exporter's
+/// textfile collector. Steps that ran more than once are summed.
+fn prometheus(metrics: &Metrics) -> String {
+    let mut out = String::new();
+    gauge(
+        &mut out,
+        "oav_run_timestamp_seconds",
+        "When the last oav run wrote its reports.",
+    );
+    out.push_str(&format!("oav_run_timestamp_seconds {}\n", unix_now()));
+
+    gauge(&mut out, "oav_tasks", "Tasks of the last run by result.");
+    for (result, count) in [("passed", metrics.passed), ("failed", metrics.failed)] {
+        out.push_str(&format!("oav_tasks{{result=\"{result}\"}} {count}\n"));
+    }
+
+    let mut steps: BTreeMap<&str, (u64, bool)> = BTreeMap::new();
+    for step in metrics.steps {
+        let total = steps.entry(&step.step).or_insert((0, true));
+        total.0 += step.duration_ms;
+        total.1 &= step.success;
+    }
+    gauge(
+        &mut out,
+        "oav_step_duration_seconds",
+        "Wall-clock time of each step of the last run.",
+    );
+    for (step, (duration_ms, _)) in &steps {
+        out.push_str(&format!(
+            "oav_step_duration_seconds{{step=\"{}\"}} {}\n",
+            label(step),
+            seconds(*duration_ms)
+        ));
+    }
+    gauge(
+        &mut out,
+        "oav_step_success",
+        "Whether each step of the last run succeeded.",
+    );
+    for (step, (_, success)) in &steps {
+        out.push_str(&format!(
+            "oav_step_success{{step=\"{}\"}} {}\n",
+            label(step),
+            u8::from(*success)
+        ));
+    }
+
+    let mut tasks: BTreeMap<(&str, &str, &str), u64> = BTreeMap::new();
+    for task in &metrics.tasks {
+        if let Some(duration_ms) = task.duration_ms {
+            tasks.insert((task.stage, task.scope, task.target), duration_ms);
+        }
+    }
+    gauge(
+        &mut out,
+        "oav_task_duration_seconds",
+        "Wall-clock time of each timed task of the last run.",
+    );
+    for ((stage, scope, target), duration_ms) in tasks {
+        out.push_str(&format!(
+            "oav_task_duration_seconds{{stage=\"{}\",scope=\"{}\",target=\"{}\"}} {}\n",
+            label(stage),
+            label(scope),
+            label(target),
+            seconds(duration_ms)
+        ));
+    }
+
+    gauge(
+        &mut out,
+        "oav_generate_cache_hits",
+        "Generate targets of the last run reused from the cache.",
+    );
+    out.push_str(&format!("oav_generate_cache_hits {}\n", metrics.cache.hits));
+    gauge(
+        &mut out,
+        "oav_generate_cache_misses",
+        "Generate targets of the last run that ran.",
+    );
+    out.push_str(&format!(
+        "oav_generate_cache_misses {}\n",
+        metrics.cache.misses
+    ));
+    out
+}
+
+fn gauge(out: &mut String, name: &str, help: &str) {
+    out.push_str(&format!("# HELP {name} {help}\n# TYPE {name} gauge\n"));
+}
+
+fn seconds(duration_ms: u64) -> String {
+    format!("{:.3}", duration_ms as f64 / 1000.0)
+}
+
+/// `value` escaped for a label value.
+fn label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
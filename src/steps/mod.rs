@@ -1,35 +1,147 @@
+mod badge;
+mod cache;
+mod check;
 mod compile;
+mod contract;
+mod coverage;
+mod custom;
+mod diff;
 mod generate;
 mod lint;
+mod metrics;
+mod pdf;
+mod pull;
 mod report;
+mod smoke;
+mod spellcheck;
+mod test;
+mod trends;
+mod warm;
 
+pub use cache::spec_files;
+pub use check::{
+    native as native_checks, offline as check_offline, run as check, security, security_levels,
+    yaml_style as check_yaml_style,
+};
 pub use compile::run as compile;
+pub use compile::{SUPPORTED_CLIENT_GENERATORS, SUPPORTED_SERVER_GENERATORS};
+pub use contract::run as contract;
+pub use custom::{any_after as custom_steps_after, run as custom};
+pub use diff::run as diff_runs;
 pub use generate::run as generate;
+pub use generate::{export as export_generated, scope_of};
+pub use lint::find_project_configs as find_lint_configs;
+pub use lint::project_files as lint_project_files;
+pub use lint::redocly;
 pub use lint::run as lint;
-pub use report::{load_status_entries, run as report};
+pub use lint::spectral_step;
+pub use pull::run as pull;
+pub use report::html_escape;
+pub use report::rebuild as rebuild_report;
+pub use report::render as render_report;
+pub use report::run as report;
+pub use smoke::run as smoke;
+pub use spellcheck::run as spellcheck;
+pub use test::run as test;
 
 use anyhow::Result;
+use std::time::Instant;
 
+use crate::cli::Mode;
+use crate::config::Config;
+use crate::events::{self, Event};
 use crate::output::Output;
+use crate::results::ResultsStore;
+
+/// `(scope, name)` of every generator the current mode and generator lists select.
+/// Empty lists select every supported generator for that scope.
+pub fn active_generators(config: &Config) -> Vec<(&'static str, String)> {
+    let mut generators = Vec::new();
+    let scopes: [(&'static str, bool, &[String], &[&str]); 2] = [
+        (
+            "server",
+            matches!(config.mode, Mode::Server | Mode::Both),
+            &config.server_generators,
+            &SUPPORTED_SERVER_GENERATORS,
+        ),
+        (
+            "client",
+            matches!(config.mode, Mode::Client | Mode::Both),
+            &config.client_generators,
+            &SUPPORTED_CLIENT_GENERATORS,
+        ),
+    ];
+    for (scope, enabled, requested, supported) in scopes {
+        if !enabled {
+            continue;
+        }
+        if requested.is_empty() {
+            generators.extend(supported.iter().map(|name| (scope, name.to_string())));
+        } else {
+            generators.extend(
+                requested
+                    .iter()
+                    .map(|name| name.trim())
+                    .filter(|name| !name.is_empty())
+                    .map(|name| (scope, name.to_string())),
+            );
+        }
+    }
+    generators
+}
+
+/// How `run_step` presents a step.
+#[derive(Debug, Clone, Copy)]
+pub struct StepOptions {
+    /// Show a spinner while the step runs and a status line when it ends.
+    /// Steps that print a line per task leave it off.
+    pub summary: bool,
+    /// Show a failure as a warning (`severity.<step>: warn`).
+    pub warn_only: bool,
+}
+
+impl StepOptions {
+    /// Spinner and status line.
+    pub const SUMMARY: Self = Self {
+        summary: true,
+        warn_only: false,
+    };
+    /// No output of its own.
+    pub const QUIET: Self = Self {
+        summary: false,
+        warn_only: false,
+    };
+}
 
 pub fn run_step(
     output: &Output,
+    results: &ResultsStore,
     label: &str,
-    show_spinner: bool,
-    show_summary: bool,
+    options: StepOptions,
     action: impl FnOnce() -> Result<bool>,
 ) -> Result<bool> {
-    let spinner = if show_spinner {
+    let spinner = if options.summary {
         output.start_spinner(label)
     } else {
         None
     };
+    events::emit(&Event::StepStarted { step: label });
+    let started = Instant::now();
     let result = action();
     let success = result.as_ref().map(|ok| *ok).unwrap_or(false);
-    if show_summary {
+    let duration_ms = started.elapsed().as_millis() as u64;
+    events::emit(&Event::StepFinished {
+        step: label,
+        success,
+        duration_ms,
+    });
+    if options.summary && !success && options.warn_only {
+        output.finish_spinner_warn(spinner.as_ref(), label);
+    } else if options.summary {
         output.finish_spinner(spinner.as_ref(), label, success);
     } else if let Some(spinner) = spinner.as_ref() {
         spinner.finish_and_clear();
     }
+    results.record_step(label, success, duration_ms)?;
     result
 }
@@ -0,0 +1,354 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::results::RunResults;
+use crate::util::{format_duration, write_atomic};
+
+/// A4, in points.
+const PAGE_WIDTH: f64 = 595.0;
+const PAGE_HEIGHT: f64 = 842.0;
+const MARGIN: f64 = 50.0;
+/// Room kept at the bottom of each page for its footer.
+const FOOTER: f64 = 20.0;
+/// Size of the Courier body text; a character is 0.6 of it wide.
+const BODY_SIZE: f64 = 8.0;
+/// Courier characters of body text across the page.
+const COLUMNS: usize = ((PAGE_WIDTH - 2.0 * MARGIN) / (BODY_SIZE * 0.6)) as usize;
+
+/// The standard fonts every PDF reader has, so nothing needs embedding.
+#[derive(Clone, Copy)]
+enum Font {
+    Sans,
+    SansBold,
+    Mono,
+    MonoBold,
+}
+
+impl Font {
+    const ALL: [(Font, &'static str); 4] = [
+        (Font::Sans, "Helvetica"),
+        (Font::SansBold, "Helvetica-Bold"),
+        (Font::Mono, "Courier"),
+        (Font::MonoBold, "Courier-Bold"),
+    ];
+
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Sans => "F1",
+            Font::SansBold => "F2",
+            Font::Mono => "F3",
+            Font::MonoBold => "F4",
+        }
+    }
+}
+
+struct Line {
+    y: f64,
+    font: Font,
+    size: f64,
+    indent: f64,
+    text: String,
+}
+
+/// Lines laid out top to bottom, starting a new page when one is full.
+struct Document {
+    pages: Vec<Vec<Line>>,
+    y: f64,
+}
+
+impl Document {
+    fn new() -> Self {
+        Self {
+            pages: vec![Vec::new()],
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    fn line(&mut self, font: Font, size: f64, text: &str) {
+        self.indented(font, size, 0, text);
+    }
+
+    /// A line starting `indent` body characters in.
+    fn indented(&mut self, font: Font, size: f64, indent: usize, text: &str) {
+        let height = size * 1.4;
+        if self.y - height < MARGIN + FOOTER {
+            self.new_page();
+        }
+        self.y -= height;
+        let y = self.y;
+        self.pages.last_mut().expect("a page").push(Line {
+            y,
+            font,
+            size,
+            indent: indent as f64 * BODY_SIZE * 0.6,
+            text: text.to_string(),
+        });
+    }
+
+    /// `text` in body text wrapped to the page, `indent` characters in.
+    fn wrapped(&mut self, indent: usize, text: &str) {
+        for line in wrap(text, COLUMNS - indent) {
+            self.indented(Font::Mono, BODY_SIZE, indent, &line);
+        }
+    }
+
+    /// A section heading, moved to the next page rather than left alone at
+    /// the bottom of this one.
+    fn heading(&mut self, text: &str) {
+        if self.y - 60.0 < MARGIN + FOOTER {
+            self.new_page();
+        } else {
+            self.y -= 12.0;
+        }
+        self.line(Font::SansBold, 13.0, text);
+        self.y -= 4.0;
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Vec::new());
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+}
+
+/// Write `run`'s summary, tasks, and findings as a paginated PDF to `path`.
+pub fn write(path: &Path, run: &RunResults) -> Result<()> {
+    write_atomic(path, &render(run)).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn render(run: &RunResults) -> String {
+    let mut doc = Document::new();
+    let (passed, failed) = (run.passed(), run.failed());
+    let errors = run
+        .findings
+        .iter()
+        .filter(|finding| finding.severity == "error")
+        .count();
+    doc.line(Font::SansBold, 18.0, "OpenAPI Validation Report");
+    doc.y -= 6.0;
+    doc.line(Font::Sans, 10.0, &format!("Run: {}", run.run_id));
+    doc.line(Font::Sans, 10.0, &format!("Started: {}", run.started_at));
+    doc.line(
+        Font::Sans,
+        10.0,
+        &format!(
+            "Result: {} ({passed} passed, {failed} failed, {} error(s), {} warning(s))",
+            if failed > 0 { "failed" } else { "passed" },
+            errors,
+            run.findings.len() - errors
+        ),
+    );
+
+    doc.heading("Tasks");
+    let widths = [10, 10, 34, 20, 10];
+    doc.line(
+        Font::MonoBold,
+        BODY_SIZE,
+        &row(&["STAGE", "SCOPE", "TARGET", "STATUS", "DURATION"], &widths),
+    );
+    for entry in &run.entries {
+        let duration = entry.duration_ms.map(format_duration).unwrap_or_default();
+        doc.line(
+            Font::Mono,
+            BODY_SIZE,
+            &row(
+                &[
+                    &entry.stage,
+                    &entry.scope,
+                    &entry.target,
+                    &entry.status_label(),
+                    &duration,
+                ],
+                &widths,
+            ),
+        );
+    }
+
+    if !run.findings.is_empty() {
+        doc.heading("Findings");
+        for finding in &run.findings {
+            doc.y -= 3.0;
+            doc.line(
+                Font::MonoBold,
+                BODY_SIZE,
+                &fit(&format!("{} [{}]", finding.severity, finding.rule), COLUMNS),
+            );
+            doc.wrapped(2, &finding.path);
+            doc.wrapped(2, &finding.message);
+            doc.wrapped(2, &format!("Reported by {}", finding.checks.join(", ")));
+        }
+    }
+
+    if !run.suppressed.is_empty() {
+        doc.heading("Suppressed");
+        for finding in &run.suppressed {
+            doc.y -= 3.0;
+            doc.line(
+                Font::MonoBold,
+                BODY_SIZE,
+                &fit(&format!("{} [{}]", finding.check, finding.rule), COLUMNS),
+            );
+            doc.wrapped(2, &finding.path);
+            doc.wrapped(2, &finding.message);
+            if let Some(reason) = &finding.reason {
+                doc.wrapped(2, &format!("Reason: {reason}"));
+            }
+        }
+    }
+
+    serialize(&doc.pages, &run.run_id)
+}
+
+/// `cells` padded or cut to `widths` characters, a space apart.
+fn row(cells: &[&str], widths: &[usize]) -> String {
+    cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, &width)| format!("{:<width$}", fit(cell, width - 1)))
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+/// `text` cut to `width` characters, marked with `...` where it was cut.
+fn fit(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(width.saturating_sub(3)).collect();
+    format!("{kept}...")
+}
+
+/// `text` in lines of at most `width` characters, broken between words
+/// where it can be.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        let length = line.chars().count();
+        if length > 0 && length + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        while word.len() > width {
+            let rest = word.split_off(width);
+            lines.push(word.into_iter().collect());
+            word = rest;
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.extend(word);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// The PDF file of `pages`, each footed with `run_id` and its page number.
+fn serialize(pages: &[Vec<Line>], run_id: &str) -> String {
+    let fonts = Font::ALL.len();
+    // Catalog, page tree, info, and fonts come first, then each page
+    // followed by its content stream.
+    let first_page = 4 + fonts;
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..pages.len())
+                .map(|index| format!("{} 0 R", first_page + 2 * index))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        ),
+        format!(
+            "<< /Title ({}) /Producer (oav {}) >>",
+            pdf_string(&format!("OpenAPI Validation Report {run_id}")),
+            env!("CARGO_PKG_VERSION")
+        ),
+    ];
+    let mut font_resources = String::new();
+    for (index, (font, name)) in Font::ALL.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /{name} /Encoding /WinAnsiEncoding >>"
+        ));
+        font_resources.push_str(&format!("/{} {} 0 R ", font.resource(), 4 + index));
+    }
+    for (index, lines) in pages.iter().enumerate() {
+        let mut content = String::new();
+        for line in lines {
+            content.push_str(&text_op(
+                line.font,
+                line.size,
+                MARGIN + line.indent,
+                line.y,
+                &line.text,
+            ));
+        }
+        let footer = format!("Page {} of {}", index + 1, pages.len());
+        content.push_str(&text_op(Font::Sans, 8.0, MARGIN, MARGIN - 10.0, run_id));
+        content.push_str(&text_op(
+            Font::Sans,
+            8.0,
+            PAGE_WIDTH - MARGIN - footer.len() as f64 * 4.5,
+            MARGIN - 10.0,
+            &footer,
+        ));
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] /Resources << /Font << {font_resources}>> >> /Contents {} 0 R >>",
+            first_page + 2 * index + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{content}endstream",
+            content.len()
+        ));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{object}\nendobj\n", index + 1));
+    }
+    let xref = pdf.len();
+    pdf.push_str(&format!(
+        "xref\n0 {}\n0000000000 65535 f \n",
+        objects.len() + 1
+    ));
+    for offset in offsets {
+        pdf.push_str(&format!("{offset:010} 00000 n \n"));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R /Info 3 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    ));
+    pdf
+}
+
+fn text_op(font: Font, size: f64, x: f64, y: f64, text: &str) -> String {
+    format!(
+        "BT /{} {size} Tf {x:.1} {y:.1} Td ({}) Tj ET\n",
+        font.resource(),
+        pdf_string(text)
+    )
+}
+
+/// `text` as the inside of a PDF literal string in WinAnsiEncoding. Latin-1
+/// characters are escaped in octal, so the file stays ASCII; characters the
+/// standard fonts can't show become `?`.
+fn pdf_string(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' | '(' | ')' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            '\t' => escaped.push(' '),
+            '\u{a0}'..='\u{ff}' => escaped.push_str(&format!("\\{:03o}", c as u32)),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
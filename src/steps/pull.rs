@@ -0,0 +1,239 @@
+use anyhow::Result;
+use indicatif::ProgressBar;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::Stdio;
+use std::thread;
+
+use super::compile::{self, BuildStage};
+use super::smoke;
+use crate::config::Config;
+use crate::docker;
+use crate::output::Output;
+use crate::results::ResultsStore;
+
+/// Pull every image the enabled stages need and that isn't present locally,
+/// concurrently and with a progress bar per image, so later tasks don't sit
+/// behind a spinner while an image downloads, then record each image's
+/// digest in the run results. Returns false if a pull failed; the affected
+/// tasks then fail with the engine's own error.
+pub fn run(root: &Path, config: &Config, results: &ResultsStore, output: &Output) -> Result<bool> {
+    let images = images(root, config)?;
+    let success = pull_missing(&images, config.platform.as_deref(), output);
+    for image in &images {
+        if let Some(digest) = docker::image_digest(image) {
+            results.record_image(image, &digest)?;
+        }
+    }
+    Ok(success)
+}
+
+fn pull_missing(images: &[String], platform: Option<&str>, output: &Output) -> bool {
+    let missing: Vec<&String> = images
+        .iter()
+        .filter(|image| !is_present(image, platform))
+        .collect();
+    if missing.is_empty() {
+        return true;
+    }
+
+    output.phase_header("Pull images");
+    let multi = output.multi_progress();
+    thread::scope(|scope| {
+        let pulls: Vec<_> = missing
+            .iter()
+            .map(|&image| {
+                let bar = multi
+                    .as_ref()
+                    .map(|multi| output.add_progress_bar(multi, image));
+                let handle = scope.spawn(move || {
+                    let error = pull(image, platform, bar.as_ref());
+                    if let Some(bar) = &bar {
+                        bar.finish_and_clear();
+                    }
+                    error
+                });
+                (image, handle)
+            })
+            .collect();
+
+        let mut success = true;
+        for (image, handle) in pulls {
+            let error = handle
+                .join()
+                .unwrap_or_else(|_| Some("Pull thread panicked".to_string()));
+            let report = || {
+                output.substep_finish(&format!("Pull {image}"), error.is_none());
+                if let Some(error) = &error {
+                    match docker::auth_guidance(error) {
+                        Some(guidance) => docker::report_auth_failure(&guidance, output),
+                        None => output.println(&format!("      {error}")),
+                    }
+                }
+            };
+            match &multi {
+                Some(multi) => multi.suspend(report),
+                None => report(),
+            }
+            success &= error.is_none();
+        }
+        success
+    })
+}
+
+/// Images used by the enabled stages, in stage order and without duplicates.
+fn images(root: &Path, config: &Config) -> Result<Vec<String>> {
+    let mut images = Vec::new();
+    if config.lint {
+        images.push(config.redocly_image.clone());
+        if config.spellcheck {
+            images.push(config.cspell_image.clone());
+        }
+    }
+    if config.generate {
+        images.push(config.generator_image.clone());
+        images.extend(config.matrix_images().into_iter().map(|(_, image)| image));
+        if config.compile {
+            images.extend(compile::service_images(root, config, BuildStage::Compile)?);
+        }
+        if config.test {
+            images.extend(compile::service_images(root, config, BuildStage::Test)?);
+        }
+        if config.smoke || (config.contract && config.contract_url.is_none()) {
+            images.extend(smoke::images(root, config)?);
+        }
+    }
+    if config.contract && (config.generate || config.contract_url.is_some()) {
+        images.push(config.schemathesis_image.clone());
+    }
+    images.extend(config.custom_steps.values().map(|step| step.image.clone()));
+    let mut seen = HashSet::new();
+    images.retain(|image| seen.insert(image.clone()));
+    Ok(images)
+}
+
+/// Whether `image` is local, for `platform` (`os/arch[/variant]`) if given.
+/// The variant isn't compared, since inspect doesn't report it everywhere.
+fn is_present(image: &str, platform: Option<&str>) -> bool {
+    let Ok(output) = docker::engine()
+        .command()
+        .args([
+            "image",
+            "inspect",
+            "--format",
+            "{{.Os}}/{{.Architecture}}",
+            image,
+        ])
+        .stderr(Stdio::null())
+        .output()
+    else {
+        return false;
+    };
+    if !output.status.success() {
+        return false;
+    }
+    let Some(platform) = platform else {
+        return true;
+    };
+    let wanted: Vec<&str> = platform.split('/').take(2).collect();
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .any(|line| line.trim().split('/').eq(wanted.iter().copied()))
+}
+
+/// Pull `image`, advancing `bar` as layers complete. Returns the error
+/// message on failure.
+fn pull(image: &str, platform: Option<&str>, bar: Option<&ProgressBar>) -> Option<String> {
+    let mut command = docker::engine().command();
+    command
+        .arg("pull")
+        .args(docker::platform_args(platform))
+        .arg(image)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(err) => return Some(format!("Failed to run pull: {err}")),
+    };
+
+    let mut layers = LayerProgress::default();
+    // An empty bar until layers are known; indicatif draws zero-length bars full.
+    if let Some(bar) = bar {
+        bar.set_length(1);
+    }
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            layers.update(&line);
+            if let Some(bar) = bar {
+                bar.set_length(layers.total().max(1) as u64);
+                bar.set_position(layers.done() as u64);
+                bar.set_message(layers.summary());
+            }
+        }
+    }
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = pipe.read_to_string(&mut stderr);
+    }
+    match child.wait() {
+        Ok(status) if status.success() => None,
+        Ok(_) => Some(
+            stderr
+                .lines()
+                .rev()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or("pull failed")
+                .trim()
+                .to_string(),
+        ),
+        Err(err) => Some(format!("Failed to wait for pull: {err}")),
+    }
+}
+
+/// Layer states parsed from `docker pull` / `nerdctl pull` progress lines
+/// such as `a1b2c3: Pull complete`. Engines without per-layer lines leave
+/// the bar spinning.
+#[derive(Default)]
+struct LayerProgress {
+    seen: HashSet<String>,
+    complete: HashSet<String>,
+}
+
+impl LayerProgress {
+    fn update(&mut self, line: &str) {
+        let Some((layer, status)) = line.split_once(": ") else {
+            return;
+        };
+        if layer.contains(' ') || layer.is_empty() {
+            return;
+        }
+        match status.trim() {
+            "Pulling fs layer" | "Waiting" | "Downloading" | "Verifying Checksum"
+            | "Download complete" | "Extracting" => {
+                self.seen.insert(layer.to_string());
+            }
+            "Pull complete" | "Already exists" => {
+                self.seen.insert(layer.to_string());
+                self.complete.insert(layer.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.seen.len()
+    }
+
+    fn done(&self) -> usize {
+        self.complete.len()
+    }
+
+    fn summary(&self) -> String {
+        if self.seen.is_empty() {
+            String::new()
+        } else {
+            format!("{}/{} layers", self.done(), self.total())
+        }
+    }
+}
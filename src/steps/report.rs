@@ -3,53 +3,193 @@ use std::fs::{self, File};
 use std::io::Read;
 use std::path::Path;
 
+use super::badge;
+use super::metrics;
+use super::pdf;
+use super::trends::{self, Point};
+use crate::config::Config;
 use crate::output::Output;
-use crate::util::OAV_DIR;
+use crate::results::{self, MergedFinding, StatusEntry, SuppressedFinding};
+use crate::util::{
+    OAV_DIR, expand_env_vars, format_bytes, format_duration, is_ci, to_posix_path, write_atomic,
+};
 
-#[derive(Debug)]
-pub struct StatusEntry {
-    pub stage: String,
-    pub scope: String,
-    pub target: String,
-    pub status: String,
-    pub log_path: String,
+/// Tasks listed under Slowest Tasks.
+const SLOWEST_TASKS: usize = 5;
+
+/// Resolves where a log should be linked from shared reports.
+struct LogLinks<'a> {
+    reports_dir: &'a Path,
+    artifact_url_template: Option<&'a str>,
 }
 
-pub fn load_status_entries(status_path: &Path) -> Result<Vec<StatusEntry>> {
-    if !status_path.exists() {
-        return Ok(Vec::new());
+impl LogLinks<'_> {
+    /// Links point at the uploaded artifact when running in CI with an
+    /// `artifact_url_template`, and are relative to the reports directory otherwise.
+    fn href(&self, entry: &StatusEntry) -> String {
+        let log_path = Path::new(&entry.log_path);
+        let relative = log_path
+            .strip_prefix(self.reports_dir)
+            .map(to_posix_path)
+            .unwrap_or_else(|_| to_posix_path(log_path));
+        match self.artifact_url_template {
+            Some(template) if is_ci() => expand_env_vars(
+                &template
+                    .replace("{path}", &relative)
+                    .replace("{stage}", &entry.stage)
+                    .replace("{scope}", &entry.scope)
+                    .replace("{target}", &entry.target),
+            ),
+            _ => relative,
+        }
     }
-    let content = fs::read_to_string(status_path).context("Failed to read status file")?;
-    let entries = content
-        .lines()
-        .filter_map(|line| {
-            let parts: Vec<&str> = line.split('\t').collect();
-            if parts.len() >= 5 {
-                Some(StatusEntry {
-                    stage: parts[0].to_string(),
-                    scope: parts[1].to_string(),
-                    target: parts[2].to_string(),
-                    status: parts[3].to_string(),
-                    log_path: parts[4].to_string(),
-                })
-            } else {
-                None
-            }
+}
+
+pub fn run(root: &Path, config: &Config, output: &Output) -> Result<bool> {
+    let run = results::load_latest(root)?;
+    let metrics = match &run {
+        Some(run) => metrics::write(root, config, run),
+        None => Ok(()),
+    };
+    let badge = match &run {
+        Some(run) => badge::write(root, config, run),
+        None => Ok(()),
+    };
+    let trend = run
+        .as_ref()
+        .map(|run| trends::collect(root, run))
+        .unwrap_or_default();
+    let (entries, suppressed, findings) = run
+        .map(|run| (run.entries, run.suppressed, run.findings))
+        .unwrap_or_default();
+    let written = write_reports(
+        root,
+        config,
+        &entries,
+        &suppressed,
+        &findings,
+        &trend,
+        output,
+    )?;
+    if let Err(err) = metrics {
+        if !output.quiet {
+            eprintln!("Metrics export failed: {err:#}");
+        }
+        return Ok(false);
+    }
+    if let Err(err) = badge {
+        if !output.quiet {
+            eprintln!("Badge generation failed: {err:#}");
+        }
+        return Ok(false);
+    }
+    Ok(written)
+}
+
+/// Rebuild the reports from the stored results of `run_id`, or of the latest
+/// run along with `status.tsv`, without running anything. Logs are looked up under
+/// this workspace's `.oav`, so results archived from another machine work.
+/// With `pdf`, `report.pdf` is written too.
+pub fn rebuild(
+    root: &Path,
+    config: &Config,
+    run_id: Option<&str>,
+    pdf: bool,
+    output: &Output,
+) -> Result<bool> {
+    let run = match run_id {
+        Some(run_id) => results::load(root, run_id)?
+            .with_context(|| format!("No results found for run {run_id}"))?,
+        None => {
+            results::load_latest(root)?.context("No results found. Run `oav validate` first.")?
+        }
+    };
+    if pdf {
+        let reports_dir = root.join(OAV_DIR).join("reports");
+        fs::create_dir_all(&reports_dir).context("Failed to create reports directory")?;
+        pdf::write(&reports_dir.join("report.pdf"), &run)?;
+    }
+    // `status.tsv` and the badge always describe the latest run.
+    if run_id.is_none() {
+        badge::write(root, config, &run)?;
+    }
+    let trend = trends::collect(root, &run);
+    let entries = local_entries(root, run.entries);
+    if run_id.is_none() {
+        write_atomic(
+            &root.join(OAV_DIR).join("status.tsv"),
+            &results::to_tsv(&entries),
+        )?;
+    }
+    write_reports(
+        root,
+        config,
+        &entries,
+        &run.suppressed,
+        &run.findings,
+        &trend,
+        output,
+    )
+}
+
+/// The dashboard and Markdown summary of the stored run `run_id`, without
+/// writing them, or `None` if its results aren't kept.
+pub fn render(root: &Path, config: &Config, run_id: &str) -> Result<Option<(String, String)>> {
+    let Some(run) = results::load(root, run_id)? else {
+        return Ok(None);
+    };
+    let trend = trends::collect(root, &run);
+    let entries = local_entries(root, run.entries);
+    Ok(Some(render_reports(
+        root,
+        config,
+        &entries,
+        &run.suppressed,
+        &run.findings,
+        &trend,
+    )))
+}
+
+/// `entries` with their logs looked up under this workspace's `.oav`.
+fn local_entries(root: &Path, entries: Vec<StatusEntry>) -> Vec<StatusEntry> {
+    entries
+        .into_iter()
+        .map(|mut entry| {
+            entry.log_path = local_log_path(root, &entry.log_path);
+            entry
         })
-        .collect();
-    Ok(entries)
+        .collect()
+}
+
+/// `log_path` re-rooted at this workspace's `.oav` when that file exists,
+/// else as recorded.
+fn local_log_path(root: &Path, log_path: &str) -> String {
+    let posix = log_path.replace('\\', "/");
+    posix
+        .rfind(&format!("/{OAV_DIR}/"))
+        .map(|index| root.join(&posix[index + 1..]))
+        .filter(|path| path.is_file())
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| log_path.to_string())
 }
 
-pub fn run(root: &Path, output: &Output) -> Result<bool> {
+fn write_reports(
+    root: &Path,
+    config: &Config,
+    entries: &[StatusEntry],
+    suppressed: &[SuppressedFinding],
+    findings: &[MergedFinding],
+    trend: &[Point],
+    output: &Output,
+) -> Result<bool> {
     let reports_dir = root.join(OAV_DIR).join("reports");
     fs::create_dir_all(&reports_dir).context("Failed to create reports directory")?;
-    let status_path = root.join(OAV_DIR).join("status.tsv");
     let output_path = reports_dir.join("dashboard.html");
+    let (html, summary) = render_reports(root, config, entries, suppressed, findings, trend);
 
-    let entries = load_status_entries(&status_path)?;
-    let html = generate_html(&entries);
-
-    if let Err(err) = fs::write(&output_path, html) {
+    if let Err(err) = fs::write(&output_path, html)
+        .and_then(|_| fs::write(reports_dir.join("summary.md"), summary))
+    {
         if !output.quiet {
             eprintln!("Report generation failed: {err}");
         }
@@ -59,10 +199,92 @@ pub fn run(root: &Path, output: &Output) -> Result<bool> {
     Ok(true)
 }
 
-fn generate_html(entries: &[StatusEntry]) -> String {
+/// The dashboard HTML and the Markdown summary.
+fn render_reports(
+    root: &Path,
+    config: &Config,
+    entries: &[StatusEntry],
+    suppressed: &[SuppressedFinding],
+    findings: &[MergedFinding],
+    trend: &[Point],
+) -> (String, String) {
+    let reports_dir = root.join(OAV_DIR).join("reports");
+    let content_types = load_content_types(&reports_dir.join("content-types.tsv"));
+    let links = LogLinks {
+        reports_dir: &reports_dir,
+        artifact_url_template: config.artifact_url_template.as_deref(),
+    };
+    (
+        generate_html(entries, suppressed, findings, trend, &content_types, &links),
+        generate_summary(entries, findings, &links),
+    )
+}
+
+/// Markdown summary suitable for posting as a pull request comment.
+fn generate_summary(
+    entries: &[StatusEntry],
+    findings: &[MergedFinding],
+    links: &LogLinks,
+) -> String {
+    let passed = entries.iter().filter(|e| e.passed()).count();
+    let failed = entries.iter().filter(|e| e.failed()).count();
+    let headline = if failed > 0 { "failed" } else { "passed" };
+
+    let mut md =
+        format!("## OpenAPI validation {headline}\n\n{passed} passed, {failed} failed\n\n");
+    if !findings.is_empty() {
+        md.push_str(&format!("{}\n\n", findings_count(findings)));
+    }
+    md.push_str("| Stage | Scope | Target | Status | Log |\n|---|---|---|---|---|\n");
+    for entry in entries {
+        let log_name = Path::new(&entry.log_path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("log");
+        md.push_str(&format!(
+            "| {} | {} | {} | {} | [{log_name}]({}) |\n",
+            entry.stage,
+            entry.scope,
+            entry.target,
+            entry.status_label(),
+            links.href(entry)
+        ));
+    }
+    md
+}
+
+/// `(operation, direction, content_type, status)` rows written by the native checks.
+fn load_content_types(path: &Path) -> Vec<(String, String, String, String)> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    content
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split('\t').collect();
+            if parts.len() >= 4 {
+                Some((
+                    parts[0].to_string(),
+                    parts[1].to_string(),
+                    parts[2].to_string(),
+                    parts[3].to_string(),
+                ))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn generate_html(
+    entries: &[StatusEntry],
+    suppressed: &[SuppressedFinding],
+    findings: &[MergedFinding],
+    trend: &[Point],
+    content_types: &[(String, String, String, String)],
+    links: &LogLinks,
+) -> String {
     let total = entries.len();
-    let passed = entries.iter().filter(|e| e.status == "ok").count();
-    let failed = entries.iter().filter(|e| e.status == "fail").count();
+    let passed = entries.iter().filter(|e| e.passed()).count();
+    let failed = entries.iter().filter(|e| e.failed()).count();
 
     let mut html = String::from(HTML_HEAD);
     html.push_str(&format!(
@@ -81,34 +303,34 @@ fn generate_html(entries: &[StatusEntry]) -> String {
   </div>
 "#
     ));
+    html.push_str(&trends::section(trend));
+    if !entries.is_empty() {
+        html.push_str(&filter_bar(entries));
+    }
 
-    for section in ["lint", "generate", "compile"] {
+    for section in STAGES {
         let section_entries: Vec<&StatusEntry> =
             entries.iter().filter(|e| e.stage == section).collect();
         if section_entries.is_empty() {
             continue;
         }
 
-        let title = match section {
-            "lint" => "Lint",
-            "generate" => "Generate",
-            "compile" => "Compile",
-            _ => section,
-        };
+        let title = stage_title(section);
 
         html.push_str(&format!(
-            r#"  <div class="section">
+            r#"  <div class="section" data-stage="{section}">
     <h2>{title}</h2>
     <table class="result-table">
       <thead>
-        <tr><th>Scope</th><th>Target</th><th>Status</th><th>Log</th></tr>
+        <tr><th>Scope</th><th>Target</th><th>Status</th><th>Warnings</th><th>Duration</th><th>Peak memory</th><th>CPU time</th><th>Log</th></tr>
       </thead>
       <tbody>
 "#
         ));
 
-        for entry in section_entries {
-            let badge = html_escape(&entry.status);
+        for entry in &section_entries {
+            let badge = html_escape(entry.badge());
+            let status = html_escape(&entry.status_label());
             let scope = html_escape(&entry.scope);
             let target = html_escape(&entry.target);
             let log_path = Path::new(&entry.log_path);
@@ -117,15 +339,44 @@ fn generate_html(entries: &[StatusEntry]) -> String {
                 .and_then(|name| name.to_str())
                 .unwrap_or("log");
             let log_content = html_escape(&read_log_snippet(log_path));
+            let log_href = html_escape(&links.href(entry));
+            let (memory, cpu) = match &entry.metrics {
+                Some(metrics) => (
+                    format_bytes(metrics.peak_memory_bytes),
+                    format!("{:.1}s", metrics.cpu_seconds),
+                ),
+                None => ("-".to_string(), "-".to_string()),
+            };
+            let title = entry
+                .known_issue
+                .as_deref()
+                .map(|reason| format!(r#" title="{}""#, html_escape(reason)))
+                .unwrap_or_default();
+            let warnings = match entry.warnings.len() {
+                0 => "-".to_string(),
+                count => count.to_string(),
+            };
+            let duration = duration_cell(entry);
+            let result = if entry.failed() {
+                "failed"
+            } else if entry.passed() {
+                "passed"
+            } else {
+                "other"
+            };
 
             html.push_str(&format!(
-                r#"        <tr>
+                r#"        <tr class="task" data-stage="{section}" data-scope="{scope}" data-result="{result}">
           <td>{scope}</td>
           <td>{target}</td>
-          <td><span class="badge {badge}">{badge}</span></td>
+          <td><span class="badge {badge}"{title}>{status}</span></td>
+          <td>{warnings}</td>
+          {duration}
+          <td>{memory}</td>
+          <td>{cpu}</td>
           <td>
             <details>
-              <summary>{log_basename}</summary>
+              <summary>{log_basename} <a href="{log_href}">open</a></summary>
               <pre><code>{log_content}</code></pre>
             </details>
           </td>
@@ -140,12 +391,337 @@ fn generate_html(entries: &[StatusEntry]) -> String {
   </div>
 "#,
         );
+
+        if section_entries
+            .iter()
+            .any(|entry| !entry.warnings.is_empty())
+        {
+            html.push_str(&warnings_section(&section_entries));
+        }
+    }
+
+    if entries.iter().any(|entry| entry.duration_ms.is_some()) {
+        html.push_str(&slowest_section(entries));
+    }
+    if !findings.is_empty() {
+        html.push_str(&findings_section(findings));
+    }
+    if !suppressed.is_empty() {
+        html.push_str(&suppressed_section(suppressed));
+    }
+    if !content_types.is_empty() {
+        html.push_str(&content_type_section(content_types));
     }
 
+    if !entries.is_empty() {
+        html.push_str(FILTER_SCRIPT);
+    }
     html.push_str(HTML_FOOTER);
     html
 }
 
+/// Stages in the order the dashboard shows them.
+const STAGES: [&str; 9] = [
+    "lint", "security", "generate", "coverage", "compile", "test", "smoke", "contract", "custom",
+];
+
+fn stage_title(stage: &str) -> &str {
+    match stage {
+        "lint" => "Lint",
+        "security" => "Security",
+        "generate" => "Generate",
+        "coverage" => "Webhook &amp; Callback Coverage",
+        "compile" => "Compile",
+        "test" => "Test",
+        "smoke" => "Smoke",
+        "contract" => "Contract",
+        "custom" => "Custom Steps",
+        _ => stage,
+    }
+}
+
+/// Controls that narrow the stage tables to a result, stage, scope, or
+/// text in a task's target or log; [`FILTER_SCRIPT`] applies them.
+fn filter_bar(entries: &[StatusEntry]) -> String {
+    let mut stages = String::new();
+    for stage in STAGES {
+        if entries.iter().any(|e| e.stage == stage) {
+            stages.push_str(&format!(
+                r#"      <option value="{stage}">{}</option>
+"#,
+                stage_title(stage)
+            ));
+        }
+    }
+    let mut scopes: Vec<&str> = entries.iter().map(|e| e.scope.as_str()).collect();
+    scopes.sort_unstable();
+    scopes.dedup();
+    let scopes: String = scopes
+        .iter()
+        .map(|scope| {
+            let scope = html_escape(scope);
+            format!(
+                r#"      <option value="{scope}">{scope}</option>
+"#
+            )
+        })
+        .collect();
+    format!(
+        r#"  <div class="filters" id="filters">
+    <select id="filter-result" aria-label="Result">
+      <option value="">All results</option>
+      <option value="failed">Failed</option>
+      <option value="passed">Passed</option>
+      <option value="other">Other</option>
+    </select>
+    <select id="filter-stage" aria-label="Stage">
+      <option value="">All stages</option>
+{stages}    </select>
+    <select id="filter-scope" aria-label="Scope">
+      <option value="">All scopes</option>
+{scopes}    </select>
+    <input id="filter-text" type="search" placeholder="Search targets and logs" aria-label="Search">
+    <span class="stat-label" id="filter-count"></span>
+  </div>
+"#
+    )
+}
+
+/// Warnings of each task that had any, collapsed per task.
+fn warnings_section(entries: &[&StatusEntry]) -> String {
+    let mut html = String::from(
+        r#"  <div class="section">
+    <h3>Warnings</h3>
+"#,
+    );
+    for entry in entries.iter().filter(|entry| !entry.warnings.is_empty()) {
+        let items: String = entry
+            .warnings
+            .iter()
+            .map(|warning| format!("          <li>{}</li>\n", html_escape(warning)))
+            .collect();
+        html.push_str(&format!(
+            r#"    <details>
+      <summary>{} {} ({})</summary>
+      <ul>
+{items}      </ul>
+    </details>
+"#,
+            html_escape(&entry.scope),
+            html_escape(&entry.target),
+            entry.warnings.len()
+        ));
+    }
+    html.push_str("  </div>\n");
+    html
+}
+
+/// The task's duration, with its start time on hover.
+fn duration_cell(entry: &StatusEntry) -> String {
+    let title = entry
+        .started_at
+        .as_deref()
+        .map(|at| format!(r#" title="Started {}""#, html_escape(at)))
+        .unwrap_or_default();
+    let duration = entry
+        .duration_ms
+        .map(format_duration)
+        .unwrap_or_else(|| "-".to_string());
+    format!("<td{title}>{duration}</td>")
+}
+
+/// The timed tasks that took longest, across stages.
+fn slowest_section(entries: &[StatusEntry]) -> String {
+    let mut timed: Vec<&StatusEntry> = entries
+        .iter()
+        .filter(|entry| entry.duration_ms.is_some())
+        .collect();
+    timed.sort_by_key(|entry| std::cmp::Reverse(entry.duration_ms));
+    let mut html = String::from(
+        r#"  <div class="section">
+    <h2>Slowest Tasks</h2>
+    <table class="result-table">
+      <thead>
+        <tr><th>Stage</th><th>Scope</th><th>Target</th><th>Duration</th><th>Started</th></tr>
+      </thead>
+      <tbody>
+"#,
+    );
+    for entry in timed.into_iter().take(SLOWEST_TASKS) {
+        html.push_str(&format!(
+            r#"        <tr>
+          <td>{}</td>
+          <td>{}</td>
+          <td>{}</td>
+          <td>{}</td>
+          <td>{}</td>
+        </tr>
+"#,
+            html_escape(&entry.stage),
+            html_escape(&entry.scope),
+            html_escape(&entry.target),
+            format_duration(entry.duration_ms.unwrap_or_default()),
+            html_escape(entry.started_at.as_deref().unwrap_or("-"))
+        ));
+    }
+    html.push_str(
+        r#"      </tbody>
+    </table>
+  </div>
+"#,
+    );
+    html
+}
+
+/// How many distinct findings there are, and how many several checks reported.
+fn findings_count(findings: &[MergedFinding]) -> String {
+    let shared = findings
+        .iter()
+        .filter(|finding| finding.checks.len() > 1)
+        .count();
+    let mut count = format!("{} finding(s)", findings.len());
+    if shared > 0 {
+        count.push_str(&format!(", {shared} reported by more than one check"));
+    }
+    count
+}
+
+/// Findings that count, one row per problem with every check that reported it.
+fn findings_section(findings: &[MergedFinding]) -> String {
+    let mut html = format!(
+        r#"  <div class="section">
+    <h2>Findings</h2>
+    <p>{}</p>
+    <table class="result-table">
+      <thead>
+        <tr><th>Severity</th><th>Rule</th><th>Path</th><th>Message</th><th>Checks</th></tr>
+      </thead>
+      <tbody>
+"#,
+        findings_count(findings)
+    );
+    for finding in findings {
+        html.push_str(&format!(
+            r#"        <tr>
+          <td>{}</td>
+          <td>{}</td>
+          <td><code>{}</code></td>
+          <td>{}</td>
+          <td>{}</td>
+        </tr>
+"#,
+            html_escape(&finding.severity),
+            html_escape(&finding.rule),
+            html_escape(&finding.path),
+            html_escape(&finding.message),
+            html_escape(&finding.checks.join(", "))
+        ));
+    }
+    html.push_str(
+        r#"      </tbody>
+    </table>
+  </div>
+"#,
+    );
+    html
+}
+
+/// Lint findings `.oavignore` left out, with the reasons given there.
+fn suppressed_section(suppressed: &[SuppressedFinding]) -> String {
+    let mut html = String::from(
+        r#"  <div class="section">
+    <h2>Suppressed</h2>
+    <table class="result-table">
+      <thead>
+        <tr><th>Check</th><th>Rule</th><th>Path</th><th>Message</th><th>Reason</th></tr>
+      </thead>
+      <tbody>
+"#,
+    );
+    for finding in suppressed {
+        html.push_str(&format!(
+            r#"        <tr>
+          <td>{}</td>
+          <td>{}</td>
+          <td><code>{}</code></td>
+          <td>{}</td>
+          <td>{}</td>
+        </tr>
+"#,
+            html_escape(&finding.check),
+            html_escape(&finding.rule),
+            html_escape(&finding.path),
+            html_escape(&finding.message),
+            html_escape(finding.reason.as_deref().unwrap_or("-"))
+        ));
+    }
+    html.push_str(
+        r#"      </tbody>
+    </table>
+  </div>
+"#,
+    );
+    html
+}
+
+fn content_type_section(rows: &[(String, String, String, String)]) -> String {
+    let mut operations: Vec<&str> = Vec::new();
+    for (operation, _, _, _) in rows {
+        if !operations.contains(&operation.as_str()) {
+            operations.push(operation);
+        }
+    }
+
+    let mut html = String::from(
+        r#"  <div class="section">
+    <h2>Content Types</h2>
+    <table class="result-table">
+      <thead>
+        <tr><th>Operation</th><th>Request</th><th>Response</th></tr>
+      </thead>
+      <tbody>
+"#,
+    );
+    for operation in operations {
+        let cell = |direction: &str| {
+            rows.iter()
+                .filter(|(op, dir, _, _)| op == operation && dir == direction)
+                .map(|(_, _, content_type, status)| {
+                    let class = if status == "ok" {
+                        "type"
+                    } else {
+                        status.as_str()
+                    };
+                    format!(
+                        r#"<span class="badge {}">{}</span>"#,
+                        html_escape(class),
+                        html_escape(content_type)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        html.push_str(&format!(
+            r#"        <tr>
+          <td><code>{}</code></td>
+          <td>{}</td>
+          <td>{}</td>
+        </tr>
+"#,
+            html_escape(operation),
+            cell("request"),
+            cell("response")
+        ));
+    }
+    html.push_str(
+        r#"      </tbody>
+    </table>
+  </div>
+"#,
+    );
+    html
+}
+
 fn read_log_snippet(path: &Path) -> String {
     match File::open(path) {
         Ok(file) => {
@@ -157,7 +733,7 @@ fn read_log_snippet(path: &Path) -> String {
     }
 }
 
-fn html_escape(input: &str) -> String {
+pub fn html_escape(input: &str) -> String {
     input
         .replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -197,7 +773,10 @@ const HTML_HEAD: &str = r#"<!DOCTYPE html>
     .result-table tr:last-child td { border-bottom: none; }
     .badge { display: inline-block; padding: 2px 8px; border-radius: 12px; font-size: 0.85em; font-weight: 500; }
     .badge.ok { background: var(--green); color: #fff; }
-    .badge.fail { background: var(--red); color: #fff; }
+    .badge.fail, .badge.timeout, .badge.oom { background: var(--red); color: #fff; }
+    .badge.warn { background: var(--yellow); color: #fff; }
+    .badge.cached { background: var(--link); color: #fff; }
+    .badge.type { background: var(--border); color: var(--fg); }
     details { background: var(--code-bg); border: 1px solid var(--border); border-radius: 6px; margin-top: 10px; }
     summary { padding: 12px; cursor: pointer; font-weight: 500; }
     summary:hover { background: var(--border); }
@@ -207,6 +786,12 @@ const HTML_HEAD: &str = r#"<!DOCTYPE html>
     a { color: var(--link); text-decoration: none; }
     a:hover { text-decoration: underline; }
     .empty { color: #8b949e; font-style: italic; }
+    .trends { display: flex; gap: 20px; flex-wrap: wrap; }
+    .filters { display: flex; gap: 12px; align-items: center; margin-bottom: 30px; flex-wrap: wrap; }
+    .filters select, .filters input { background: var(--code-bg); color: var(--fg); border: 1px solid var(--border);
+                                      border-radius: 6px; padding: 6px 10px; font: inherit; }
+    .filters input { min-width: 240px; }
+    .chart { background: var(--code-bg); border: 1px solid var(--border); border-radius: 6px; padding: 16px; }
   </style>
 </head>
 <body>
@@ -214,6 +799,46 @@ const HTML_HEAD: &str = r#"<!DOCTYPE html>
   <div class="summary">
 "#;
 
+/// Hides task rows that don't match the filter bar, and stage sections left
+/// without rows. Logs matching the search are expanded.
+const FILTER_SCRIPT: &str = r#"  <script>
+    (function () {
+      const controls = ["result", "stage", "scope", "text"].map(function (name) {
+        return document.getElementById("filter-" + name);
+      });
+      const rows = Array.from(document.querySelectorAll("tr.task"));
+      function apply() {
+        const result = controls[0].value;
+        const stage = controls[1].value;
+        const scope = controls[2].value;
+        const text = controls[3].value.trim().toLowerCase();
+        let shown = 0;
+        rows.forEach(function (row) {
+          const log = row.querySelector("details");
+          const logMatch = text !== "" && log !== null && log.textContent.toLowerCase().includes(text);
+          const match = (result === "" || row.dataset.result === result)
+            && (stage === "" || row.dataset.stage === stage)
+            && (scope === "" || row.dataset.scope === scope)
+            && (text === "" || logMatch || row.textContent.toLowerCase().includes(text));
+          row.hidden = !match;
+          if (log !== null && text !== "") {
+            log.open = match && logMatch;
+          }
+          shown += match ? 1 : 0;
+        });
+        document.querySelectorAll(".section[data-stage]").forEach(function (section) {
+          section.hidden = section.querySelector("tr.task:not([hidden])") === null;
+        });
+        document.getElementById("filter-count").textContent =
+          shown === rows.length ? "" : shown + " of " + rows.length + " tasks";
+      }
+      controls.forEach(function (control) {
+        control.addEventListener("input", apply);
+      });
+    })();
+  </script>
+"#;
+
 const HTML_FOOTER: &str = r#"  <footer style="margin-top: 40px; padding-top: 20px; border-top: 1px solid var(--border); color: #8b949e; font-size: 0.85em;">
     Generated by OpenAPI Validator.
   </footer>
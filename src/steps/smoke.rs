@@ -0,0 +1,263 @@
+use anyhow::{Context, Result};
+use serde_yaml::Value;
+use std::fs;
+use std::path::Path;
+use std::process::Stdio;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::active_generators;
+use super::compile;
+use crate::config::Config;
+use crate::docker::{self, RunStatus};
+use crate::output::Output;
+use crate::results::ResultsStore;
+use crate::util::{OAV_DIR, append_error, write_log_header};
+
+/// Service that probes the servers from inside the project's network.
+const PROBE_SERVICE: &str = "smoke-probe";
+/// How long a server may take to answer when `timeouts.smoke` is unset.
+const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(300);
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Boot each generated server in its `smoke-<generator>` compose service,
+/// wait for it to answer on `smoke_path`, and pass it if the answer isn't a
+/// 5xx. Servers without a smoke service are skipped.
+pub fn run(root: &Path, config: &Config, results: &ResultsStore, output: &Output) -> Result<bool> {
+    let report_dir = root
+        .join(OAV_DIR)
+        .join("reports")
+        .join("smoke")
+        .join("server");
+    fs::create_dir_all(&report_dir).context("Failed to create smoke reports directory")?;
+
+    let (compose, compose_args) = compile::compose_project(root, config, results)?;
+    let (servers, missing) = servers(config, &compose);
+    if !missing.is_empty() {
+        output.println(&format!("No smoke test for {}", missing.join(", ")));
+    }
+
+    let timeout = ready_timeout(config)?;
+    let mut failures = 0;
+    for name in servers {
+        if results.should_stop() {
+            break;
+        }
+        let log_path = report_dir.join(format!("{name}.log"));
+        let label = format!("Smoke server {name}");
+        output.substep_start(&label);
+        results.start("smoke", "server", &name);
+
+        let status = match Server::start(&compose, &compose_args, config, &name, &log_path, output)?
+        {
+            Some(server) => {
+                let status =
+                    server.wait_ready(&compose_args, &config.smoke_path, timeout, &log_path)?;
+                server.stop(&log_path, output)?;
+                status
+            }
+            None => RunStatus::Failed,
+        };
+
+        let success = status.success();
+        results.record("smoke", "server", &name, status.as_str(), &log_path)?;
+        let label = status.label(&label);
+        if !success && config.warn_only("smoke") {
+            output.substep_warn(&label);
+        } else {
+            output.substep_finish(&label, success);
+        }
+        if !success {
+            failures += 1;
+        }
+    }
+
+    down(&compose_args);
+    Ok(failures == 0)
+}
+
+/// Selected servers with a smoke service, and those without.
+pub(super) fn servers(config: &Config, compose: &Value) -> (Vec<String>, Vec<String>) {
+    let services = compose.get("services");
+    active_generators(config)
+        .into_iter()
+        .filter(|(scope, _)| *scope == "server")
+        .map(|(_, name)| name)
+        .partition(|name| {
+            services
+                .and_then(|services| services.get(format!("smoke-{name}")))
+                .is_some()
+        })
+}
+
+/// How long a booted server may take to answer: `timeouts.smoke`, or five minutes.
+pub(super) fn ready_timeout(config: &Config) -> Result<Duration> {
+    Ok(config.timeout("smoke")?.unwrap_or(DEFAULT_READY_TIMEOUT))
+}
+
+/// Remove the compose project's leftover containers and network.
+pub(super) fn down(compose_args: &[String]) {
+    let _ = docker::engine()
+        .command()
+        .args(compose_args)
+        .args(["down", "--remove-orphans"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// A generated server running in the background in its smoke service.
+pub(super) struct Server {
+    container: String,
+    /// `http://<container>:<port>`, reachable on the compose project's network.
+    pub(super) origin: String,
+}
+
+impl Server {
+    /// Start the smoke service of `name`, logging the command to `log_path`.
+    /// `None` if it didn't start.
+    pub(super) fn start(
+        compose: &Value,
+        compose_args: &[String],
+        config: &Config,
+        name: &str,
+        log_path: &Path,
+        output: &Output,
+    ) -> Result<Option<Server>> {
+        let service = format!("smoke-{name}");
+        let port = exposed_port(compose, &service);
+        let container = docker::container_name(&service);
+        let mut args = compose_args.to_vec();
+        args.extend([
+            "run".to_string(),
+            "-d".to_string(),
+            "--name".to_string(),
+            container.clone(),
+        ]);
+        args.extend(config.docker_args("smoke", Some(name)));
+        args.push(service);
+        write_log_header(
+            log_path,
+            &format!("$ {} {}", docker::engine().binary(), args.join(" ")),
+        )?;
+        let (started, _) = docker::run_with_logging(
+            docker::engine().command().args(&args),
+            log_path,
+            output,
+            None,
+            None,
+        )?;
+        let origin = format!("http://{container}:{port}");
+        Ok(started.success().then_some(Server { container, origin }))
+    }
+
+    /// Poll `path` until the server answers: `Ok` for any status below 500,
+    /// `Failed` for a 5xx or a server that exits, `TimedOut` when it never
+    /// answers.
+    pub(super) fn wait_ready(
+        &self,
+        compose_args: &[String],
+        path: &str,
+        timeout: Duration,
+        log_path: &Path,
+    ) -> Result<RunStatus> {
+        let url = format!("{}{path}", self.origin);
+        let deadline = Instant::now() + timeout;
+        loop {
+            let response = docker::engine()
+                .command()
+                .args(compose_args)
+                .args(["run", "--rm", PROBE_SERVICE])
+                .args(["-s", "-o", "/dev/null", "-w", "%{http_code}"])
+                .args(["--max-time", "5", &url])
+                .stderr(Stdio::null())
+                .output()
+                .context("Failed to run the smoke probe")?;
+            let code = String::from_utf8_lossy(&response.stdout).trim().to_string();
+            if let Ok(code) = code.parse::<u16>()
+                && code > 0
+            {
+                append_error(log_path, &format!("GET {url} -> {code}"))?;
+                return Ok(if code < 500 {
+                    RunStatus::Ok
+                } else {
+                    RunStatus::Failed
+                });
+            }
+            if !is_running(&self.container) {
+                append_error(log_path, "Server exited before answering")?;
+                return Ok(RunStatus::Failed);
+            }
+            if Instant::now() >= deadline {
+                append_error(
+                    log_path,
+                    &format!("No answer from {url} within {}s", timeout.as_secs()),
+                )?;
+                return Ok(RunStatus::TimedOut);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Append the server's output to `log_path` and remove its container.
+    pub(super) fn stop(self, log_path: &Path, output: &Output) -> Result<()> {
+        append_error(log_path, "\n--- server output ---")?;
+        let mut logs = docker::engine().command();
+        logs.args(["logs", &self.container]);
+        docker::run_with_logging(&mut logs, log_path, output, None, None)?;
+        let _ = docker::engine()
+            .command()
+            .args(["rm", "-f", &self.container])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        Ok(())
+    }
+}
+
+/// Images of the smoke services the selected servers use, and of the probe.
+pub(super) fn images(root: &Path, config: &Config) -> Result<Vec<String>> {
+    let compose = compile::load_compose(&root.join(OAV_DIR).join("docker-compose.yaml"))?;
+    let image = |service: &str| {
+        compose
+            .get("services")?
+            .get(service)?
+            .get("image")?
+            .as_str()
+            .map(str::to_string)
+    };
+    let mut images: Vec<String> = active_generators(config)
+        .into_iter()
+        .filter(|(scope, _)| *scope == "server")
+        .filter_map(|(_, name)| image(&format!("smoke-{name}")))
+        .collect();
+    if !images.is_empty() {
+        images.extend(image(PROBE_SERVICE));
+    }
+    Ok(images)
+}
+
+fn is_running(container: &str) -> bool {
+    docker::engine()
+        .command()
+        .args(["inspect", "-f", "{{.State.Running}}", container])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "true")
+        .unwrap_or(false)
+}
+
+/// First port the service declares under `expose`, else 8080.
+fn exposed_port(compose: &Value, service: &str) -> String {
+    compose
+        .get("services")
+        .and_then(|services| services.get(service))
+        .and_then(|definition| definition.get("expose"))
+        .and_then(Value::as_sequence)
+        .and_then(|ports| ports.first())
+        .and_then(|port| match port {
+            Value::Number(number) => Some(number.to_string()),
+            Value::String(port) => Some(port.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| "8080".to_string())
+}
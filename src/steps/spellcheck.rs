@@ -0,0 +1,255 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_yaml::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use super::check;
+use crate::config::Config;
+use crate::docker::{self, RunStatus};
+use crate::output::Output;
+use crate::remote;
+use crate::results::ResultsStore;
+use crate::rules::{self, Finding};
+use crate::util::{OAV_DIR, append_error, to_posix_path, write_atomic, write_log_header};
+
+/// Working files of the spell check, in `.oav`.
+const SPELLCHECK_DIR: &str = "spellcheck";
+/// Keys whose values are data rather than prose, and aren't checked.
+const DATA_KEYS: [&str; 5] = ["example", "value", "default", "enum", "const"];
+
+/// The cspell config: English plus the project's words.
+#[derive(Serialize)]
+struct CspellConfig<'a> {
+    version: &'a str,
+    language: &'a str,
+    words: Vec<String>,
+}
+
+/// Spell-check the spec's `summary` and `description` fields with cspell,
+/// reporting each unknown word at the JSON pointer of the field it's in.
+/// Words in `spellcheck_dictionary` are accepted.
+pub fn run(
+    root: &Path,
+    spec_path: &Path,
+    config: &Config,
+    results: &ResultsStore,
+    output: &Output,
+) -> Result<bool> {
+    let reports_dir = root.join(OAV_DIR).join("reports").join("lint");
+    fs::create_dir_all(&reports_dir).context("Failed to create lint reports directory")?;
+    let work_dir = Path::new(OAV_DIR).join(SPELLCHECK_DIR);
+    fs::create_dir_all(root.join(&work_dir)).context("Failed to create spellcheck directory")?;
+    let log_path = root.join(&work_dir).join("cspell.log");
+
+    let doc = rules::load_document(&root.join(spec_path))?;
+    let mut fields = Vec::new();
+    collect_prose(&doc, "#", &mut fields);
+    // One line of prose.txt per line of prose, so cspell's line numbers map
+    // back to the field.
+    let mut pointers = Vec::new();
+    let mut prose = String::new();
+    for (pointer, text) in &fields {
+        for line in strip_code(text).lines() {
+            prose.push_str(line);
+            prose.push('\n');
+            pointers.push(pointer.as_str());
+        }
+    }
+    let words = match &config.spellcheck_dictionary {
+        Some(path) => load_dictionary(&root.join(path))
+            .with_context(|| format!("Invalid spellcheck_dictionary {path}"))?,
+        None => Vec::new(),
+    };
+    write_atomic(&root.join(work_dir.join("prose.txt")), &prose)?;
+    write_atomic(
+        &root.join(work_dir.join("cspell.json")),
+        &serde_json::to_string_pretty(&CspellConfig {
+            version: "0.2",
+            language: "en",
+            words,
+        })?,
+    )?;
+    if let Some(remote) = remote::active() {
+        remote.push(root, std::slice::from_ref(&work_dir))?;
+    }
+
+    let image = &config.cspell_image;
+    let platform = config.platform.as_deref();
+    let user_args: Vec<String> = docker::explicit_user(config)
+        .map(|user| vec!["--user".to_string(), user])
+        .unwrap_or_default();
+    let extra_args = config.docker_args("lint", None);
+    let mount = remote::work_mount(root, config);
+    let container_dir = format!("/work/{}", to_posix_path(&work_dir));
+    let cspell_args = [
+        "lint".to_string(),
+        "--no-progress".to_string(),
+        "--no-summary".to_string(),
+        "--no-must-find-files".to_string(),
+        "--config".to_string(),
+        format!("{container_dir}/cspell.json"),
+        format!("{container_dir}/prose.txt"),
+    ];
+    let command_line = format!(
+        "$ {engine} run {user} {platform} {extra} -v {mount} {image} {args}",
+        engine = docker::engine().binary(),
+        user = user_args.join(" "),
+        platform = docker::platform_args(platform).join(" "),
+        extra = extra_args.join(" "),
+        args = cspell_args.join(" ")
+    )
+    .replace("  ", " ");
+    results.start("lint", "spec", "spelling");
+    write_log_header(&log_path, &command_line)?;
+
+    let container = docker::container_name("lint-spelling");
+    let mut command = docker::engine().command();
+    command
+        .arg("run")
+        .arg("--name")
+        .arg(&container)
+        .args(&user_args)
+        .args(docker::platform_args(platform))
+        .args(docker::env_args(config))
+        .args(&extra_args)
+        .arg("-v")
+        .arg(&mount)
+        .arg(image)
+        .args(&cspell_args);
+    let (status, _) = docker::run_with_logging(
+        &mut command,
+        &log_path,
+        output,
+        Some(&container),
+        config.timeout("lint")?,
+    )?;
+
+    let findings = parse_unknown_words(
+        &fs::read_to_string(&log_path).unwrap_or_default(),
+        &pointers,
+    );
+    // cspell exits non-zero when it finds unknown words; without any, a
+    // failure is cspell's own.
+    if !status.success() && (status != RunStatus::Failed || findings.is_empty()) {
+        append_error(&log_path, "cspell failed without reporting unknown words")?;
+        results.record("lint", "spec", "spelling", status.as_str(), &log_path)?;
+        return Ok(false);
+    }
+    check::record(
+        &reports_dir,
+        config,
+        results,
+        "lint",
+        "spec",
+        "spelling",
+        &findings,
+    )
+}
+
+/// `(pointer, text)` of every string `summary` and `description` under
+/// `value`, skipping example and schema values.
+fn collect_prose(value: &Value, path: &str, fields: &mut Vec<(String, String)>) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, child) in mapping {
+                let Some(key) = key.as_str() else {
+                    continue;
+                };
+                if DATA_KEYS.contains(&key) || key.starts_with("x-") {
+                    continue;
+                }
+                let child_path = rules::pointer(path, key);
+                match child {
+                    Value::String(text) if key == "summary" || key == "description" => {
+                        fields.push((child_path, text.clone()));
+                    }
+                    _ => collect_prose(child, &child_path, fields),
+                }
+            }
+        }
+        Value::Sequence(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                collect_prose(item, &rules::pointer(path, &idx.to_string()), fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `text` with Markdown code blocks and code spans blanked out, keeping its
+/// lines.
+fn strip_code(text: &str) -> String {
+    let mut fenced = false;
+    let mut stripped = Vec::new();
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            fenced = !fenced;
+            stripped.push(String::new());
+            continue;
+        }
+        if fenced {
+            stripped.push(String::new());
+            continue;
+        }
+        let mut kept = String::new();
+        for (idx, part) in line.split('`').enumerate() {
+            // Odd parts are inside a code span.
+            if idx % 2 == 0 {
+                kept.push_str(part);
+            } else {
+                kept.push(' ');
+            }
+        }
+        stripped.push(kept);
+    }
+    stripped.join("\n")
+}
+
+/// Words in a dictionary file: one per line, `#` starting a comment.
+fn load_dictionary(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)?;
+    let words: BTreeSet<String> = content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect();
+    Ok(words.into_iter().collect())
+}
+
+/// Findings for cspell's `<file>:<line>:<col> - Unknown word (<word>)` lines,
+/// one per word and field.
+fn parse_unknown_words(log: &str, pointers: &[&str]) -> Vec<Finding> {
+    let mut seen = BTreeSet::new();
+    let mut findings = Vec::new();
+    for line in log.lines() {
+        let Some((location, rest)) = line.split_once(" - Unknown word (") else {
+            continue;
+        };
+        let Some((word, _)) = rest.split_once(')') else {
+            continue;
+        };
+        let mut parts = location.rsplitn(3, ':');
+        let (Some(_column), Some(number)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let Some(pointer) = number
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| pointers.get(number.checked_sub(1)?))
+        else {
+            continue;
+        };
+        if seen.insert((pointer.to_string(), word.to_string())) {
+            findings.push(Finding::warning(
+                "spelling",
+                pointer.to_string(),
+                format!("Unknown word \"{word}\""),
+            ));
+        }
+    }
+    findings
+}
@@ -0,0 +1,13 @@
+use anyhow::Result;
+use std::path::Path;
+
+use super::compile::{self, BuildStage};
+use crate::config::Config;
+use crate::output::Output;
+use crate::results::ResultsStore;
+
+/// Run the test suites of the generated projects in their `test-*` compose
+/// services, alongside the `build-*` services compile uses.
+pub fn run(root: &Path, config: &Config, results: &ResultsStore, output: &Output) -> Result<bool> {
+    compile::run_stage(root, config, results, output, BuildStage::Test)
+}
@@ -0,0 +1,156 @@
+use std::path::Path;
+
+use super::report::html_escape;
+use crate::history;
+use crate::results::RunResults;
+use crate::util::format_duration;
+
+/// Runs the Trends section plots, the reported one included.
+const TREND_RUNS: usize = 20;
+const WIDTH: f64 = 320.0;
+const HEIGHT: f64 = 100.0;
+const PADDING: f64 = 6.0;
+
+/// What the Trends section plots of one run.
+pub(super) struct Point {
+    run_id: String,
+    passed: usize,
+    failed: usize,
+    findings: usize,
+    /// Sum of the run's steps up to the report, which the reported run
+    /// hasn't finished yet; runs from before steps were timed have none.
+    duration_ms: Option<u64>,
+}
+
+impl Point {
+    pub(super) fn of(run: &RunResults) -> Self {
+        Self {
+            run_id: run.run_id.clone(),
+            passed: run.passed(),
+            failed: run.failed(),
+            findings: run.findings.len(),
+            duration_ms: (!run.steps.is_empty()).then(|| {
+                run.steps
+                    .iter()
+                    .filter(|step| step.step != "Report")
+                    .map(|step| step.duration_ms)
+                    .sum()
+            }),
+        }
+    }
+}
+
+/// `run` and the runs before it in `.oav/history`, oldest first. A history
+/// that can't be read only leaves the section out.
+pub(super) fn collect(root: &Path, run: &RunResults) -> Vec<Point> {
+    let mut points: Vec<Point> = history::list(root)
+        .unwrap_or_default()
+        .iter()
+        .filter(|earlier| earlier.run_id < run.run_id)
+        .take(TREND_RUNS - 1)
+        .map(Point::of)
+        .collect();
+    points.reverse();
+    points.push(Point::of(run));
+    points
+}
+
+/// Charts of task results, findings, and duration across `points`, or
+/// nothing without at least two runs to compare.
+pub(super) fn section(points: &[Point]) -> String {
+    if points.len() < 2 {
+        return String::new();
+    }
+    let count = |value: usize, label: &str| Some((value as f64, format!("{value} {label}")));
+    let charts = [
+        chart(
+            "Tasks",
+            points,
+            &[
+                ("var(--green)", &|point: &Point| {
+                    count(point.passed, "passed")
+                }),
+                ("var(--red)", &|point: &Point| count(point.failed, "failed")),
+            ],
+        ),
+        chart(
+            "Findings",
+            points,
+            &[("var(--yellow)", &|point: &Point| {
+                count(point.findings, "finding(s)")
+            })],
+        ),
+        chart(
+            "Duration",
+            points,
+            &[("var(--link)", &|point: &Point| {
+                point.duration_ms.map(|ms| (ms as f64, format_duration(ms)))
+            })],
+        ),
+    ];
+    format!(
+        r#"  <div class="section">
+    <h2>Trends</h2>
+    <p>Last {} runs, oldest first.</p>
+    <div class="trends">
+{}    </div>
+  </div>
+"#,
+        points.len(),
+        charts.concat()
+    )
+}
+
+/// A value of a point, and how to label it.
+type Series<'a> = (&'a str, &'a dyn Fn(&Point) -> Option<(f64, String)>);
+
+/// A line per series, with each value's run and label on hover.
+fn chart(title: &str, points: &[Point], series: &[Series]) -> String {
+    let max = series
+        .iter()
+        .flat_map(|(_, value)| points.iter().filter_map(value))
+        .map(|(value, _)| value)
+        .fold(1.0, f64::max);
+    let step = (WIDTH - 2.0 * PADDING) / (points.len() - 1) as f64;
+    let latest = series
+        .iter()
+        .filter_map(|(_, value)| points.last().and_then(value))
+        .map(|(_, label)| label)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut svg = String::new();
+    for (color, value) in series {
+        let mut line = Vec::new();
+        let mut marks = String::new();
+        for (index, point) in points.iter().enumerate() {
+            let Some((value, label)) = value(point) else {
+                continue;
+            };
+            let x = PADDING + index as f64 * step;
+            let y = HEIGHT - PADDING - value / max * (HEIGHT - 2.0 * PADDING);
+            line.push(format!("{x:.1},{y:.1}"));
+            marks.push_str(&format!(
+                r#"          <circle cx="{x:.1}" cy="{y:.1}" r="3" fill="{color}"><title>{}: {}</title></circle>
+"#,
+                html_escape(&point.run_id),
+                html_escape(&label)
+            ));
+        }
+        svg.push_str(&format!(
+            r#"          <polyline fill="none" stroke="{color}" stroke-width="2" points="{}"/>
+{marks}"#,
+            line.join(" ")
+        ));
+    }
+    format!(
+        r#"      <div class="chart">
+        <h3>{title}</h3>
+        <div class="stat-label">Latest: {}</div>
+        <svg viewBox="0 0 {WIDTH} {HEIGHT}" width="{WIDTH}" height="{HEIGHT}" role="img" aria-label="{title}">
+{svg}        </svg>
+      </div>
+"#,
+        html_escape(&latest)
+    )
+}
@@ -0,0 +1,328 @@
+use anyhow::{Context, Result};
+use serde_yaml::{Mapping, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+
+use super::compile::{cache_mount, environment_value, named_volumes};
+use crate::config::Config;
+use crate::docker;
+use crate::output::Output;
+use crate::remote;
+use crate::util::{OAV_DIR, append_error, write_atomic, write_log_header};
+
+const WARM_OVERRIDE_FILE: &str = "docker-compose.warm.yaml";
+const GENERATED_MOUNT: &str = "/generated";
+
+/// Where and how a build service's command runs inside its builder.
+struct Exec {
+    /// Container name of the builder.
+    builder: String,
+    working_dir: String,
+    command: String,
+}
+
+struct Builder {
+    /// Service name in the override file.
+    name: String,
+    container: String,
+    image: String,
+    volumes: Vec<String>,
+}
+
+/// Long-lived builder containers, one per scope and image. Each mounts the
+/// whole `generated/<scope>` directory plus its services' cache volumes and
+/// idles, so compile commands can be `docker exec`'d into it instead of
+/// starting a container per generator. Builders are started with
+/// `docker compose run`, taking the `docker_args` that apply to every compile
+/// task, and are removed on drop, or as soon as a command in them times out.
+pub struct Builders {
+    compose_args: Vec<String>,
+    started: Vec<String>,
+    execs: HashMap<String, Exec>,
+}
+
+impl Builders {
+    pub fn start(
+        root: &Path,
+        config: &Config,
+        compose: &Value,
+        compose_args: Vec<String>,
+        services: &[(&str, &str)],
+        reports_root: &Path,
+        output: &Output,
+    ) -> Result<Self> {
+        let named = named_volumes(compose);
+        let mut builders: Vec<Builder> = Vec::new();
+        let mut execs = HashMap::new();
+        for (scope, service) in services {
+            let Some(definition) = compose.get("services").and_then(|s| s.get(*service)) else {
+                continue;
+            };
+            let (Some(image), Some(command)) = (
+                definition.get("image").and_then(Value::as_str),
+                command_string(definition.get("command")),
+            ) else {
+                continue;
+            };
+
+            // Services that don't mount their generated project can't share a builder.
+            let scope_prefix = format!("./generated/{scope}/");
+            let mut project = None;
+            let mut mounts = Vec::new();
+            for volume in definition
+                .get("volumes")
+                .and_then(Value::as_sequence)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                let Some((source, target)) = volume.split_once(':') else {
+                    mounts.push(volume.to_string());
+                    continue;
+                };
+                let target = target.split(':').next().unwrap_or(target);
+                if let Some(name) = source.strip_prefix(&scope_prefix) {
+                    project = Some((name, target));
+                } else if named.contains(&source) {
+                    mounts.push(
+                        cache_mount(root, config, source, target)?
+                            .unwrap_or_else(|| format!("{source}:{target}")),
+                    );
+                } else {
+                    mounts.push(volume.to_string());
+                }
+            }
+            let Some((name, project_target)) = project else {
+                continue;
+            };
+            let working_dir = definition
+                .get("working_dir")
+                .and_then(Value::as_str)
+                .unwrap_or(project_target);
+            let Some(relative) = working_dir.strip_prefix(project_target) else {
+                continue;
+            };
+
+            let builder_name = format!("warm-{scope}-{}", slug(image));
+            let builder = match builders.iter_mut().find(|b| b.name == builder_name) {
+                Some(builder) => builder,
+                None => {
+                    builders.push(Builder {
+                        name: builder_name.clone(),
+                        container: docker::container_name(&builder_name),
+                        image: image.to_string(),
+                        volumes: vec![format!("./generated/{scope}:{GENERATED_MOUNT}")],
+                    });
+                    builders.last_mut().expect("builder was just pushed")
+                }
+            };
+            for mount in mounts {
+                if !builder
+                    .volumes
+                    .iter()
+                    .any(|existing| mount_target(existing) == mount_target(&mount))
+                {
+                    builder.volumes.push(mount);
+                }
+            }
+            execs.insert(
+                (*service).to_string(),
+                Exec {
+                    builder: builder.container.clone(),
+                    working_dir: format!("{GENERATED_MOUNT}/{name}{relative}"),
+                    command,
+                },
+            );
+        }
+
+        let mut warm = Self {
+            compose_args,
+            started: Vec::new(),
+            execs,
+        };
+        if builders.is_empty() {
+            return Ok(warm);
+        }
+
+        let override_path = root.join(OAV_DIR).join(WARM_OVERRIDE_FILE);
+        write_override(&override_path, &builders, config)?;
+        warm.compose_args
+            .extend(["-f".to_string(), override_path.display().to_string()]);
+        let log_path = reports_root.join("_builders.log");
+        output.substep_start("Start warm builders");
+        let mut success = true;
+        for (index, builder) in builders.into_iter().enumerate() {
+            let mut args = warm.compose_args.clone();
+            args.extend([
+                "run".to_string(),
+                "--detach".to_string(),
+                "--name".to_string(),
+                builder.container.clone(),
+            ]);
+            args.extend(config.docker_args("compile", None));
+            args.push(builder.name);
+            let command_line = format!("$ {} {}", docker::engine().binary(), args.join(" "));
+            if index == 0 {
+                write_log_header(&log_path, &command_line)?;
+            } else {
+                append_error(&log_path, &format!("\n{command_line}\n"))?;
+            }
+            let mut command = docker::engine().command();
+            command.args(&args);
+            // Started detached, so the exit status is only whether it started.
+            let started = docker::run_with_logging(&mut command, &log_path, output, None, None)?
+                .0
+                .success();
+            warm.started.push(builder.container);
+            if !started {
+                success = false;
+                break;
+            }
+        }
+        output.substep_finish("Start warm builders", success);
+        if !success {
+            // Fall back to a fresh container per task.
+            warm.execs.clear();
+        }
+        Ok(warm)
+    }
+
+    /// Compose arguments including the builder override file.
+    pub fn compose_args(&self) -> &[String] {
+        &self.compose_args
+    }
+
+    /// Remove the builder `service` ran in, stopping whatever it was still
+    /// running, e.g. a command killing the `docker exec` client doesn't
+    /// stop. Its remaining services get a fresh container each.
+    pub fn stop(&mut self, service: &str) {
+        let Some(container) = self.execs.get(service).map(|exec| exec.builder.clone()) else {
+            return;
+        };
+        let _ = docker::engine()
+            .command()
+            .args(["rm", "--force", "--volumes", &container])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        self.execs.retain(|_, exec| exec.builder != container);
+        self.started.retain(|started| *started != container);
+    }
+
+    /// `docker` arguments running `service`'s command in its builder, if it has one.
+    pub fn exec_args(&self, service: &str) -> Option<Vec<String>> {
+        let exec = self.execs.get(service)?;
+        Some(vec![
+            "exec".to_string(),
+            "-w".to_string(),
+            exec.working_dir.clone(),
+            exec.builder.clone(),
+            "sh".to_string(),
+            "-c".to_string(),
+            exec.command.clone(),
+        ])
+    }
+}
+
+impl Drop for Builders {
+    fn drop(&mut self) {
+        if self.started.is_empty() {
+            return;
+        }
+        let _ = docker::engine()
+            .command()
+            .args(["rm", "--force", "--volumes"])
+            .args(&self.started)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+}
+
+fn write_override(path: &Path, builders: &[Builder], config: &Config) -> Result<()> {
+    let environment = docker::compose_env(config);
+    let mut services = Mapping::new();
+    for builder in builders {
+        let mut definition = Mapping::new();
+        definition.insert("image".into(), builder.image.clone().into());
+        if let Some(platform) = &config.platform {
+            definition.insert("platform".into(), platform.clone().into());
+        }
+        if let Some(user) = docker::explicit_user(config) {
+            definition.insert("user".into(), user.into());
+        }
+        docker::compose_resources(config, &mut definition);
+        if !environment.is_empty() {
+            definition.insert("environment".into(), environment_value(&environment));
+        }
+        definition.insert(
+            "volumes".into(),
+            Value::Sequence(
+                builder
+                    .volumes
+                    .iter()
+                    .map(|volume| remote::compose_volume(&docker::label_mount(config, volume)))
+                    .collect(),
+            ),
+        );
+        definition.insert("init".into(), true.into());
+        definition.insert(
+            "command".into(),
+            Value::Sequence(vec!["tail".into(), "-f".into(), "/dev/null".into()]),
+        );
+        services.insert(builder.name.clone().into(), Value::Mapping(definition));
+    }
+    let mut document = Mapping::new();
+    document.insert("services".into(), Value::Mapping(services));
+    let mut volumes = Mapping::new();
+    remote::declare_compose_volume(&mut volumes);
+    if !volumes.is_empty() {
+        document.insert("volumes".into(), Value::Mapping(volumes));
+    }
+    let yaml = serde_yaml::to_string(&document).context("Failed to serialize builder override")?;
+    write_atomic(
+        path,
+        &format!("# Generated by oav for warm_builders; do not edit.\n{yaml}"),
+    )
+}
+
+/// Compose `command` as a shell string. Compose unescapes `$$` itself, but
+/// `exec` arguments are passed through verbatim.
+fn command_string(command: Option<&Value>) -> Option<String> {
+    let command = match command? {
+        Value::String(command) => command.trim().to_string(),
+        Value::Sequence(parts) => parts
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|part| format!("'{}'", part.replace('\'', r"'\''")))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => return None,
+    };
+    Some(command.replace("$$", "$"))
+}
+
+fn mount_target(volume: &str) -> &str {
+    match volume.split_once(':') {
+        Some((_, target)) => target.split(':').next().unwrap_or(target),
+        None => volume,
+    }
+}
+
+fn slug(image: &str) -> String {
+    let slug: String = image
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
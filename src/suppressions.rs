@@ -0,0 +1,146 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::rules;
+
+/// Lint findings to leave out, kept next to `.oavc`.
+pub const FILE_NAME: &str = ".oavignore";
+
+/// Findings of `rule` at `path`, a JSON pointer glob, or anywhere below it.
+#[derive(Debug, Clone)]
+pub struct Suppression {
+    pub rule: String,
+    pub path: String,
+    pub reason: Option<String>,
+}
+
+/// A path, or a path with the reason it is suppressed.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Path(String),
+    Explained {
+        path: String,
+        reason: Option<String>,
+    },
+}
+
+impl Suppression {
+    pub fn matches(&self, rule: &str, path: &str) -> bool {
+        self.rule == rule && matches_below(&self.path, path)
+    }
+}
+
+/// The suppressions in `.oavignore`: rule IDs mapped to lists of paths. No
+/// file means none.
+pub fn load(root: &Path) -> Result<Vec<Suppression>> {
+    let path = root.join(FILE_NAME);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {FILE_NAME}"))?;
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let rules: BTreeMap<String, Vec<Entry>> =
+        serde_yaml::from_str(&content).with_context(|| format!("Failed to parse {FILE_NAME}"))?;
+    let mut suppressions = Vec::new();
+    for (rule, entries) in rules {
+        for entry in entries {
+            let (path, reason) = match entry {
+                Entry::Path(path) => (path, None),
+                Entry::Explained { path, reason } => (path, reason),
+            };
+            if !path.starts_with('#') && !path.contains(':') {
+                bail!(
+                    "Invalid entry in {FILE_NAME}: {rule}: {path} (expected a JSON pointer such as #/paths/~1pets, or file:line:column)"
+                );
+            }
+            suppressions.push(Suppression {
+                rule: rule.clone(),
+                path,
+                reason,
+            });
+        }
+    }
+    Ok(suppressions)
+}
+
+/// Suppressions from `# oav-ignore: <rule>[, <rule>...] [-- reason]` comments
+/// in YAML `content`, each covering the node on the next line and everything
+/// below it.
+pub fn inline(content: &str) -> Vec<Suppression> {
+    let starts = rules::node_starts(content);
+    let lines: Vec<&str> = content.lines().collect();
+    let mut suppressions = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let Some(comment) = line.trim_start().strip_prefix('#') else {
+            continue;
+        };
+        let Some(rules) = comment.trim_start().strip_prefix("oav-ignore:") else {
+            continue;
+        };
+        let (rules, reason) = match rules.split_once(" -- ") {
+            Some((rules, reason)) => (rules, Some(reason.trim().to_string())),
+            None => (rules, None),
+        };
+        // The comment covers the first node on the next line with content.
+        let Some(next) = (idx + 1..lines.len()).find(|&next| {
+            let trimmed = lines[next].trim_start();
+            !trimmed.is_empty() && !trimmed.starts_with('#')
+        }) else {
+            continue;
+        };
+        let Some(target) = starts.iter().find(|start| start.line == next + 1) else {
+            continue;
+        };
+        let reason = reason.unwrap_or_else(|| format!("oav-ignore comment on line {}", idx + 1));
+        suppressions.extend(
+            rules
+                .split(',')
+                .map(str::trim)
+                .filter(|rule| !rule.is_empty())
+                .map(|rule| Suppression {
+                    rule: rule.to_string(),
+                    path: target.pointer.clone(),
+                    reason: Some(reason.clone()),
+                }),
+        );
+    }
+    suppressions
+}
+
+/// Whether `path` matches `pattern` or lies below a match. In `pattern`, `*`
+/// matches within one segment and `**` any number of segments.
+fn matches_below(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.trim_end_matches('/').split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => true,
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((segment, rest)) => path
+            .split_first()
+            .is_some_and(|(name, path)| match_segment(segment, name) && match_segments(rest, path)),
+    }
+}
+
+fn match_segment(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            name.starts_with(prefix)
+                && (0..=name.len() - prefix.len()).any(|skip| {
+                    name.is_char_boundary(prefix.len() + skip)
+                        && match_segment(rest, &name[prefix.len() + skip..])
+                })
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::{Context, Result, bail};
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::rules;
+use crate::util::{OAV_DIR, to_posix_path, write_atomic};
+
+/// Spec with placeholders substituted, in `.oav`.
+pub const EFFECTIVE_SPEC: &str = "effective-spec.yaml";
+
+/// With `spec_template` on, substitute the spec's `${VAR}` placeholders and
+/// write the result to `.oav/effective-spec.yaml`, which is then validated
+/// instead. Variables come from the environment, then `spec_values`; `${VAR:-default}`
+/// gives a fallback and `$${` is a literal `${`. Returns the spec to use,
+/// relative to `root`.
+pub fn apply(root: &Path, config: &Config, spec_path: PathBuf) -> Result<PathBuf> {
+    if !config.spec_template {
+        return Ok(spec_path);
+    }
+    let values = match &config.spec_values {
+        Some(file) => load_values(&root.join(file))
+            .with_context(|| format!("Invalid spec_values file {file}"))?,
+        None => BTreeMap::new(),
+    };
+    let lookup = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .or_else(|| values.get(name).cloned())
+    };
+
+    let doc = rules::load_document(&root.join(&spec_path))?;
+    let mut missing = BTreeSet::new();
+    let mut doc = substitute_value(&doc, &lookup, &mut missing);
+    if !missing.is_empty() {
+        bail!(
+            "Unresolved placeholders in {}: {} (set them in the environment or spec_values)",
+            spec_path.display(),
+            missing.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    // References stay relative to the original spec's directory.
+    let spec_dir = to_posix_path(spec_path.parent().unwrap_or(Path::new("")));
+    let prefix = if spec_dir.is_empty() {
+        "../".to_string()
+    } else {
+        format!("../{spec_dir}/")
+    };
+    rebase_refs(&mut doc, &prefix);
+
+    let effective = Path::new(OAV_DIR).join(EFFECTIVE_SPEC);
+    write_atomic(&root.join(&effective), &serde_yaml::to_string(&doc)?)
+        .context("Failed to write the effective spec")?;
+    Ok(effective)
+}
+
+fn load_values(path: &Path) -> Result<BTreeMap<String, String>> {
+    let content = fs::read_to_string(path)?;
+    let mapping: Mapping = serde_yaml::from_str(&content)?;
+    mapping
+        .into_iter()
+        .map(|(key, value)| {
+            let key = key
+                .as_str()
+                .context("Value names must be strings")?
+                .to_string();
+            let value = match value {
+                Value::String(value) => value,
+                Value::Number(value) => value.to_string(),
+                Value::Bool(value) => value.to_string(),
+                _ => bail!("{key} must be a string, number, or boolean"),
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+fn substitute_value(
+    value: &Value,
+    lookup: &impl Fn(&str) -> Option<String>,
+    missing: &mut BTreeSet<String>,
+) -> Value {
+    match value {
+        Value::String(text) => Value::String(substitute(text, lookup, missing)),
+        Value::Sequence(items) => Value::Sequence(
+            items
+                .iter()
+                .map(|item| substitute_value(item, lookup, missing))
+                .collect(),
+        ),
+        Value::Mapping(mapping) => Value::Mapping(
+            mapping
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        substitute_value(key, lookup, missing),
+                        substitute_value(value, lookup, missing),
+                    )
+                })
+                .collect(),
+        ),
+        Value::Tagged(tagged) => {
+            let mut tagged = tagged.clone();
+            tagged.value = substitute_value(&tagged.value, lookup, missing);
+            Value::Tagged(tagged)
+        }
+        other => other.clone(),
+    }
+}
+
+/// Replace `${NAME}` and `${NAME:-default}` in `text`, adding names without a
+/// value to `missing`.
+fn substitute(
+    text: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+    missing: &mut BTreeSet<String>,
+) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            result.push_str(&rest[..start - 1]);
+            result.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            result.push_str(&rest[start..]);
+            return result;
+        };
+        let (name, default) = match after[..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&after[..end], None),
+        };
+        match lookup(name).or_else(|| default.map(str::to_string)) {
+            Some(value) => result.push_str(&value),
+            None => {
+                missing.insert(name.to_string());
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Prefix relative file `$ref`s with `prefix`, so they resolve from `.oav`.
+pub fn rebase_refs(value: &mut Value, prefix: &str) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, child) in mapping.iter_mut() {
+                if key.as_str() == Some("$ref")
+                    && let Value::String(reference) = child
+                    && !reference.starts_with('#')
+                    && !reference.starts_with('/')
+                    && !reference.contains("://")
+                {
+                    *reference = format!("{prefix}{reference}");
+                } else {
+                    rebase_refs(child, prefix);
+                }
+            }
+        }
+        Value::Sequence(items) => {
+            for item in items {
+                rebase_refs(item, prefix);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,163 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::http;
+use crate::output::Output;
+use crate::util::{OAV_DIR, unix_now, write_atomic};
+
+const CHECK_INTERVAL_SECS: u64 = 24 * 60 * 60;
+const TAGS_URL: &str = "https://hub.docker.com/v2/repositories";
+
+/// Newest versioned tag per repository, as last fetched, stored in
+/// `.oav/cache/image-updates.json`.
+#[derive(Serialize, Deserialize, Default)]
+struct UpdateCache {
+    checked_at: u64,
+    latest: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct TagPage {
+    results: Vec<Tag>,
+}
+
+#[derive(Deserialize)]
+struct Tag {
+    name: String,
+}
+
+/// With `image_update_check`, print a notice for each tool image that has a
+/// newer version tag on Docker Hub. Tags are fetched at most once a day;
+/// network and registry errors are ignored.
+pub fn check(root: &Path, config: &Config, output: &Output) {
+    if !config.image_update_check {
+        return;
+    }
+    let keys = [
+        ("generator_image", &config.generator_image),
+        ("redocly_image", &config.redocly_image),
+    ];
+    let Ok(latest) = latest_tags(root, keys.iter().map(|(_, image)| image.as_str())) else {
+        return;
+    };
+    for (key, image) in keys {
+        let Some((repository, tag)) = split_tag(image) else {
+            continue;
+        };
+        if let Some(newer) = latest.get(repository).filter(|newer| is_newer(newer, tag)) {
+            output.println(&format!(
+                "A newer {repository} is available: {newer} (using {tag}). Upgrade with: oav config set {key} {repository}:{newer}"
+            ));
+        }
+    }
+}
+
+fn latest_tags<'a>(
+    root: &Path,
+    images: impl Iterator<Item = &'a str>,
+) -> Result<BTreeMap<String, String>> {
+    let path = root.join(OAV_DIR).join("cache").join("image-updates.json");
+    let mut cache: UpdateCache = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    let now = unix_now();
+    if now.saturating_sub(cache.checked_at) >= CHECK_INTERVAL_SECS {
+        cache = UpdateCache {
+            checked_at: now,
+            latest: BTreeMap::new(),
+        };
+    }
+
+    let mut changed = false;
+    for image in images {
+        let Some((repository, tag)) = split_tag(image) else {
+            continue;
+        };
+        if cache.latest.contains_key(repository) {
+            continue;
+        }
+        // Record a miss too, so an unreachable registry isn't retried every run.
+        let newest = fetch_tags(repository)
+            .and_then(|tags| newest_like(&tags, tag))
+            .unwrap_or_default();
+        cache.latest.insert(repository.to_string(), newest);
+        changed = true;
+    }
+    if changed {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create cache directory")?;
+        }
+        write_atomic(&path, &serde_json::to_string_pretty(&cache)?)?;
+    }
+    Ok(cache.latest)
+}
+
+/// Recently updated tags of a Docker Hub repository. Images from other
+/// registries aren't checked.
+fn fetch_tags(repository: &str) -> Option<Vec<String>> {
+    let first = repository.split('/').next()?;
+    if repository.contains('/') && (first.contains(['.', ':']) || first == "localhost") {
+        return None;
+    }
+    let path = if repository.contains('/') {
+        repository.to_string()
+    } else {
+        format!("library/{repository}")
+    };
+    let body = http::get(
+        &format!("{TAGS_URL}/{path}/tags?page_size=100&ordering=last_updated"),
+        Duration::from_secs(5),
+    )
+    .ok()?;
+    let page: TagPage = serde_json::from_str(&body).ok()?;
+    Some(page.results.into_iter().map(|tag| tag.name).collect())
+}
+
+/// Split `repo:tag`, skipping digest-pinned images and unversioned tags.
+fn split_tag(image: &str) -> Option<(&str, &str)> {
+    if image.contains('@') {
+        return None;
+    }
+    let (repository, tag) = image.rsplit_once(':')?;
+    if tag.contains('/') || version(tag).is_none() {
+        return None;
+    }
+    Some((repository, tag))
+}
+
+/// The highest tag versioned like `current` (same `v` prefix and number of
+/// components), skipping pre-releases and variants such as `-alpine`.
+fn newest_like(tags: &[String], current: &str) -> Option<String> {
+    let shape = version(current)?;
+    tags.iter()
+        .filter_map(|tag| version(tag).map(|parsed| (parsed, tag)))
+        .filter(|(parsed, _)| parsed.0 == shape.0 && parsed.1.len() == shape.1.len())
+        .max_by(|(a, _), (b, _)| a.1.cmp(&b.1))
+        .map(|(_, tag)| tag.clone())
+}
+
+fn is_newer(candidate: &str, current: &str) -> bool {
+    match (version(candidate), version(current)) {
+        (Some(candidate), Some(current)) => candidate.1 > current.1,
+        _ => false,
+    }
+}
+
+/// `v7.17.0` -> `(true, [7, 17, 0])`.
+fn version(tag: &str) -> Option<(bool, Vec<u64>)> {
+    let (prefixed, numbers) = match tag.strip_prefix('v') {
+        Some(rest) => (true, rest),
+        None => (false, tag),
+    };
+    let parts = numbers
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    (!parts.is_empty()).then_some((prefixed, parts))
+}
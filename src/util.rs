@@ -3,6 +3,9 @@ use include_dir::{Dir, DirEntry};
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::http;
 
 pub const OAV_DIR: &str = ".oav";
 
@@ -12,6 +15,132 @@ pub fn to_posix_path(path: &Path) -> String {
     path.to_string_lossy().replace('\\', "/")
 }
 
+/// Replace `${NAME}` placeholders with environment variable values (empty when unset).
+pub fn expand_env_vars(input: &str) -> String {
+    let mut result = String::new();
+    let mut rest = input;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                result.push_str(&std::env::var(&after[..end]).unwrap_or_default());
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Whether we are running inside a CI system (the de-facto `CI` variable).
+pub fn is_ci() -> bool {
+    std::env::var("CI")
+        .map(|value| !value.is_empty() && value != "false" && value != "0")
+        .unwrap_or(false)
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Format seconds since the Unix epoch as an RFC 3339 UTC timestamp.
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil-from-days (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3_600,
+        (rem % 3_600) / 60,
+        rem % 60
+    )
+}
+
+/// Write a file via a temporary sibling and rename, so readers never see partial content.
+/// `1536` -> `1.5 KiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// `duration_ms` for people: `850ms`, `12.3s`, or `2m 05s`.
+pub fn format_duration(duration_ms: u64) -> String {
+    match duration_ms {
+        0..1_000 => format!("{duration_ms}ms"),
+        1_000..60_000 => format!("{:.1}s", duration_ms as f64 / 1000.0),
+        _ => {
+            let secs = duration_ms / 1000;
+            format!("{}m {:02}s", secs / 60, secs % 60)
+        }
+    }
+}
+
+/// Copy the files and directories under `source` to `destination`.
+pub fn copy_dir(source: &Path, destination: &Path) -> Result<()> {
+    for entry in walkdir::WalkDir::new(source).follow_links(false) {
+        let entry = entry.with_context(|| format!("Failed to read {}", source.display()))?;
+        let target = destination.join(entry.path().strip_prefix(source)?);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)
+                .with_context(|| format!("Failed to create {}", target.display()))?;
+        } else if entry.file_type().is_file() {
+            fs::copy(entry.path(), &target)
+                .with_context(|| format!("Failed to copy to {}", target.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Total size of the files under `path`.
+pub fn disk_usage(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+pub fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("file");
+    let tmp = path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()));
+    fs::write(&tmp, content).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
+
 pub fn ensure_oav_dir(root: &Path) -> Result<()> {
     fs::create_dir_all(root.join(OAV_DIR)).context("Failed to create .oav directory")?;
     Ok(())
@@ -20,12 +149,16 @@ pub fn ensure_oav_dir(root: &Path) -> Result<()> {
 pub fn prepare_runtime_dirs(root: &Path) -> Result<()> {
     let oav_dir = root.join(OAV_DIR);
     fs::create_dir_all(oav_dir.join("reports").join("lint"))?;
+    fs::create_dir_all(oav_dir.join("reports").join("security"))?;
     fs::create_dir_all(oav_dir.join("reports").join("generate").join("server"))?;
     fs::create_dir_all(oav_dir.join("reports").join("generate").join("client"))?;
     fs::create_dir_all(oav_dir.join("reports").join("compile").join("server"))?;
     fs::create_dir_all(oav_dir.join("reports").join("compile").join("client"))?;
+    fs::create_dir_all(oav_dir.join("reports").join("test").join("server"))?;
+    fs::create_dir_all(oav_dir.join("reports").join("test").join("client"))?;
+    fs::create_dir_all(oav_dir.join("reports").join("smoke").join("server"))?;
+    fs::create_dir_all(oav_dir.join("reports").join("contract").join("server"))?;
     fs::create_dir_all(oav_dir.join("generated"))?;
-    fs::write(oav_dir.join("status.tsv"), "")?;
     Ok(())
 }
 
@@ -86,9 +219,10 @@ fn set_script_permissions(path: &Path) -> Result<()> {
 // Gitignore management
 
 pub fn ensure_gitignore(root: &Path, ignore_config: bool) -> Result<()> {
+    let config_file = crate::config::file_name(root);
     let mut entries = vec![".oav/"];
     if ignore_config {
-        entries.push(".oavc");
+        entries.push(&config_file);
     }
     add_gitignore_entries(root, &entries)
 }
@@ -155,6 +289,52 @@ pub fn normalize_spec_path(root: &Path, spec: &str) -> Result<PathBuf> {
     Ok(relative.to_path_buf())
 }
 
+/// Download the spec at `url` to `dest` (relative to `root`), by default a
+/// file named after the URL's last path segment or `openapi.yaml`. Refuses
+/// to overwrite an existing file and to save anything that isn't an OpenAPI
+/// or Swagger document. Returns the path it was saved to.
+pub fn download_spec(root: &Path, url: &str, dest: Option<&str>) -> Result<String> {
+    let content = http::get(url, Duration::from_secs(60))
+        .with_context(|| format!("Failed to download {url}"))?;
+    let document: serde_yaml::Value = serde_yaml::from_str(&content)
+        .with_context(|| format!("{url} did not return YAML or JSON"))?;
+    if document.get("openapi").is_none() && document.get("swagger").is_none() {
+        bail!("{url} did not return an OpenAPI document");
+    }
+
+    let dest = match dest {
+        Some(dest) => dest.to_string(),
+        None => {
+            let name = url
+                .split(['?', '#'])
+                .next()
+                .and_then(|path| path.rsplit('/').next())
+                .unwrap_or_default();
+            if [".yaml", ".yml", ".json"]
+                .iter()
+                .any(|ext| name.ends_with(ext))
+            {
+                name.to_string()
+            } else {
+                "openapi.yaml".to_string()
+            }
+        }
+    };
+    let path = root.join(&dest);
+    if path.exists() {
+        bail!(
+            "{} already exists. Pass --spec to save the download elsewhere.",
+            path.display()
+        );
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(dest)
+}
+
 pub fn discover_spec(root: &Path) -> Result<Option<String>> {
     for name in ["openapi.yaml", "openapi.yml"] {
         let candidate = root.join(name);
@@ -250,10 +430,11 @@ fn select_spec_from_candidates(candidates: Vec<String>) -> Result<Option<String>
         if trimmed.eq_ignore_ascii_case("q") {
             return Ok(None);
         }
-        if let Ok(choice) = trimmed.parse::<usize>() {
-            if choice >= 1 && choice <= candidates.len() {
-                return Ok(Some(candidates[choice - 1].clone()));
-            }
+        if let Ok(choice) = trimmed.parse::<usize>()
+            && choice >= 1
+            && choice <= candidates.len()
+        {
+            return Ok(Some(candidates[choice - 1].clone()));
         }
         println!("Invalid selection.");
     }
@@ -273,28 +454,6 @@ pub fn write_log_header(log_path: &Path, command_line: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn append_status(
-    root: &Path,
-    stage: &str,
-    scope: &str,
-    target: &str,
-    status: &str,
-    log_path: &Path,
-) -> Result<()> {
-    let status_path = root.join(OAV_DIR).join("status.tsv");
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(status_path)
-        .context("Failed to open status file")?;
-    writeln!(
-        file,
-        "{stage}\t{scope}\t{target}\t{status}\t{}",
-        log_path.display()
-    )?;
-    Ok(())
-}
-
 pub fn append_error(log_path: &Path, message: &str) -> Result<()> {
     let mut file = OpenOptions::new()
         .create(true)
@@ -304,3 +463,83 @@ pub fn append_error(log_path: &Path, message: &str) -> Result<()> {
     writeln!(file, "{message}")?;
     Ok(())
 }
+
+/// Line diff of `old` and `new`, each line prefixed with ` `, `-`, or `+`.
+pub fn line_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    // Longest common subsequence lengths of every pair of suffixes.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut diff = String::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push_str(&format!(" {}\n", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            diff.push_str(&format!("-{}\n", old[i]));
+            i += 1;
+        } else {
+            diff.push_str(&format!("+{}\n", new[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+/// The candidate closest to `word` by edit distance, if it is close enough
+/// to be a likely typo.
+pub fn closest<'a>(word: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = (word.chars().count() / 3).max(2);
+    candidates
+        .into_iter()
+        .map(|candidate| (levenshtein(word, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Parse `90`, `90s`, `10m`, or `1h` into a duration.
+pub fn parse_duration(raw: &str) -> Result<Duration> {
+    let raw = raw.trim();
+    let (number, unit) = raw
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or((raw, ""), |index| raw.split_at(index));
+    let value: u64 = number
+        .parse()
+        .with_context(|| format!("Invalid duration: {raw} (examples: 90s, 10m, 1h)"))?;
+    let seconds = match unit.trim() {
+        "" | "s" => value,
+        "m" => value * 60,
+        "h" => value * 3600,
+        _ => bail!("Invalid duration unit in {raw} (expected s, m, or h)"),
+    };
+    if seconds == 0 {
+        bail!("Duration must be greater than zero: {raw}");
+    }
+    Ok(Duration::from_secs(seconds))
+}
@@ -0,0 +1,48 @@
+;; Test plugin for oav's plugin_dir: requires an `x-owner` key in the spec.
+;; Build with any WAT assembler, e.g. `wat2wasm require-owner.wat`.
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\"x-owner\"")
+  (data (i32.const 16) "[{\"rule\":\"info-owner\",\"severity\":\"error\",\"path\":\"#/info\",\"message\":\"info.x-owner names the owning team and is required\"}]")
+  (data (i32.const 496) "[]")
+  (global $heap (mut i32) (i32.const 1024))
+
+  ;; Bump allocator, growing memory as needed.
+  (func (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (local.get $ptr) (local.get $len)))
+    (if (i32.gt_u (global.get $heap) (i32.mul (memory.size) (i32.const 65536)))
+      (then
+        (drop (memory.grow
+          (i32.add
+            (i32.div_u (i32.sub (global.get $heap) (i32.mul (memory.size) (i32.const 65536))) (i32.const 65536))
+            (i32.const 1))))))
+    (local.get $ptr))
+
+  ;; Whether the needle at 0..9 occurs in ptr..ptr+len.
+  (func $contains (param $ptr i32) (param $len i32) (result i32)
+    (local $i i32)
+    (local $j i32)
+    (block $done
+      (loop $outer
+        (br_if $done (i32.gt_s (i32.add (local.get $i) (i32.const 9)) (local.get $len)))
+        (local.set $j (i32.const 0))
+        (block $mismatch
+          (loop $inner
+            (br_if $mismatch
+              (i32.ne
+                (i32.load8_u (i32.add (i32.add (local.get $ptr) (local.get $i)) (local.get $j)))
+                (i32.load8_u (local.get $j))))
+            (local.set $j (i32.add (local.get $j) (i32.const 1)))
+            (if (i32.eq (local.get $j) (i32.const 9))
+              (then (return (i32.const 1))))
+            (br $inner)))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $outer)))
+    (i32.const 0))
+
+  (func (export "check") (param $ptr i32) (param $len i32) (result i64)
+    (if (result i64) (call $contains (local.get $ptr) (local.get $len))
+      (then (i64.or (i64.shl (i64.const 496) (i64.const 32)) (i64.const 2)))
+      (else (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 121))))))